@@ -1,7 +1,4 @@
-use sb_itchy::{blocks::*, export::export, prelude::*};
-
-type Bib = BlockInputBuilder;
-type Biv = BlockInputValue;
+use sb_itchy::{export::export, prelude::*, scratch};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut project = ProjectBuilder::default();
@@ -31,21 +28,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 Resource::load("examples\\hello_world\\cat.svg")?,
             )));
 
-        #[rustfmt::skip]
-        sprite1
-            .target
-            .add_block_stack(
-                when_flag_clicked()
-                // say "hi mom"
-                .next(say(Bib::value(Biv::String { value: "hi mom".to_owned().into(), })))
-                // wait 1 secs
-                .next(wait(Bib::value(Biv::Number { value: 1.into() })))
-                // forever
-                .next(forever(Some(Bib::stack(
-                    // move_steps 10
-                    move_steps(Bib::value(Biv::Number { value: 10.into() }))
-                ))))
-            );
+        sprite1.target.add_block_stacks(scratch! {
+            when flag clicked {
+                say "hi mom";
+                wait 1;
+                forever {
+                    move 10
+                }
+            }
+        }?);
 
         project.add_sprite(sprite1);
     }
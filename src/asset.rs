@@ -1,11 +1,44 @@
 use sb_sbity::asset::{Asset, Costume, Sound};
 
-use crate::resource::Resource;
+use crate::{
+    audio::{self, SoundError, SoundInfo},
+    image::{self, ImageError, ImageFormat},
+    resource::Resource,
+};
+
+/// How many of the image's pixels is one pixel on the stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BitmapResolution {
+    #[default]
+    Single,
+    /// Bitmaps drawn at the editor's resolution, which is twice the stage's
+    Double,
+}
+
+impl BitmapResolution {
+    /// None when it's not 1 or 2, the only ones Scratch has
+    pub fn from_u64(value: u64) -> Option<BitmapResolution> {
+        match value {
+            1 => Some(BitmapResolution::Single),
+            2 => Some(BitmapResolution::Double),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> u64 {
+        match self {
+            BitmapResolution::Single => 1,
+            BitmapResolution::Double => 2,
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CostumeBuilder {
+    /// In the image's pixels from its top left
     pub rotation_center_x: i64,
     pub rotation_center_y: i64,
+    pub bitmap_resolution: BitmapResolution,
     pub asset: AssetBuilder,
 }
 
@@ -15,9 +48,50 @@ impl CostumeBuilder {
             asset: asset_builder,
             rotation_center_x: 0,
             rotation_center_y: 0,
+            bitmap_resolution: BitmapResolution::Single,
         }
     }
 
+    /// Costume that rotates around the center of the image, which is read from `resource`.
+    /// See [`crate::image`] for the formats it can read.
+    pub fn from_resource<S: Into<String>>(
+        name: S,
+        resource: Resource,
+    ) -> Result<CostumeBuilder, ImageError> {
+        let (_, size) = image::read_size(&resource)?;
+        let mut costume_builder = CostumeBuilder::new(AssetBuilder::new(name, resource));
+        costume_builder.set_rotation_center(
+            (size.width / 2.).round() as i64,
+            (size.height / 2.).round() as i64,
+        );
+        Ok(costume_builder)
+    }
+
+    /// Rotation center is in the image's pixels so it stays at the same spot on the image.
+    /// SVG is always [`BitmapResolution::Single`] in Scratch, see [`CostumeBuilder::bitmap_resolution`].
+    pub fn set_bitmap_resolution(&mut self, bitmap_resolution: BitmapResolution) -> &mut Self {
+        self.bitmap_resolution = bitmap_resolution;
+        self
+    }
+
+    /// Resolution that the costume is built with, [`BitmapResolution::Double`] is ignored for SVG
+    pub fn bitmap_resolution(&self) -> BitmapResolution {
+        let is_vector = ImageFormat::from_extension(self.asset.resource.extension())
+            .is_some_and(|format| !format.is_bitmap());
+        match is_vector {
+            true => BitmapResolution::Single,
+            false => self.bitmap_resolution,
+        }
+    }
+
+    /// Rotation center in the stage's pixels, it's scaled by the bitmap resolution
+    pub fn set_rotation_center_on_stage(&mut self, x: f64, y: f64) -> &mut Self {
+        let resolution = self.bitmap_resolution().as_u64() as f64;
+        self.rotation_center_x = (x * resolution).round() as i64;
+        self.rotation_center_y = (y * resolution).round() as i64;
+        self
+    }
+
     pub fn set_rotation_center(&mut self, x: i64, y: i64) -> &mut Self {
         self.rotation_center_x = x;
         self.rotation_center_y = y;
@@ -40,30 +114,63 @@ impl CostumeBuilder {
     }
 
     pub fn build(self, file_buff: &mut Vec<Resource>) -> Costume {
+        let bitmap_resolution = self.bitmap_resolution();
         let CostumeBuilder {
             rotation_center_x,
             rotation_center_y,
             asset,
+            ..
         } = self;
         Costume {
             rotation_center_x: rotation_center_x.into(),
             rotation_center_y: rotation_center_y.into(),
-            bitmap_resolution: Some(1),
+            bitmap_resolution: Some(bitmap_resolution.as_u64()),
             asset: asset.build(file_buff),
         }
     }
 }
 
-/// Not really sure what to do here yet
+/// Use [`SoundBuilder::from_resource`] so the rate and sample count match the file,
+/// otherwise the sound plays at the wrong speed or gets cut short.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SoundBuilder {
+    /// Samples per second
     pub rate: u64,
     pub sample_count: u64,
+    /// `"adpcm"` for IMA ADPCM WAV
     pub format: Option<String>,
     pub asset: AssetBuilder,
 }
 
 impl SoundBuilder {
+    pub fn new(asset_builder: AssetBuilder, rate: u64, sample_count: u64) -> SoundBuilder {
+        SoundBuilder {
+            rate,
+            sample_count,
+            format: None,
+            asset: asset_builder,
+        }
+    }
+
+    /// Sound with the rate, sample count and format read from `resource`.
+    /// See [`crate::audio`] for the formats it can read.
+    pub fn from_resource<S: Into<String>>(
+        name: S,
+        resource: Resource,
+    ) -> Result<SoundBuilder, SoundError> {
+        let SoundInfo {
+            rate,
+            sample_count,
+            format,
+        } = audio::read_info(&resource)?;
+        Ok(SoundBuilder {
+            rate,
+            sample_count,
+            format,
+            asset: AssetBuilder::new(name, resource),
+        })
+    }
+
     pub fn rate(&mut self, rate: u64) -> &mut Self {
         self.rate = rate;
         self
@@ -114,17 +221,46 @@ impl AssetBuilder {
         }
     }
 
+    /// Resource isn't pushed when the same file is already in `res_buf`
     pub fn build(self, res_buf: &mut Vec<Resource>) -> Asset {
         let AssetBuilder { name, mut resource } = self;
         let extension = resource.extension().to_owned();
-        let md5_hash = resource.get_or_compute_md5_hash();
+        let md5_hash = resource.get_or_compute_md5_hash().to_owned();
+        let md5ext = resource.md5ext();
         let asset = Asset {
-            asset_id: md5_hash.to_owned(),
+            asset_id: md5_hash,
             name,
-            md5ext: Some(md5_hash.to_owned() + "." + &extension),
+            md5ext: Some(md5ext.clone()),
             data_format: extension,
         };
-        res_buf.push(resource);
+        if !res_buf.iter_mut().any(|res| res.md5ext() == md5ext) {
+            res_buf.push(resource);
+        }
         asset
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn costume(extension: &str) -> CostumeBuilder {
+        let resource = Resource::new(extension.to_owned(), vec![]).unwrap();
+        let mut costume = CostumeBuilder::new(AssetBuilder::new("costume", resource));
+        costume
+            .set_bitmap_resolution(BitmapResolution::Double)
+            .set_rotation_center_on_stage(10., 5.);
+        costume
+    }
+
+    #[test]
+    fn svg_is_always_single_resolution() {
+        let svg = costume("svg").build(&mut vec![]);
+        assert_eq!(svg.bitmap_resolution, Some(1));
+        assert_eq!(svg.rotation_center_x, 10_i64.into());
+
+        let png = costume("png").build(&mut vec![]);
+        assert_eq!(png.bitmap_resolution, Some(2));
+        assert_eq!(png.rotation_center_x, 20_i64.into());
+    }
+}
@@ -0,0 +1,446 @@
+//! Reading the sample rate and length of sounds from their headers
+//!
+//! Only what Scratch can load as a sound is supported: WAV in PCM or IMA ADPCM, and MP3.
+//! Nothing but the headers is decoded.
+
+use std::io::{Error as IoError, Read};
+
+use crate::resource::Resource;
+
+#[derive(Debug)]
+pub enum SoundError {
+    Io(IoError),
+    /// Extension of a file that isn't a sound Scratch supports
+    UnsupportedFormat(String),
+    /// Codec inside the file that Scratch can't play, ex. `"WAV format tag 0x0002"`
+    UnsupportedCodec(String),
+    /// Header of this format can't be read
+    Malformed(&'static str),
+}
+
+impl From<IoError> for SoundError {
+    fn from(value: IoError) -> Self {
+        SoundError::Io(value)
+    }
+}
+
+impl std::error::Error for SoundError {}
+
+impl std::fmt::Display for SoundError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SoundError::Io(io) => write!(f, "{io}"),
+            SoundError::UnsupportedFormat(extension) => {
+                write!(f, "`{extension}` isn't a sound Scratch supports")
+            }
+            SoundError::UnsupportedCodec(codec) => write!(f, "{codec} isn't supported by Scratch"),
+            SoundError::Malformed(format) => {
+                write!(f, "can't read the header of the {format} sound")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoundInfo {
+    pub rate: u64,
+    pub sample_count: u64,
+    /// `"adpcm"` for IMA ADPCM WAV, None otherwise. Same as `format` in `project.json`.
+    pub format: Option<String>,
+}
+
+/// Info of the sound from the resource's extension and its headers
+pub fn read_info(resource: &Resource) -> Result<SoundInfo, SoundError> {
+    let mut content = vec![];
+    match resource.extension().to_ascii_lowercase().as_str() {
+        "wav" => {
+            resource.reader()?.read_to_end(&mut content)?;
+            wav_info(&content)
+        }
+        "mp3" => {
+            resource.reader()?.read_to_end(&mut content)?;
+            mp3_info(&content)
+        }
+        extension => Err(SoundError::UnsupportedFormat(extension.to_owned())),
+    }
+}
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_IMA_ADPCM: u16 = 0x0011;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+fn wav_info(content: &[u8]) -> Result<SoundInfo, SoundError> {
+    let malformed = || SoundError::Malformed("WAV");
+    if !content.starts_with(b"RIFF") || content.get(8..12) != Some(b"WAVE".as_slice()) {
+        return Err(malformed());
+    }
+    let mut fmt = None;
+    let mut fact_sample_count = None;
+    let mut data_size = None;
+    let mut i = 12;
+    while let Some(header) = content.get(i..i + 8) {
+        let size = u32_le(&header[4..8]) as usize;
+        // Last chunk is often cut short, its size is taken as is
+        let chunk = &content[i + 8..content.len().min(i + 8 + size)];
+        match &header[..4] {
+            b"fmt " => fmt = Some(chunk),
+            b"fact" if chunk.len() >= 4 => fact_sample_count = Some(u32_le(&chunk[..4]) as u64),
+            b"data" => data_size = Some(chunk.len() as u64),
+            _ => {}
+        }
+        // Chunks are padded to even size
+        i += 8 + size + size % 2;
+    }
+    let (Some(fmt), Some(data_size)) = (fmt, data_size) else {
+        return Err(malformed());
+    };
+    if fmt.len() < 16 {
+        return Err(malformed());
+    }
+    let mut format_tag = u16_le(&fmt[0..2]);
+    let channels = u16_le(&fmt[2..4]) as u64;
+    let rate = u32_le(&fmt[4..8]) as u64;
+    let block_align = u16_le(&fmt[12..14]) as u64;
+    if format_tag == WAVE_FORMAT_EXTENSIBLE {
+        // Sub format GUID starts with the format tag
+        format_tag = fmt.get(24..26).map(u16_le).ok_or_else(malformed)?;
+    }
+    if channels == 0 || block_align == 0 {
+        return Err(malformed());
+    }
+    match format_tag {
+        WAVE_FORMAT_PCM | WAVE_FORMAT_IEEE_FLOAT => Ok(SoundInfo {
+            rate,
+            sample_count: data_size / block_align,
+            format: None,
+        }),
+        WAVE_FORMAT_IMA_ADPCM => {
+            if channels != 1 {
+                return Err(SoundError::UnsupportedCodec(format!(
+                    "IMA ADPCM WAV with {channels} channels"
+                )));
+            }
+            // Each block starts with a header holding the first sample, then 2 samples a byte
+            let samples_per_block = match fmt.get(18..20) {
+                Some(samples_per_block) => u16_le(samples_per_block) as u64,
+                None => block_align.checked_sub(4).ok_or_else(malformed)? * 2 + 1,
+            };
+            let sample_count = fact_sample_count.unwrap_or_else(|| {
+                let last_block = data_size % block_align;
+                data_size / block_align * samples_per_block
+                    + match last_block {
+                        0..=3 => 0,
+                        _ => (last_block - 4) * 2 + 1,
+                    }
+            });
+            Ok(SoundInfo {
+                rate,
+                sample_count,
+                format: Some("adpcm".to_owned()),
+            })
+        }
+        other => Err(SoundError::UnsupportedCodec(format!(
+            "WAV format tag {other:#06x}"
+        ))),
+    }
+}
+
+/// Every frame is counted since the bitrate can change between frames
+fn mp3_info(content: &[u8]) -> Result<SoundInfo, SoundError> {
+    const VBR_TAGS: [&[u8]; 2] = [b"Xing", b"Info"];
+    let mut i = 0;
+    // ID3v2 tag goes before the first frame, its size is 7 bits a byte
+    if content.starts_with(b"ID3") && content.len() >= 10 {
+        let size = content[6..10]
+            .iter()
+            .fold(0, |size, byte| (size << 7) | (*byte as usize & 0x7F));
+        let has_footer = content[5] & 0x10 != 0;
+        i = 10 + size + if has_footer { 10 } else { 0 };
+    }
+    let first_frame = i;
+    let mut rate = None;
+    let mut sample_count = 0;
+    while let Some(header) = content.get(i..i + 4) {
+        let Some(frame) = mp3_frame(header)? else {
+            break;
+        };
+        if *rate.get_or_insert(frame.rate) != frame.rate {
+            return Err(SoundError::Malformed("MP3"));
+        }
+        // Encoders put the Xing or Info tag in a first frame that's silent and not played
+        let vbr_tag = content.get(i + frame.vbr_tag_offset..i + frame.vbr_tag_offset + 4);
+        if !(i == first_frame && vbr_tag.is_some_and(|tag| VBR_TAGS.contains(&tag))) {
+            sample_count += frame.sample_count;
+        }
+        i += frame.length;
+    }
+    match rate {
+        Some(rate) => Ok(SoundInfo {
+            rate,
+            sample_count,
+            format: None,
+        }),
+        None => Err(SoundError::Malformed("MP3")),
+    }
+}
+
+struct Mp3Frame {
+    rate: u64,
+    sample_count: u64,
+    length: usize,
+    /// Where a Xing or Info tag would be, right after the CRC and side info
+    vbr_tag_offset: usize,
+}
+
+/// None when `header` isn't a frame header, ex. ID3v1 tag at the end
+fn mp3_frame(header: &[u8]) -> Result<Option<Mp3Frame>, SoundError> {
+    #[rustfmt::skip]
+    const BITRATES_V1: [u64; 15] = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320];
+    #[rustfmt::skip]
+    const BITRATES_V2: [u64; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+    if header[0] != 0xFF || header[1] & 0xE0 != 0xE0 {
+        return Ok(None);
+    }
+    let version = (header[1] >> 3) & 0b11;
+    let layer = (header[1] >> 1) & 0b11;
+    let bitrate_index = (header[2] >> 4) as usize;
+    let rate_index = ((header[2] >> 2) & 0b11) as usize;
+    let padding = ((header[2] >> 1) & 1) as usize;
+    let has_crc = header[1] & 1 == 0;
+    let is_mono = header[3] >> 6 == 0b11;
+    if version == 0b01 || rate_index == 3 || bitrate_index == 15 {
+        return Ok(None);
+    }
+    if layer != 0b01 {
+        let layer = match layer {
+            0b11 => "I",
+            0b10 => "II",
+            _ => return Ok(None),
+        };
+        return Err(SoundError::UnsupportedCodec(format!("MPEG layer {layer}")));
+    }
+    if bitrate_index == 0 {
+        return Err(SoundError::UnsupportedCodec("free bitrate MP3".to_owned()));
+    }
+    let is_v1 = version == 0b11;
+    let rate = match version {
+        0b11 => [44100, 48000, 32000][rate_index],
+        0b10 => [22050, 24000, 16000][rate_index],
+        _ => [11025, 12000, 8000][rate_index],
+    };
+    let bitrate = match is_v1 {
+        true => BITRATES_V1[bitrate_index],
+        false => BITRATES_V2[bitrate_index],
+    } * 1000;
+    let sample_count = if is_v1 { 1152 } else { 576 };
+    let side_info_length = match (is_v1, is_mono) {
+        (true, false) => 32,
+        (true, true) | (false, false) => 17,
+        (false, true) => 9,
+    };
+    Ok(Some(Mp3Frame {
+        rate,
+        sample_count,
+        length: (sample_count / 8 * bitrate / rate) as usize + padding,
+        vbr_tag_offset: 4 + if has_crc { 2 } else { 0 } + side_info_length,
+    }))
+}
+
+fn u16_le(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes([bytes[0], bytes[1]])
+}
+
+fn u32_le(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8], content: &[u8]) -> Vec<u8> {
+        let mut chunk = id.to_vec();
+        chunk.extend((content.len() as u32).to_le_bytes());
+        chunk.extend(content);
+        chunk
+    }
+
+    fn wav(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let chunks = chunks.concat();
+        let mut wav = b"RIFF".to_vec();
+        wav.extend((chunks.len() as u32 + 4).to_le_bytes());
+        wav.extend(b"WAVE");
+        wav.extend(chunks);
+        wav
+    }
+
+    fn fmt(format_tag: u16, channels: u16, rate: u32, block_align: u16, extra: &[u8]) -> Vec<u8> {
+        let mut fmt = vec![];
+        fmt.extend(format_tag.to_le_bytes());
+        fmt.extend(channels.to_le_bytes());
+        fmt.extend(rate.to_le_bytes());
+        fmt.extend((rate * block_align as u32).to_le_bytes());
+        fmt.extend(block_align.to_le_bytes());
+        fmt.extend(16_u16.to_le_bytes());
+        if !extra.is_empty() {
+            fmt.extend((extra.len() as u16).to_le_bytes());
+            fmt.extend(extra);
+        }
+        chunk(b"fmt ", &fmt)
+    }
+
+    /// MPEG1 layer III frame at 128 kbps and 44100 Hz, 417 bytes long
+    fn frame_bytes(mono: bool, vbr_tag: Option<&[u8]>) -> Vec<u8> {
+        let mut frame = vec![0; 417];
+        frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, if mono { 0xC0 } else { 0x00 }]);
+        if let Some(tag) = vbr_tag {
+            let offset = if mono { 4 + 17 } else { 4 + 32 };
+            frame[offset..offset + 4].copy_from_slice(tag);
+        }
+        frame
+    }
+
+    fn id3v2(size: u8) -> Vec<u8> {
+        let mut tag = b"ID3\x04\x00\x00\x00\x00\x00".to_vec();
+        tag.push(size);
+        tag.extend(vec![0; size as usize]);
+        tag
+    }
+
+    #[test]
+    fn pcm_wav() {
+        let content = wav(&[
+            fmt(WAVE_FORMAT_PCM, 2, 44100, 4, &[]),
+            chunk(b"LIST", b"INFO"),
+            chunk(b"data", &[0; 400]),
+        ]);
+        let info = wav_info(&content).unwrap();
+        assert_eq!(
+            info,
+            SoundInfo {
+                rate: 44100,
+                sample_count: 100,
+                format: None
+            }
+        );
+    }
+
+    #[test]
+    fn adpcm_wav() {
+        let samples_per_block = 505_u16.to_le_bytes();
+        let adpcm_fmt = fmt(WAVE_FORMAT_IMA_ADPCM, 1, 22050, 256, &samples_per_block);
+        let with_fact = wav(&[
+            adpcm_fmt.clone(),
+            chunk(b"fact", &1000_u32.to_le_bytes()),
+            chunk(b"data", &[0; 612]),
+        ]);
+        let info = wav_info(&with_fact).unwrap();
+        assert_eq!(info.rate, 22050);
+        assert_eq!(info.sample_count, 1000);
+        assert_eq!(info.format.as_deref(), Some("adpcm"));
+
+        // 2 full blocks and a last one with 100 bytes after its header
+        let without_fact = wav(&[adpcm_fmt, chunk(b"data", &[0; 616])]);
+        assert_eq!(wav_info(&without_fact).unwrap().sample_count, 505 * 2 + 201);
+
+        // Samples per block from the block align when fmt doesn't have it
+        let short_fmt = wav(&[
+            fmt(WAVE_FORMAT_IMA_ADPCM, 1, 22050, 256, &[]),
+            chunk(b"data", &[0; 512]),
+        ]);
+        assert_eq!(wav_info(&short_fmt).unwrap().sample_count, 505 * 2);
+    }
+
+    #[test]
+    fn adpcm_block_align_too_small() {
+        let content = wav(&[
+            fmt(WAVE_FORMAT_IMA_ADPCM, 1, 22050, 2, &[]),
+            chunk(b"data", &[0; 16]),
+        ]);
+        assert!(matches!(
+            wav_info(&content),
+            Err(SoundError::Malformed("WAV"))
+        ));
+    }
+
+    #[test]
+    fn extensible_wav() {
+        // Valid bits, channel mask, then the sub format GUID
+        let mut extra = vec![];
+        extra.extend(16_u16.to_le_bytes());
+        extra.extend(3_u32.to_le_bytes());
+        extra.extend(WAVE_FORMAT_PCM.to_le_bytes());
+        extra.extend([0; 14]);
+        let content = wav(&[
+            fmt(WAVE_FORMAT_EXTENSIBLE, 2, 48000, 4, &extra),
+            chunk(b"data", &[0; 4800]),
+        ]);
+        let info = wav_info(&content).unwrap();
+        assert_eq!(info.rate, 48000);
+        assert_eq!(info.sample_count, 1200);
+    }
+
+    #[test]
+    fn unsupported_wav_codec() {
+        let content = wav(&[fmt(0x0002, 1, 22050, 256, &[]), chunk(b"data", &[0; 512])]);
+        assert!(matches!(
+            wav_info(&content),
+            Err(SoundError::UnsupportedCodec(codec)) if codec == "WAV format tag 0x0002"
+        ));
+        let stereo_adpcm = wav(&[
+            fmt(WAVE_FORMAT_IMA_ADPCM, 2, 22050, 512, &[]),
+            chunk(b"data", &[0; 512]),
+        ]);
+        assert!(matches!(
+            wav_info(&stereo_adpcm),
+            Err(SoundError::UnsupportedCodec(_))
+        ));
+    }
+
+    #[test]
+    fn mp3_with_id3v2() {
+        let content = [
+            id3v2(20),
+            frame_bytes(false, None),
+            frame_bytes(false, None),
+            frame_bytes(false, None),
+        ]
+        .concat();
+        let info = mp3_info(&content).unwrap();
+        assert_eq!(
+            info,
+            SoundInfo {
+                rate: 44100,
+                sample_count: 1152 * 3,
+                format: None
+            }
+        );
+    }
+
+    #[test]
+    fn mp3_vbr_tag_frame_is_skipped() {
+        let xing = [
+            id3v2(20),
+            frame_bytes(false, Some(b"Xing")),
+            frame_bytes(false, None),
+            frame_bytes(false, None),
+        ]
+        .concat();
+        assert_eq!(mp3_info(&xing).unwrap().sample_count, 1152 * 2);
+        let info = [frame_bytes(true, Some(b"Info")), frame_bytes(true, None)].concat();
+        assert_eq!(mp3_info(&info).unwrap().sample_count, 1152);
+    }
+
+    #[test]
+    fn unsupported_mp3_layer() {
+        let mut frame = frame_bytes(false, None);
+        // Layer II
+        frame[1] = 0xFD;
+        assert!(matches!(
+            mp3_info(&frame),
+            Err(SoundError::UnsupportedCodec(codec)) if codec == "MPEG layer II"
+        ));
+    }
+}
@@ -10,7 +10,14 @@ use sb_sbity::{
     value::OpCode,
 };
 
-use crate::{build_context::TargetContext, comment::CommentBuilder, stack::StackBuilder, uid::Uid};
+use crate::{
+    build_context::TargetContext,
+    comment::CommentBuilder,
+    value::{number_to_f64, value_to_string},
+    opcode::StandardOpCode,
+    stack::StackBuilder,
+    uid::{Uid, UidGenerator},
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum StackOrValue {
@@ -44,9 +51,14 @@ impl BlockInputBuilder {
 
     /// Shortcut for
     /// ```
-    /// BlockInputBuilder::new()
-    ///     .shadow(ShadowInputType::Shadow)
-    ///     .input(Some(StackOrValue::Value(value)))
+    /// # use sb_itchy::block::{BlockInputBuilder, StackOrValue};
+    /// # use sb_sbity::{block::{BlockInputValue, ShadowInputType}, value::Value};
+    /// # let value = BlockInputValue::String { value: Value::Text("hi".to_owned()) };
+    /// let mut input = BlockInputBuilder::new();
+    /// input
+    ///     .set_shadow(ShadowInputType::Shadow)
+    ///     .add_input(Some(StackOrValue::Value(value.clone())));
+    /// assert_eq!(input, BlockInputBuilder::value(value));
     /// ```
     pub fn value(value: BlockInputValue) -> Self {
         let mut b = BlockInputBuilder::new();
@@ -57,9 +69,13 @@ impl BlockInputBuilder {
 
     /// Shortcut for
     /// ```
-    /// BlockInputBuilder::new()
-    ///     .shadow(ShadowInputType::NoShadow)
-    ///     .input(Some(StackOrValue::Stack(stack)))
+    /// # use sb_itchy::{block::{BlockInputBuilder, StackOrValue}, blocks::answer};
+    /// # use sb_sbity::block::ShadowInputType;
+    /// let mut input = BlockInputBuilder::new();
+    /// input
+    ///     .set_shadow(ShadowInputType::NoShadow)
+    ///     .add_input(Some(StackOrValue::Stack(answer())));
+    /// assert_eq!(input, BlockInputBuilder::stack(answer()));
     /// ```
     pub fn stack(stack: StackBuilder) -> Self {
         let mut b = BlockInputBuilder::new();
@@ -70,10 +86,18 @@ impl BlockInputBuilder {
 
     /// Shortcut for
     /// ```
-    /// BlockInputBuilder::new()
-    ///     .shadow(ShadowInputType::ShadowObscured)
-    ///     .input(Some(StackOrValue::Stack(stack)))
-    ///     .input(Some(StackOrValue::Value(value)))
+    /// # use sb_itchy::{block::{BlockInputBuilder, StackOrValue}, blocks::answer};
+    /// # use sb_sbity::{block::{BlockInputValue, ShadowInputType}, value::Value};
+    /// # let value = BlockInputValue::String { value: Value::Text("hi".to_owned()) };
+    /// let mut input = BlockInputBuilder::new();
+    /// input
+    ///     .set_shadow(ShadowInputType::ShadowObscured)
+    ///     .add_input(Some(StackOrValue::Stack(answer())))
+    ///     .add_input(Some(StackOrValue::Value(value.clone())));
+    /// assert_eq!(
+    ///     input,
+    ///     BlockInputBuilder::stack_with_value_obscured(answer(), value)
+    /// );
     /// ```
     pub fn stack_with_value_obscured(stack: StackBuilder, value: BlockInputValue) -> Self {
         let mut b = BlockInputBuilder::new();
@@ -83,42 +107,56 @@ impl BlockInputBuilder {
         b
     }
 
+    /// Broadcast value's id is looked up from its name.
     pub fn build(
         self,
         this_block_uid: &Uid,
         comment_buff: &mut HashMap<Uid, Comment>,
         final_stack: &mut HashMap<Uid, Block>,
         target_context: &TargetContext,
+        uid_gen: &mut UidGenerator,
     ) -> BlockInput {
         let BlockInputBuilder { shadow, values } = self;
         let mut values_b: Vec<Option<UidOrValue>> = vec![];
         for value in values {
             match value {
+                Some(StackOrValue::Value(BlockInputValue::Broadcast { name, id })) => {
+                    let id = match target_context.all_broadcasts.get(&name) {
+                        Some(uid) => uid.clone().into_inner(),
+                        None => id,
+                    };
+                    values_b.push(Some(UidOrValue::Value(BlockInputValue::Broadcast {
+                        name,
+                        id,
+                    })))
+                }
                 Some(StackOrValue::Value(v)) => values_b.push(Some(UidOrValue::Value(v))),
                 Some(StackOrValue::Stack(s)) => {
-                    let first_block_uid = Uid::generate();
-                    let mut s_builded = s.build(&first_block_uid, comment_buff, target_context);
+                    let first_block_uid = uid_gen.generate();
+                    let mut s_builded =
+                        s.build(&first_block_uid, comment_buff, target_context, uid_gen);
                     let first_block = s_builded.get_mut(&first_block_uid).unwrap();
-                    match first_block {
+                    let value_b = match first_block {
                         Block::Normal(n) => {
                             n.parent = Some(this_block_uid.clone().into_inner());
                             n.top_level = false;
                             n.x = None;
                             n.y = None;
+                            UidOrValue::Uid(first_block_uid.into_inner())
                         }
                         Block::VarList(_) => {
                             let Block::VarList(vl) = s_builded.remove(&first_block_uid).unwrap() else {
                                 unreachable!()
                             };
                             let BlockVarListReporterTop { kind, name, id, .. } = vl;
-                            values_b.push(Some(UidOrValue::Value(match kind {
+                            UidOrValue::Value(match kind {
                                 ListOrVariable::Variable => BlockInputValue::Variable { name, id },
                                 ListOrVariable::List => BlockInputValue::List { name, id },
-                            })))
+                            })
                         }
-                    }
+                    };
                     final_stack.extend(s_builded);
-                    values_b.push(Some(UidOrValue::Uid(first_block_uid.into_inner())))
+                    values_b.push(Some(value_b))
                 }
                 None => values_b.push(None),
             }
@@ -128,6 +166,47 @@ impl BlockInputBuilder {
             inputs: values_b,
         }
     }
+
+    /// Reverse of [`BlockInputBuilder::build`].
+    /// Blocks that `input` points to are looked up in `blocks` and reconstructed into [`StackBuilder`].
+    pub fn from_block_input(
+        input: &BlockInput,
+        blocks: &HashMap<String, Block>,
+        comments: &HashMap<String, Comment>,
+        target_context: &TargetContext,
+    ) -> BlockInputBuilder {
+        let varlist = |kind, name: &String, id: &String| {
+            StackBuilder::start_varlist(BlockVarListBuilder::from_reference(
+                kind,
+                name,
+                id,
+                target_context,
+            ))
+        };
+        let values = input
+            .inputs
+            .iter()
+            .map(|value| match value {
+                None => None,
+                Some(UidOrValue::Uid(uid)) => {
+                    let stack =
+                        StackBuilder::from_blocks(&Uid::new(uid), blocks, comments, target_context);
+                    Some(StackOrValue::Stack(stack))
+                }
+                Some(UidOrValue::Value(BlockInputValue::Variable { name, id })) => Some(
+                    StackOrValue::Stack(varlist(ListOrVariable::Variable, name, id)),
+                ),
+                Some(UidOrValue::Value(BlockInputValue::List { name, id })) => {
+                    Some(StackOrValue::Stack(varlist(ListOrVariable::List, name, id)))
+                }
+                Some(UidOrValue::Value(value)) => Some(StackOrValue::Value(value.clone())),
+            })
+            .collect();
+        BlockInputBuilder {
+            shadow: input.shadow.clone(),
+            values,
+        }
+    }
 }
 
 impl Default for BlockInputBuilder {
@@ -160,12 +239,12 @@ impl BlockNormalBuilder {
         }
     }
 
-    pub fn add_input<K: Into<String>>(
+    pub fn add_input<K: Into<String>, I: Into<BlockInputBuilder>>(
         &mut self,
         key: K,
-        block_input_builder: BlockInputBuilder,
+        block_input_builder: I,
     ) -> &mut Self {
-        self.inputs.insert(key.into(), block_input_builder);
+        self.inputs.insert(key.into(), block_input_builder.into());
         self
     }
 
@@ -224,12 +303,53 @@ impl BlockNormalBuilder {
         self
     }
 
-    fn build(
+    pub fn opcode(&self) -> &OpCode {
+        &self.opcode
+    }
+
+    pub fn comment(&self) -> Option<&CommentBuilder> {
+        self.comment.as_ref()
+    }
+
+    pub fn comment_mut(&mut self) -> Option<&mut CommentBuilder> {
+        self.comment.as_mut()
+    }
+
+    pub fn inputs(&self) -> &HashMap<String, BlockInputBuilder> {
+        &self.inputs
+    }
+
+    pub fn inputs_mut(&mut self) -> &mut HashMap<String, BlockInputBuilder> {
+        &mut self.inputs
+    }
+
+    pub fn fields(&self) -> &HashMap<String, BlockFieldBuilder> {
+        &self.fields
+    }
+
+    pub fn mutation(&self) -> Option<&BlockMutation> {
+        self.mutation.as_ref()
+    }
+
+    pub fn is_shadow(&self) -> bool {
+        self.shadow
+    }
+
+    pub fn x(&self) -> Option<f64> {
+        self.x
+    }
+
+    pub fn y(&self) -> Option<f64> {
+        self.y
+    }
+
+    pub(crate) fn build(
         self,
         my_uid: &Uid,
         comment_buff: &mut HashMap<Uid, Comment>,
         final_stack: &mut HashMap<Uid, Block>,
         target_context: &TargetContext,
+        uid_gen: &mut UidGenerator,
     ) -> BlockNormal {
         let BlockNormalBuilder {
             opcode,
@@ -245,12 +365,15 @@ impl BlockNormalBuilder {
         // for (key, input) in inputs {
         //     inputs_b.insert(key, input.build(comment_buff, final_stack, &my_uid));
         // }
+        // Inputs are built in order of their key so uids are generated in the same order every build
+        let mut inputs: Vec<(String, BlockInputBuilder)> = inputs.into_iter().collect();
+        inputs.sort_by(|(a, _), (b, _)| a.cmp(b));
         let inputs: HashMap<String, BlockInput> = inputs
             .into_iter()
             .map(|(key, input)| {
                 (
                     key,
-                    input.build(my_uid, comment_buff, final_stack, target_context),
+                    input.build(my_uid, comment_buff, final_stack, target_context, uid_gen),
                 )
             })
             .collect();
@@ -260,7 +383,7 @@ impl BlockNormalBuilder {
             .collect();
         let comment = match comment {
             Some(comment) => {
-                let comment_uid = Uid::generate();
+                let comment_uid = uid_gen.generate();
                 let mut comment = comment.build();
                 comment.block_id = Some(my_uid.clone().into_inner());
                 comment_buff.insert(comment_uid.clone(), comment);
@@ -283,6 +406,53 @@ impl BlockNormalBuilder {
             y: y.map(|y| y.into()),
         }
     }
+
+    /// Reverse of [`BlockNormalBuilder::build`].
+    /// Links to other blocks (`next` and `parent`) are not kept; [`StackBuilder::from_blocks`] follows them instead.
+    pub fn from_block_normal(
+        block: &BlockNormal,
+        blocks: &HashMap<String, Block>,
+        comments: &HashMap<String, Comment>,
+        target_context: &TargetContext,
+    ) -> BlockNormalBuilder {
+        let inputs = block
+            .inputs
+            .0
+            .iter()
+            .map(|(key, input)| {
+                (
+                    key.clone(),
+                    BlockInputBuilder::from_block_input(input, blocks, comments, target_context),
+                )
+            })
+            .collect();
+        let fields = block
+            .fields
+            .0
+            .iter()
+            .map(|(key, field)| {
+                (
+                    key.clone(),
+                    BlockFieldBuilder::from_block_field(field, target_context),
+                )
+            })
+            .collect();
+        let comment = block
+            .comment
+            .as_ref()
+            .and_then(|comment_uid| comments.get(comment_uid))
+            .map(|comment| CommentBuilder::from_comment(comment.clone()));
+        BlockNormalBuilder {
+            opcode: block.opcode.clone(),
+            comment,
+            inputs,
+            fields,
+            mutation: block.mutation.clone(),
+            shadow: block.shadow,
+            x: block.x.as_ref().map(number_to_f64),
+            y: block.y.as_ref().map(number_to_f64),
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -295,6 +465,11 @@ pub enum FieldKind {
     GlobalVariable,
     SpriteList,
     GlobalList,
+    /// Variable of the sprite or the stage, whichever has it. See [`TargetContext::resolve_var`].
+    /// [`crate::export::export`] refuses one that neither has.
+    Variable,
+    /// List of the sprite or the stage, whichever has it. See [`TargetContext::resolve_list`].
+    List,
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -334,25 +509,64 @@ impl BlockFieldBuilder {
             FieldKind::NoRef => return BlockField::NoId { value },
             FieldKind::NoRefMaybe => return BlockField::WithId { value, id: None },
 
-            FieldKind::Broadcast => target_context.all_broadcasts,
-            FieldKind::SpriteVariable => target_context.this_sprite_vars,
-            FieldKind::GlobalVariable => target_context.global_vars,
-            FieldKind::SpriteList => target_context.this_sprite_lists,
-            FieldKind::GlobalList => target_context.global_lists,
+            FieldKind::Broadcast => target_context.all_broadcasts.get(value_str),
+            FieldKind::SpriteVariable => target_context.this_sprite_vars.get(value_str),
+            FieldKind::GlobalVariable => target_context.global_vars.get(value_str),
+            FieldKind::SpriteList => target_context.this_sprite_lists.get(value_str),
+            FieldKind::GlobalList => target_context.global_lists.get(value_str),
+            FieldKind::Variable => target_context.resolve_var(value_str),
+            FieldKind::List => target_context.resolve_list(value_str),
         }
-        .get(value_str).cloned()
+        .cloned()
         .unwrap_or_else(|| Uid::new("__unknown__"));
         BlockField::WithId {
             value,
             id: Some(id.into_inner()),
         }
     }
+
+    /// Reverse of [`BlockFieldBuilder::build`].
+    /// [`FieldKind`] is guessed from where the field's id is found in `target_context`.
+    pub fn from_block_field(
+        field: &BlockField,
+        target_context: &TargetContext,
+    ) -> BlockFieldBuilder {
+        let (value, id) = match field {
+            BlockField::NoId { value } => {
+                return BlockFieldBuilder::new_with_kind(value_to_string(value), FieldKind::NoRef)
+            }
+            BlockField::WithId { value, id: None } => {
+                return BlockFieldBuilder::new_with_kind(
+                    value_to_string(value),
+                    FieldKind::NoRefMaybe,
+                )
+            }
+            BlockField::WithId {
+                value,
+                id: Some(id),
+            } => (value_to_string(value), id),
+        };
+        let kind = [
+            (FieldKind::SpriteVariable, target_context.this_sprite_vars),
+            (FieldKind::GlobalVariable, target_context.global_vars),
+            (FieldKind::SpriteList, target_context.this_sprite_lists),
+            (FieldKind::GlobalList, target_context.global_lists),
+            (FieldKind::Broadcast, target_context.all_broadcasts),
+        ]
+        .into_iter()
+        .find(|(_, ctx)| ctx.get(&value).is_some_and(|uid| uid.inner() == id))
+        .map_or(FieldKind::NoRefMaybe, |(kind, _)| kind);
+        BlockFieldBuilder::new_with_kind(value, kind)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VarListFrom {
     Global,
     Sprite,
+    /// The sprite's own or the stage's, whichever has it. See [`TargetContext::resolve_var`].
+    /// [`crate::export::export`] refuses one that neither has.
+    Scope,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -410,6 +624,30 @@ impl BlockVarListBuilder {
         }
     }
 
+    /// Variable that is looked up in the sprite and then the stage
+    pub fn var<S: Into<String>>(name: S) -> BlockVarListBuilder {
+        BlockVarListBuilder {
+            kind: ListOrVariable::Variable,
+            from: VarListFrom::Scope,
+            name: name.into(),
+            x: 0.,
+            y: 0.,
+            comment: None,
+        }
+    }
+
+    /// List that is looked up in the sprite and then the stage
+    pub fn list<S: Into<String>>(name: S) -> BlockVarListBuilder {
+        BlockVarListBuilder {
+            kind: ListOrVariable::List,
+            from: VarListFrom::Scope,
+            name: name.into(),
+            x: 0.,
+            y: 0.,
+            comment: None,
+        }
+    }
+
     pub fn set_kind(&mut self, kind: ListOrVariable) -> &mut Self {
         self.kind = kind;
         self
@@ -446,11 +684,42 @@ impl BlockVarListBuilder {
         self
     }
 
+    /// Same reporter as a `data_variable` or `data_listcontents` block,
+    /// which unlike the short form can have a next and a parent block.
+    pub fn into_block_normal(self) -> BlockNormalBuilder {
+        let BlockVarListBuilder {
+            kind,
+            from,
+            name,
+            x,
+            y,
+            comment,
+        } = self;
+        let (opcode, key) = match kind {
+            ListOrVariable::Variable => (StandardOpCode::data_variable, "VARIABLE"),
+            ListOrVariable::List => (StandardOpCode::data_listcontents, "LIST"),
+        };
+        let field_kind = match (kind, from) {
+            (ListOrVariable::Variable, VarListFrom::Global) => FieldKind::GlobalVariable,
+            (ListOrVariable::Variable, VarListFrom::Sprite) => FieldKind::SpriteVariable,
+            (ListOrVariable::Variable, VarListFrom::Scope) => FieldKind::Variable,
+            (ListOrVariable::List, VarListFrom::Global) => FieldKind::GlobalList,
+            (ListOrVariable::List, VarListFrom::Sprite) => FieldKind::SpriteList,
+            (ListOrVariable::List, VarListFrom::Scope) => FieldKind::List,
+        };
+        let mut b = BlockNormalBuilder::new(opcode);
+        b.add_field(key, BlockFieldBuilder::new_with_kind(name, field_kind))
+            .set_comment(comment)
+            .set_pos(Some(x), Some(y));
+        b
+    }
+
     pub fn build(
         self,
         my_uid: &Uid,
         comment_buff: &mut HashMap<Uid, Comment>,
         target_context: &TargetContext,
+        uid_gen: &mut UidGenerator,
     ) -> BlockVarListReporterTop {
         let BlockVarListBuilder {
             kind,
@@ -461,15 +730,23 @@ impl BlockVarListBuilder {
             comment,
         } = self;
         let varlist_id = match (&kind, from) {
-            (ListOrVariable::Variable, VarListFrom::Global) => target_context.global_vars,
-            (ListOrVariable::Variable, VarListFrom::Sprite) => target_context.this_sprite_vars,
-            (ListOrVariable::List, VarListFrom::Global) => target_context.global_lists,
-            (ListOrVariable::List, VarListFrom::Sprite) => target_context.this_sprite_lists,
+            (ListOrVariable::Variable, VarListFrom::Global) => {
+                target_context.global_vars.get(&name)
+            }
+            (ListOrVariable::Variable, VarListFrom::Sprite) => {
+                target_context.this_sprite_vars.get(&name)
+            }
+            (ListOrVariable::Variable, VarListFrom::Scope) => target_context.resolve_var(&name),
+            (ListOrVariable::List, VarListFrom::Global) => target_context.global_lists.get(&name),
+            (ListOrVariable::List, VarListFrom::Sprite) => {
+                target_context.this_sprite_lists.get(&name)
+            }
+            (ListOrVariable::List, VarListFrom::Scope) => target_context.resolve_list(&name),
         }
-        .get(&name).cloned()
+        .cloned()
         .unwrap_or(Uid::new("__unknown__"));
         if let Some(comment) = comment {
-            let comment_uid = Uid::generate();
+            let comment_uid = uid_gen.generate();
             let mut comment = comment.build();
             comment.block_id = Some(my_uid.clone().into_inner());
             comment_buff.insert(comment_uid, comment);
@@ -483,29 +760,88 @@ impl BlockVarListBuilder {
             y: y.into(),
         }
     }
+
+    /// Reverse of [`BlockVarListBuilder::build`].
+    pub fn from_block_varlist(
+        my_uid: &Uid,
+        block: &BlockVarListReporterTop,
+        comments: &HashMap<String, Comment>,
+        target_context: &TargetContext,
+    ) -> BlockVarListBuilder {
+        let mut b = BlockVarListBuilder::from_reference(
+            block.kind.clone(),
+            &block.name,
+            &block.id,
+            target_context,
+        );
+        b.set_pos(number_to_f64(&block.x), number_to_f64(&block.y))
+            .set_comment(
+                comments
+                    .values()
+                    .find(|comment| comment.block_id.as_deref() == Some(my_uid.inner()))
+                    .map(|comment| CommentBuilder::from_comment(comment.clone())),
+            );
+        b
+    }
+
+    /// Variable or list that is referenced by `name` and `id`.
+    /// Reference that can't be found in the sprite is assumed to be global.
+    pub fn from_reference(
+        kind: ListOrVariable,
+        name: &str,
+        id: &str,
+        target_context: &TargetContext,
+    ) -> BlockVarListBuilder {
+        let this_sprite = match kind {
+            ListOrVariable::Variable => target_context.this_sprite_vars,
+            ListOrVariable::List => target_context.this_sprite_lists,
+        };
+        let from = match this_sprite.get(name) {
+            Some(uid) if uid.inner() == id => VarListFrom::Sprite,
+            _ => VarListFrom::Global,
+        };
+        BlockVarListBuilder {
+            kind,
+            from,
+            name: name.to_owned(),
+            x: 0.,
+            y: 0.,
+            comment: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
 pub enum BlockBuilder {
     Normal(BlockNormalBuilder),
     VarList(BlockVarListBuilder),
 }
 
 impl BlockBuilder {
+    /// Variable or list reporter becomes a normal block, see [`BlockVarListBuilder::into_block_normal`]
+    pub fn into_block_normal(self) -> BlockNormalBuilder {
+        match self {
+            BlockBuilder::Normal(n) => n,
+            BlockBuilder::VarList(vl) => vl.into_block_normal(),
+        }
+    }
+
     pub fn build(
         self,
         my_uid: &Uid,
         comment_buff: &mut HashMap<Uid, Comment>,
         final_stack: &mut HashMap<Uid, Block>,
         target_context: &TargetContext,
+        uid_gen: &mut UidGenerator,
     ) -> Block {
         match self {
             BlockBuilder::Normal(n) => {
-                let b = n.build(my_uid, comment_buff, final_stack, target_context);
+                let b = n.build(my_uid, comment_buff, final_stack, target_context, uid_gen);
                 Block::Normal(b)
             }
             BlockBuilder::VarList(vl) => {
-                let b = vl.build(my_uid, comment_buff, target_context);
+                let b = vl.build(my_uid, comment_buff, target_context, uid_gen);
                 Block::VarList(b)
             }
         }
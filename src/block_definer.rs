@@ -0,0 +1,389 @@
+//! Custom blocks (procedures)
+//!
+//! Custom block is made out of a few blocks:
+//!  - `procedures_definition` the hat block that the custom block's script is under.
+//!  - `procedures_prototype` the shadow block inside the definition that describes the custom block.
+//!  - `argument_reporter_*` the shadow blocks inside the prototype for each argument,
+//!    it's also the reporter that is used inside the script to get value of an argument.
+//!  - `procedures_call` the block that actually runs the custom block.
+//!
+//! [`ProcedureBuilder`] keeps all of them in sync.
+
+use sb_sbity::block::{BlockMutation, BlockMutationEnum, ShadowInputType};
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder, StackOrValue},
+    opcode::StandardOpCode,
+    stack::StackBuilder,
+    uid::{Uid, UidGenerator, UidMode},
+};
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcedureError {
+    /// Amount of argument names doesn't match amount of `%s` and `%b` in the proccode
+    ArgumentCount { placeholders: usize, names: usize },
+    /// Wrong amount of arguments is given to [`ProcedureBuilder::call`]
+    Arity { expected: usize, found: usize },
+    /// There's no argument with this name in the custom block
+    UnknownArgument(String),
+}
+
+impl std::error::Error for ProcedureError {}
+
+impl std::fmt::Display for ProcedureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcedureError::ArgumentCount {
+                placeholders,
+                names,
+            } => write!(
+                f,
+                "proccode has {placeholders} argument(s) but {names} argument name(s) is given"
+            ),
+            ProcedureError::Arity { expected, found } => {
+                write!(f, "expected {expected} argument(s) but found {found}")
+            }
+            ProcedureError::UnknownArgument(name) => write!(f, "unknown argument `{name}`"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgumentKind {
+    /// `%s` (or the legacy `%n`) in proccode
+    StringNumber,
+    /// `%b` in proccode
+    Boolean,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcedureArgument {
+    pub name: String,
+    pub kind: ArgumentKind,
+    pub uid: Uid,
+}
+
+/// Custom block definer.
+///
+/// Proccode is the text on the custom block where each `%s` (string or number) and `%b` (boolean)
+/// is replaced by an argument in order.
+/// Ex. `"move %s steps and jump if %b"`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcedureBuilder {
+    proccode: String,
+    arguments: Vec<ProcedureArgument>,
+    warp: bool,
+}
+
+impl ProcedureBuilder {
+    pub fn new<S: Into<String>, N: Into<String>>(
+        proccode: S,
+        argument_names: Vec<N>,
+    ) -> Result<ProcedureBuilder, ProcedureError> {
+        let proccode = proccode.into();
+        let kinds = parse_proccode(&proccode);
+        if kinds.len() != argument_names.len() {
+            return Err(ProcedureError::ArgumentCount {
+                placeholders: kinds.len(),
+                names: argument_names.len(),
+            });
+        }
+        // Seeded by the proccode so the same custom block always gets the same argument uids
+        let seed = u64::from_le_bytes(md5::compute(&proccode).0[..8].try_into().unwrap());
+        let mut uid_gen = UidGenerator::new_unique(UidMode::Seeded(seed));
+        let arguments = kinds
+            .into_iter()
+            .zip(argument_names)
+            .map(|(kind, name)| ProcedureArgument {
+                name: name.into(),
+                kind,
+                uid: uid_gen.generate(),
+            })
+            .collect();
+        Ok(ProcedureBuilder {
+            proccode,
+            arguments,
+            warp: false,
+        })
+    }
+
+    /// "Run without screen refresh" option
+    pub fn set_warp(&mut self, run_without_screen_refresh: bool) -> &mut Self {
+        self.warp = run_without_screen_refresh;
+        self
+    }
+
+    pub fn proccode(&self) -> &str {
+        &self.proccode
+    }
+
+    pub fn arguments(&self) -> &[ProcedureArgument] {
+        &self.arguments
+    }
+
+    pub fn warp(&self) -> bool {
+        self.warp
+    }
+
+    /// The `define` hat block. Put the custom block's script after this with [`StackBuilder::next`].
+    pub fn definition(&self) -> StackBuilder {
+        StackBuilder::start({
+            let mut b = BlockNormalBuilder::new(StandardOpCode::procedures_definition);
+            b.add_input("custom_block", {
+                let mut input = Bib::new();
+                input
+                    .set_shadow(ShadowInputType::Shadow)
+                    .add_input(Some(StackOrValue::Stack(self.prototype())));
+                input
+            });
+            b
+        })
+    }
+
+    /// Block that runs this custom block.
+    ///
+    /// `arguments` is in the same order as in the proccode.
+    pub fn call(&self, arguments: Vec<Bib>) -> Result<StackBuilder, ProcedureError> {
+        if arguments.len() != self.arguments.len() {
+            return Err(ProcedureError::Arity {
+                expected: self.arguments.len(),
+                found: arguments.len(),
+            });
+        }
+        Ok(StackBuilder::start({
+            let mut b = BlockNormalBuilder::new(StandardOpCode::procedures_call);
+            for (argument, input) in self.arguments.iter().zip(arguments) {
+                b.add_input(argument.uid.inner(), input);
+            }
+            b.set_mutation(self.mutation(false));
+            b
+        }))
+    }
+
+    /// Reporter of the argument's value. Only works inside the custom block's script.
+    pub fn argument<S: AsRef<str>>(&self, name: S) -> Result<StackBuilder, ProcedureError> {
+        let name = name.as_ref();
+        let argument = self
+            .arguments
+            .iter()
+            .find(|argument| argument.name == name)
+            .ok_or_else(|| ProcedureError::UnknownArgument(name.to_owned()))?;
+        Ok(StackBuilder::start(argument_reporter(argument, false)))
+    }
+
+    fn prototype(&self) -> StackBuilder {
+        StackBuilder::start({
+            let mut b = BlockNormalBuilder::new(StandardOpCode::procedures_prototype);
+            for argument in &self.arguments {
+                b.add_input(argument.uid.inner(), {
+                    let mut input = Bib::new();
+                    input
+                        .set_shadow(ShadowInputType::Shadow)
+                        .add_input(Some(StackOrValue::Stack(StackBuilder::start(
+                            argument_reporter(argument, true),
+                        ))));
+                    input
+                });
+            }
+            b.set_mutation(self.mutation(true)).set_shadow(true);
+            b
+        })
+    }
+
+    fn mutation(&self, is_prototype: bool) -> BlockMutation {
+        let argumentids = self
+            .arguments
+            .iter()
+            .map(|argument| argument.uid.clone().into_inner())
+            .collect();
+        let mutation_enum = if is_prototype {
+            BlockMutationEnum::ProceduresPrototype {
+                proccode: self.proccode.clone(),
+                argumentids,
+                argumentnames: self
+                    .arguments
+                    .iter()
+                    .map(|argument| argument.name.clone())
+                    .collect(),
+                argumentdefaults: self
+                    .arguments
+                    .iter()
+                    .map(|argument| match argument.kind {
+                        ArgumentKind::StringNumber => "".to_owned(),
+                        ArgumentKind::Boolean => "false".to_owned(),
+                    })
+                    .collect(),
+                warp: Some(self.warp),
+            }
+        } else {
+            BlockMutationEnum::ProceduresCall {
+                proccode: self.proccode.clone(),
+                argumentids,
+                warp: Some(self.warp),
+            }
+        };
+        BlockMutation {
+            tag_name: "mutation".to_owned(),
+            children: vec![],
+            mutation_enum,
+        }
+    }
+}
+
+fn argument_reporter(argument: &ProcedureArgument, is_shadow: bool) -> BlockNormalBuilder {
+    let mut b = BlockNormalBuilder::new(match argument.kind {
+        ArgumentKind::StringNumber => StandardOpCode::argument_reporter_string_number,
+        ArgumentKind::Boolean => StandardOpCode::argument_reporter_boolean,
+    });
+    b.add_field("VALUE", Bfb::new(argument.name.clone()))
+        .set_shadow(is_shadow);
+    b
+}
+
+/// Kind of every argument placeholder in the proccode in order.
+/// `%` that isn't followed by `s`, `n` or `b` is just text.
+fn parse_proccode(proccode: &str) -> Vec<ArgumentKind> {
+    let mut kinds = vec![];
+    let mut chars = proccode.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        let kind = match chars.peek() {
+            Some('s') | Some('n') => ArgumentKind::StringNumber,
+            Some('b') => ArgumentKind::Boolean,
+            _ => continue,
+        };
+        chars.next();
+        kinds.push(kind);
+    }
+    kinds
+}
+
+#[cfg(test)]
+mod tests {
+    use sb_sbity::value::OpCode;
+
+    use super::*;
+    use crate::block::BlockBuilder;
+
+    fn top_block(stack: &StackBuilder) -> &BlockNormalBuilder {
+        match &stack.stack[0] {
+            BlockBuilder::Normal(b) => b,
+            BlockBuilder::VarList(_) => panic!("expected a normal block"),
+        }
+    }
+
+    fn prototype(definition: &StackBuilder) -> &BlockNormalBuilder {
+        match &top_block(definition).inputs()["custom_block"].values[0] {
+            Some(StackOrValue::Stack(prototype)) => top_block(prototype),
+            other => panic!("expected the prototype, found {other:?}"),
+        }
+    }
+
+    /// Input names sorted, the ids of the arguments
+    fn input_ids(b: &BlockNormalBuilder) -> Vec<String> {
+        let mut ids: Vec<String> = b.inputs().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn proccode_placeholders() {
+        use ArgumentKind::*;
+        assert_eq!(
+            parse_proccode("move %s steps and jump if %b"),
+            [StringNumber, Boolean]
+        );
+        assert_eq!(parse_proccode("legacy %n"), [StringNumber]);
+        assert_eq!(parse_proccode("grow 50% then %s"), [StringNumber]);
+        assert_eq!(parse_proccode("100%%s"), [StringNumber]);
+        assert_eq!(parse_proccode("%d %"), []);
+    }
+
+    #[test]
+    fn argument_names_must_match_placeholders() {
+        assert_eq!(
+            ProcedureBuilder::new("jump %s %b", vec!["height"]),
+            Err(ProcedureError::ArgumentCount {
+                placeholders: 2,
+                names: 1
+            })
+        );
+    }
+
+    #[test]
+    fn call_with_wrong_arity() {
+        let procedure = ProcedureBuilder::new("jump %s", vec!["height"]).unwrap();
+        assert_eq!(
+            procedure.call(vec![]),
+            Err(ProcedureError::Arity {
+                expected: 1,
+                found: 0
+            })
+        );
+        assert_eq!(
+            procedure.argument("width"),
+            Err(ProcedureError::UnknownArgument("width".to_owned()))
+        );
+    }
+
+    #[test]
+    fn definition_and_call_agree() {
+        let mut procedure =
+            ProcedureBuilder::new("jump %s if %b", vec!["height", "allowed"]).unwrap();
+        procedure.set_warp(true);
+        let definition = procedure.definition();
+        let prototype = prototype(&definition);
+        let call = procedure.call(vec![Bib::new(), Bib::new()]).unwrap();
+        let call = top_block(&call);
+        let ids: Vec<String> = procedure
+            .arguments()
+            .iter()
+            .map(|argument| argument.uid.clone().into_inner())
+            .collect();
+        let mut sorted_ids = ids.clone();
+        sorted_ids.sort();
+        assert_eq!(input_ids(prototype), sorted_ids);
+        assert_eq!(input_ids(call), sorted_ids);
+
+        let Some(BlockMutationEnum::ProceduresPrototype {
+            proccode,
+            argumentids,
+            argumentnames,
+            argumentdefaults,
+            warp,
+        }) = prototype.mutation().map(|m| &m.mutation_enum)
+        else {
+            panic!("expected the prototype mutation");
+        };
+        assert_eq!(proccode, "jump %s if %b");
+        assert_eq!(argumentids, &ids);
+        assert_eq!(argumentnames, &["height", "allowed"]);
+        assert_eq!(argumentdefaults, &["", "false"]);
+        assert_eq!(*warp, Some(true));
+
+        let Some(BlockMutationEnum::ProceduresCall {
+            proccode: call_proccode,
+            argumentids: call_argumentids,
+            warp: call_warp,
+        }) = call.mutation().map(|m| &m.mutation_enum)
+        else {
+            panic!("expected the call mutation");
+        };
+        assert_eq!(call_proccode, proccode);
+        assert_eq!(call_argumentids, argumentids);
+        assert_eq!(call_warp, warp);
+
+        // Reporters in the prototype and in the script are the same argument
+        let reporter = procedure.argument("allowed").unwrap();
+        assert_eq!(
+            top_block(&reporter).opcode(),
+            &OpCode::from(StandardOpCode::argument_reporter_boolean)
+        );
+        assert_eq!(top_block(&reporter).fields()["VALUE"].value, "allowed");
+    }
+}
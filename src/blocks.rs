@@ -13,9 +13,22 @@
 //!  - `"_stage_"`
 //!
 
+pub mod ev3;
+pub mod gdxfor;
+pub mod makeymakey;
+pub mod microbit;
+pub mod music;
+pub mod pen;
+pub mod text2speech;
+pub mod translate;
+pub mod video_sensing;
+pub mod wedo2;
+
 use crate::{
     block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder, BlockVarListBuilder},
+    input::{Angle, Broadcast, Color, Integer, Number, PositiveNumber, Text, WholeNumber},
     opcode::StandardOpCode,
+    schema,
     stack::StackBuilder,
 };
 use sb_sbity::block::{BlockMutation, BlockMutationEnum};
@@ -32,19 +45,34 @@ use sb_sbity::block::{BlockMutation, BlockMutationEnum};
 type Bfb = BlockFieldBuilder;
 type Bib = BlockInputBuilder;
 
+/// Every helper starts its block with this so a wrong opcode or key fails in debug build.
+/// Values aren't checked since they're coming from the user.
+fn start(block: BlockNormalBuilder) -> StackBuilder {
+    if cfg!(debug_assertions) {
+        for violation in schema::check_block(&block) {
+            assert!(
+                !violation.is_key_mismatch(),
+                "`{}` {violation}",
+                block.opcode()
+            );
+        }
+    }
+    StackBuilder::start(block)
+}
+
 // Control =====================================================================
-pub fn wait(duration: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn wait(duration: impl Into<PositiveNumber>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::control_wait);
-        b.add_input("DURATION", duration);
+        b.add_input("DURATION", duration.into());
         b
     })
 }
 
-pub fn repeat(times: Bib, to_repeat: Option<Bib>) -> StackBuilder {
-    StackBuilder::start({
+pub fn repeat(times: impl Into<WholeNumber>, to_repeat: Option<Bib>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::control_repeat);
-        b.add_input("TIMES", times);
+        b.add_input("TIMES", times.into());
         if let Some(to_repeat) = to_repeat {
             b.add_input("SUBSTACK", to_repeat);
         }
@@ -53,7 +81,7 @@ pub fn repeat(times: Bib, to_repeat: Option<Bib>) -> StackBuilder {
 }
 
 pub fn forever(to_repeat: Option<Bib>) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::control_forever);
         if let Some(to_repeat) = to_repeat {
             b.add_input("SUBSTACK", to_repeat);
@@ -62,10 +90,10 @@ pub fn forever(to_repeat: Option<Bib>) -> StackBuilder {
     })
 }
 
-pub fn if_(condition: Bib, if_true: Option<Bib>) -> StackBuilder {
-    StackBuilder::start({
+pub fn if_(condition: impl Into<Bib>, if_true: Option<Bib>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::control_if);
-        b.add_input("CONDITION", condition);
+        b.add_input("CONDITION", condition.into());
         if let Some(if_true) = if_true {
             b.add_input("SUBSTACK", if_true);
         }
@@ -73,10 +101,14 @@ pub fn if_(condition: Bib, if_true: Option<Bib>) -> StackBuilder {
     })
 }
 
-pub fn if_else(condition: Bib, if_true: Option<Bib>, if_false: Option<Bib>) -> StackBuilder {
-    StackBuilder::start({
+pub fn if_else(
+    condition: impl Into<Bib>,
+    if_true: Option<Bib>,
+    if_false: Option<Bib>,
+) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::control_if_else);
-        b.add_input("CONDITION", condition);
+        b.add_input("CONDITION", condition.into());
         if let Some(if_true) = if_true {
             b.add_input("SUBSTACK", if_true);
         }
@@ -87,18 +119,18 @@ pub fn if_else(condition: Bib, if_true: Option<Bib>, if_false: Option<Bib>) -> S
     })
 }
 
-pub fn wait_until(condition: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn wait_until(condition: impl Into<Bib>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::control_wait_until);
-        b.add_input("CONDITION", condition);
+        b.add_input("CONDITION", condition.into());
         b
     })
 }
 
-pub fn repeat_until(condition: Bib, to_repeat: Option<Bib>) -> StackBuilder {
-    StackBuilder::start({
-        let mut b = BlockNormalBuilder::new(StandardOpCode::control_if_else);
-        b.add_input("CONDITION", condition);
+pub fn repeat_until(condition: impl Into<Bib>, to_repeat: Option<Bib>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(StandardOpCode::control_repeat_until);
+        b.add_input("CONDITION", condition.into());
         if let Some(to_repeat) = to_repeat {
             b.add_input("SUBSTACK", to_repeat);
         }
@@ -112,7 +144,7 @@ pub fn repeat_until(condition: Bib, to_repeat: Option<Bib>) -> StackBuilder {
 ///  - `"other scripts in sprite"` and `has_next` should be `true`
 ///  - `"all"` and `has_next` should be `false`
 pub fn stop(stop_option: Bfb, has_next: bool) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::control_stop);
         b.add_field("STOP_OPTION", stop_option)
             .set_mutation(BlockMutation {
@@ -125,7 +157,7 @@ pub fn stop(stop_option: Bfb, has_next: bool) -> StackBuilder {
 }
 
 pub fn when_i_start_as_a_clone() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(
+    start(BlockNormalBuilder::new(
         StandardOpCode::control_start_as_clone,
     ))
 }
@@ -134,7 +166,7 @@ pub fn when_i_start_as_a_clone() -> StackBuilder {
 /// Accepts:
 ///  - Sprite name
 pub fn create_clone_of(sprite: Bib) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::control_create_clone_of);
         b.add_input("CLONE_OPTION", sprite);
         b
@@ -146,22 +178,22 @@ pub fn create_clone_of(sprite: Bib) -> StackBuilder {
 /// Accepts:
 ///  - Sprite name
 pub fn create_clone_of_menu(sprite: Bfb) -> StackBuilder {
-    StackBuilder::start({
-        let mut b = BlockNormalBuilder::new(StandardOpCode::control_create_clone_of);
+    start({
+        let mut b = BlockNormalBuilder::new(StandardOpCode::control_create_clone_of_menu);
         b.add_field("CLONE_OPTION", sprite).set_shadow(true);
         b
     })
 }
 
 pub fn delete_this_clone() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(
+    start(BlockNormalBuilder::new(
         StandardOpCode::control_delete_this_clone,
     ))
 }
 
 // Event =======================================================================
 pub fn when_flag_clicked() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(
+    start(BlockNormalBuilder::new(
         StandardOpCode::event_whenflagclicked,
     ))
 }
@@ -177,7 +209,7 @@ pub fn when_flag_clicked() -> StackBuilder {
 ///  - Number 0 - 9
 ///  - Letter a - z
 pub fn when_key_pressed(key: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::event_whenkeypressed);
         b.add_field("KEY_OPTION", key);
         b
@@ -185,7 +217,7 @@ pub fn when_key_pressed(key: Bfb) -> StackBuilder {
 }
 
 pub fn when_this_sprite_clicked() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(
+    start(BlockNormalBuilder::new(
         StandardOpCode::event_whenthisspriteclicked,
     ))
 }
@@ -194,7 +226,7 @@ pub fn when_this_sprite_clicked() -> StackBuilder {
 /// Accepts:
 ///  - Backdrop name
 pub fn when_backdrop_switches_to(backdrop: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::event_whenbackdropswitchesto);
         b.add_field("BACKDROP", backdrop);
         b
@@ -205,68 +237,70 @@ pub fn when_backdrop_switches_to(backdrop: Bfb) -> StackBuilder {
 /// Accepts:
 /// - `"LOUDNESS"`
 /// - `"TIMER"`
-pub fn when_greater_than(variable: Bfb, value: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn when_greater_than(variable: Bfb, value: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::event_whengreaterthan);
-        b.add_input("VALUE", value)
+        b.add_input("VALUE", value.into())
             .add_field("WHENGREATERTHANMENU", variable);
         b
     })
 }
 
 pub fn when_broadcast_received(broadcast: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::event_whenbroadcastreceived);
         b.add_field("BROADCAST_OPTION", broadcast);
         b
     })
 }
 
-pub fn broadcast(broadcast: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn broadcast(broadcast: impl Into<Broadcast>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::event_broadcast);
-        b.add_input("BROADCAST_INPUT", broadcast);
+        b.add_input("BROADCAST_INPUT", broadcast.into());
         b
     })
 }
 
-pub fn broadcast_and_wait(broadcast: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn broadcast_and_wait(broadcast: impl Into<Broadcast>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::event_broadcastandwait);
-        b.add_input("BROADCAST_INPUT", broadcast);
+        b.add_input("BROADCAST_INPUT", broadcast.into());
         b
     })
 }
 
 // Looks =======================================================================
-pub fn think(message: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn think(message: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_think);
-        b.add_input("MESSAGE", message);
+        b.add_input("MESSAGE", message.into());
         b
     })
 }
 
-pub fn think_for_secs(message: Bib, secs: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn think_for_secs(message: impl Into<Text>, secs: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_thinkforsecs);
-        b.add_input("MESSAGE", message).add_input("SECS", secs);
+        b.add_input("MESSAGE", message.into())
+            .add_input("SECS", secs.into());
         b
     })
 }
 
-pub fn say(message: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn say(message: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_say);
-        b.add_input("MESSAGE", message);
+        b.add_input("MESSAGE", message.into());
         b
     })
 }
 
-pub fn say_for_secs(message: Bib, secs: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn say_for_secs(message: impl Into<Text>, secs: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_sayforsecs);
-        b.add_input("MESSAGE", message).add_input("SECS", secs);
+        b.add_input("MESSAGE", message.into())
+            .add_input("SECS", secs.into());
         b
     })
 }
@@ -275,7 +309,7 @@ pub fn say_for_secs(message: Bib, secs: Bib) -> StackBuilder {
 /// Accepts:
 ///  - Costume name
 pub fn switch_costume_to(costume: Bib) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_switchcostumeto);
         b.add_input("COSTUME", costume);
         b
@@ -287,7 +321,7 @@ pub fn switch_costume_to(costume: Bib) -> StackBuilder {
 /// Accepts:
 ///  - Costume name
 pub fn costume_menu(costume: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_costume);
         b.add_field("COSTUME", costume).set_shadow(true);
         b
@@ -295,14 +329,14 @@ pub fn costume_menu(costume: Bfb) -> StackBuilder {
 }
 
 pub fn next_costume() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::looks_nextcostume))
+    start(BlockNormalBuilder::new(StandardOpCode::looks_nextcostume))
 }
 
 /// <br/>
 /// Accepts:
 ///  - Costume name
 pub fn switch_backdrop_to(backdrop: Bib) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_switchbackdropto);
         b.add_input("BACKDROP", backdrop);
         b
@@ -314,7 +348,7 @@ pub fn switch_backdrop_to(backdrop: Bib) -> StackBuilder {
 /// Accepts:
 ///  - Backdrop name
 pub fn backdrop_menu(backdrop: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_backdrops);
         b.add_field("BACKDROP", backdrop).set_shadow(true);
         b
@@ -322,21 +356,21 @@ pub fn backdrop_menu(backdrop: Bfb) -> StackBuilder {
 }
 
 pub fn next_backdrop() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::looks_nextbackdrop))
+    start(BlockNormalBuilder::new(StandardOpCode::looks_nextbackdrop))
 }
 
-pub fn change_size_by(by: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn change_size_by(by: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_changesizeby);
-        b.add_input("CHANGE", by);
+        b.add_input("CHANGE", by.into());
         b
     })
 }
 
-pub fn set_size_to(to: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn set_size_to(to: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_setsizeto);
-        b.add_input("SIZE", to);
+        b.add_input("SIZE", to.into());
 
         b
     })
@@ -351,10 +385,10 @@ pub fn set_size_to(to: Bib) -> StackBuilder {
 ///  - `"MOSAIC"`
 ///  - `"BRIGHTNESS"`
 ///  - `"GHOST"`
-pub fn change_looks_effect_by(effect: Bfb, by: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn change_looks_effect_by(effect: Bfb, by: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_changeeffectby);
-        b.add_input("CHANGE", by).add_field("EFFECT", effect);
+        b.add_input("CHANGE", by.into()).add_field("EFFECT", effect);
         b
     })
 }
@@ -368,26 +402,26 @@ pub fn change_looks_effect_by(effect: Bfb, by: Bib) -> StackBuilder {
 ///  - `"MOSAIC"`
 ///  - `"BRIGHTNESS"`
 ///  - `"GHOST"`
-pub fn set_looks_effect_to(effect: Bfb, to: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn set_looks_effect_to(effect: Bfb, to: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_seteffectto);
-        b.add_input("TO", to).add_field("EFFECT", effect);
+        b.add_input("VALUE", to.into()).add_field("EFFECT", effect);
         b
     })
 }
 
 pub fn clear_graphic_effects() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(
+    start(BlockNormalBuilder::new(
         StandardOpCode::looks_cleargraphiceffects,
     ))
 }
 
 pub fn show() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::looks_show))
+    start(BlockNormalBuilder::new(StandardOpCode::looks_show))
 }
 
 pub fn hide() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::looks_hide))
+    start(BlockNormalBuilder::new(StandardOpCode::looks_hide))
 }
 
 /// <br/>
@@ -395,7 +429,7 @@ pub fn hide() -> StackBuilder {
 ///  - `"front"`
 ///  - `"back"`
 pub fn go_to_layer(layer: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_gotofrontback);
         b.add_field("FRONT_BACK", layer);
         b
@@ -404,12 +438,13 @@ pub fn go_to_layer(layer: Bfb) -> StackBuilder {
 
 /// <br/>
 /// `layer` Accepts:
-///  - `"foward"`
+///  - `"forward"`
 ///  - `"backward"`
-pub fn change_layer(layer: Bfb, by: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn change_layer(layer: Bfb, by: impl Into<Integer>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_goforwardbackwardlayers);
-        b.add_input("NUM", by).add_field("FORWARD_BACKWORD", layer);
+        b.add_input("NUM", by.into())
+            .add_field("FORWARD_BACKWARD", layer);
         b
     })
 }
@@ -419,7 +454,7 @@ pub fn change_layer(layer: Bfb, by: Bib) -> StackBuilder {
 /// - `"number"`
 /// - `"name"`
 pub fn costume(return_type: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_costumenumbername);
         b.add_field("NUMBER_NAME", return_type);
         b
@@ -431,7 +466,7 @@ pub fn costume(return_type: Bfb) -> StackBuilder {
 /// - `"number"`
 /// - `"name"`
 pub fn backdrop(return_type: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::looks_backdropnumbername);
         b.add_field("NUMBER_NAME", return_type);
         b
@@ -439,30 +474,30 @@ pub fn backdrop(return_type: Bfb) -> StackBuilder {
 }
 
 pub fn size() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::looks_size))
+    start(BlockNormalBuilder::new(StandardOpCode::looks_size))
 }
 
 // Motion ======================================================================
-pub fn move_steps(steps: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn move_steps(steps: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_movesteps);
-        b.add_input("STEPS", steps);
+        b.add_input("STEPS", steps.into());
         b
     })
 }
 
-pub fn turn_right(degress: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn turn_right(degress: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_turnright);
-        b.add_input("DEGREES", degress);
+        b.add_input("DEGREES", degress.into());
         b
     })
 }
 
-pub fn turn_left(degress: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn turn_left(degress: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_turnleft);
-        b.add_input("DEGREES", degress);
+        b.add_input("DEGREES", degress.into());
         b
     })
 }
@@ -473,7 +508,7 @@ pub fn turn_left(degress: Bib) -> StackBuilder {
 ///  - `"_mouse_"` go to mouse position
 ///  - `"_random_"` go to random position
 pub fn go_to(to: Bib) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_goto);
         b.add_input("TO", to);
         b
@@ -487,17 +522,17 @@ pub fn go_to(to: Bib) -> StackBuilder {
 ///  - `"_mouse_"` go to mouse position
 ///  - `"_random_"` go to random position
 pub fn go_to_menu(to: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_goto_menu);
         b.add_field("TO", to).set_shadow(true);
         b
     })
 }
 
-pub fn goto_xy(x: Bib, y: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn goto_xy(x: impl Into<Number>, y: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_gotoxy);
-        b.add_input("X", x).add_input("Y", y);
+        b.add_input("X", x.into()).add_input("Y", y.into());
         b
     })
 }
@@ -507,10 +542,11 @@ pub fn goto_xy(x: Bib, y: Bib) -> StackBuilder {
 ///  - Sprite name
 ///  - `"_mouse_"` glide to mouse position
 ///  - `"_random_"` glide to random position
-pub fn glide_to(duration_secs: Bib, to: Bib) -> StackBuilder {
-    StackBuilder::start({
-        let mut b = BlockNormalBuilder::new(StandardOpCode::motion_gotoxy);
-        b.add_input("SECS", duration_secs).add_input("TO", to);
+pub fn glide_to(duration_secs: impl Into<Number>, to: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(StandardOpCode::motion_glideto);
+        b.add_input("SECS", duration_secs.into())
+            .add_input("TO", to);
         b
     })
 }
@@ -522,25 +558,31 @@ pub fn glide_to(duration_secs: Bib, to: Bib) -> StackBuilder {
 ///  - `"_mouse_"` glide to mouse position
 ///  - `"_random_"` glide to random position
 pub fn glide_to_menu(to: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_glideto_menu);
         b.add_field("TO", to).set_shadow(true);
         b
     })
 }
 
-pub fn glide_to_xy(dur: Bib, x: Bib, y: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn glide_to_xy(
+    dur: impl Into<Number>,
+    x: impl Into<Number>,
+    y: impl Into<Number>,
+) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_glidesecstoxy);
-        b.add_input("SECS", dur).add_input("X", x).add_input("Y", y);
+        b.add_input("SECS", dur.into())
+            .add_input("X", x.into())
+            .add_input("Y", y.into());
         b
     })
 }
 
-pub fn point_in_direction(direction: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn point_in_direction(direction: impl Into<Angle>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_pointindirection);
-        b.add_input("DIRECTION", direction);
+        b.add_input("DIRECTION", direction.into());
         b
     })
 }
@@ -550,7 +592,7 @@ pub fn point_in_direction(direction: Bib) -> StackBuilder {
 ///  - Sprite name
 ///  - `"_mouse_"` glide to mouse position
 pub fn point_towards(towards: Bib) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_pointtowards);
         b.add_input("TOWARDS", towards);
         b
@@ -563,47 +605,47 @@ pub fn point_towards(towards: Bib) -> StackBuilder {
 ///  - Sprite name
 ///  - `"_mouse_"` glide to mouse position
 pub fn point_towards_menu(towards: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_pointtowards_menu);
         b.add_field("TOWARDS", towards).set_shadow(true);
         b
     })
 }
 
-pub fn set_x(x: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn set_x(x: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_setx);
-        b.add_input("X", x);
+        b.add_input("X", x.into());
         b
     })
 }
 
-pub fn set_y(y: Bib) -> StackBuilder {
-    StackBuilder::start({
-        let mut b = BlockNormalBuilder::new(StandardOpCode::motion_setx);
-        b.add_input("Y", y);
+pub fn set_y(y: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(StandardOpCode::motion_sety);
+        b.add_input("Y", y.into());
         b
     })
 }
 
-pub fn change_x_by(by: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn change_x_by(by: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_changexby);
-        b.add_input("DX", by);
+        b.add_input("DX", by.into());
         b
     })
 }
 
-pub fn change_y_by(by: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn change_y_by(by: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_changeyby);
-        b.add_input("DY", by);
+        b.add_input("DY", by.into());
         b
     })
 }
 
 pub fn if_on_edge_bounce() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(
+    start(BlockNormalBuilder::new(
         StandardOpCode::motion_ifonedgebounce,
     ))
 }
@@ -614,7 +656,7 @@ pub fn if_on_edge_bounce() -> StackBuilder {
 ///  - `"don't rotate"`
 ///  - `"all around"`
 pub fn set_rotation_style(style: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::motion_setrotationstyle);
         b.add_field("STYLE", style);
         b
@@ -622,150 +664,163 @@ pub fn set_rotation_style(style: Bfb) -> StackBuilder {
 }
 
 pub fn direction() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::motion_direction))
+    start(BlockNormalBuilder::new(StandardOpCode::motion_direction))
 }
 
 pub fn y_position() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::motion_yposition))
+    start(BlockNormalBuilder::new(StandardOpCode::motion_yposition))
 }
 
 pub fn x_position() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::motion_xposition))
+    start(BlockNormalBuilder::new(StandardOpCode::motion_xposition))
 }
 
 // Operators ===================================================================
-pub fn add(lhs: Bib, rhs: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn add(lhs: impl Into<Number>, rhs: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_add);
-        b.add_input("NUM1", lhs).add_input("NUM2", rhs);
+        b.add_input("NUM1", lhs.into())
+            .add_input("NUM2", rhs.into());
         b
     })
 }
 
-pub fn sub(lhs: Bib, rhs: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn sub(lhs: impl Into<Number>, rhs: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_subtract);
-        b.add_input("NUM1", lhs).add_input("NUM2", rhs);
+        b.add_input("NUM1", lhs.into())
+            .add_input("NUM2", rhs.into());
         b
     })
 }
 
-pub fn mul(lhs: Bib, rhs: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn mul(lhs: impl Into<Number>, rhs: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_multiply);
-        b.add_input("NUM1", lhs).add_input("NUM2", rhs);
+        b.add_input("NUM1", lhs.into())
+            .add_input("NUM2", rhs.into());
         b
     })
 }
 
-pub fn div(lhs: Bib, rhs: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn div(lhs: impl Into<Number>, rhs: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_divide);
-        b.add_input("NUM1", lhs).add_input("NUM2", rhs);
+        b.add_input("NUM1", lhs.into())
+            .add_input("NUM2", rhs.into());
         b
     })
 }
 
-pub fn random(from: Bib, to: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn random(from: impl Into<Number>, to: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_random);
-        b.add_input("FROM", from).add_input("TO", to);
+        b.add_input("FROM", from.into()).add_input("TO", to.into());
         b
     })
 }
 
-pub fn less_than(lhs: Bib, rhs: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn less_than(lhs: impl Into<Text>, rhs: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_lt);
-        b.add_input("OPERAND1", lhs).add_input("OPERAND2", rhs);
+        b.add_input("OPERAND1", lhs.into())
+            .add_input("OPERAND2", rhs.into());
         b
     })
 }
 
-pub fn greater_than(lhs: Bib, rhs: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn greater_than(lhs: impl Into<Text>, rhs: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_gt);
-        b.add_input("OPERAND1", lhs).add_input("OPERAND2", rhs);
+        b.add_input("OPERAND1", lhs.into())
+            .add_input("OPERAND2", rhs.into());
         b
     })
 }
 
-pub fn equals(lhs: Bib, rhs: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn equals(lhs: impl Into<Text>, rhs: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_equals);
-        b.add_input("OPERAND1", lhs).add_input("OPERAND2", rhs);
+        b.add_input("OPERAND1", lhs.into())
+            .add_input("OPERAND2", rhs.into());
         b
     })
 }
 
-pub fn and(a: Bib, b: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn and(a: impl Into<Bib>, b: impl Into<Bib>) -> StackBuilder {
+    start({
         let mut bl = BlockNormalBuilder::new(StandardOpCode::operator_and);
-        bl.add_input("OPERAND1", a).add_input("OPERAND2", b);
+        bl.add_input("OPERAND1", a.into())
+            .add_input("OPERAND2", b.into());
         bl
     })
 }
 
-pub fn or(a: Bib, b: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn or(a: impl Into<Bib>, b: impl Into<Bib>) -> StackBuilder {
+    start({
         let mut bl = BlockNormalBuilder::new(StandardOpCode::operator_or);
-        bl.add_input("OPERAND1", a).add_input("OPERAND2", b);
+        bl.add_input("OPERAND1", a.into())
+            .add_input("OPERAND2", b.into());
         bl
     })
 }
 
-pub fn not(val: Bib) -> StackBuilder {
-    StackBuilder::start({
-        let mut b = BlockNormalBuilder::new(StandardOpCode::operator_or);
-        b.add_input("OPERAND", val);
+pub fn not(val: impl Into<Bib>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(StandardOpCode::operator_not);
+        b.add_input("OPERAND", val.into());
         b
     })
 }
 
-pub fn join(a: Bib, b: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn join(a: impl Into<Text>, b: impl Into<Text>) -> StackBuilder {
+    start({
         let mut bl = BlockNormalBuilder::new(StandardOpCode::operator_join);
-        bl.add_input("STRING1", a).add_input("STRING2", b);
+        bl.add_input("STRING1", a.into())
+            .add_input("STRING2", b.into());
         bl
     })
 }
 
-pub fn letter_of(idx: Bib, text: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn letter_of(idx: impl Into<WholeNumber>, text: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_letter_of);
-        b.add_input("LETTER", idx).add_input("STRING", text);
+        b.add_input("LETTER", idx.into())
+            .add_input("STRING", text.into());
         b
     })
 }
 
-pub fn length_of(text: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn length_of(text: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_length);
-        b.add_input("STRING", text);
+        b.add_input("STRING", text.into());
         b
     })
 }
 
-pub fn contains(text: Bib, contains: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn contains(text: impl Into<Text>, contains: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_contains);
-        b.add_input("STRING1", text).add_input("STRING2", contains);
+        b.add_input("STRING1", text.into())
+            .add_input("STRING2", contains.into());
         b
     })
 }
 
-pub fn modulo(dividend: Bib, divisor: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn modulo(dividend: impl Into<Number>, divisor: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_mod);
-        b.add_input("NUM1", dividend).add_input("NUM2", divisor);
+        b.add_input("NUM1", dividend.into())
+            .add_input("NUM2", divisor.into());
         b
     })
 }
 
-pub fn round(val: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn round(val: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_round);
-        b.add_input("NUM", val);
+        b.add_input("NUM", val.into());
         b
     })
 }
@@ -786,10 +841,10 @@ pub fn round(val: Bib) -> StackBuilder {
 ///  - `"log"`
 ///  - `"e ^"`
 ///  - `"10 ^"`
-pub fn math_op(op: Bfb, val: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn math_op(op: Bfb, val: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::operator_mathop);
-        b.add_input("NUM", val).add_field("OPERATOR", op);
+        b.add_input("NUM", val.into()).add_field("OPERATOR", op);
         b
     })
 }
@@ -802,7 +857,7 @@ pub fn math_op(op: Bfb, val: Bib) -> StackBuilder {
 ///  - `"_mouse_"`
 ///  - `"_edge_"`
 pub fn touching(what: Bib) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_touchingobject);
         b.add_input("TOUCHINGOBJECTMENU", what);
         b
@@ -816,25 +871,26 @@ pub fn touching(what: Bib) -> StackBuilder {
 ///  - `"_mouse_"`
 ///  - `"_edge_"`
 pub fn touching_menu(what: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_touchingobjectmenu);
         b.add_field("TOUCHINGOBJECTMENU", what).set_shadow(true);
         b
     })
 }
 
-pub fn touching_color(color: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn touching_color(color: impl Into<Color>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_touchingcolor);
-        b.add_input("COLOR", color);
+        b.add_input("COLOR", color.into());
         b
     })
 }
 
-pub fn color_touching_color(color_a: Bib, color_b: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn color_touching_color(color_a: impl Into<Color>, color_b: impl Into<Color>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_coloristouchingcolor);
-        b.add_input("COLOR", color_a).add_input("COLOR2", color_b);
+        b.add_input("COLOR", color_a.into())
+            .add_input("COLOR2", color_b.into());
         b
     })
 }
@@ -844,8 +900,8 @@ pub fn color_touching_color(color_a: Bib, color_b: Bib) -> StackBuilder {
 ///  - Sprite name
 ///  - `"_mouse_"`
 pub fn distance_to(what: Bib) -> StackBuilder {
-    StackBuilder::start({
-        let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_coloristouchingcolor);
+    start({
+        let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_distanceto);
         b.add_input("DISTANCETOMENU", what);
         b
     })
@@ -857,23 +913,23 @@ pub fn distance_to(what: Bib) -> StackBuilder {
 ///  - Sprite name
 ///  - `"_mouse_"`
 pub fn distance_to_menu(what: Bfb) -> StackBuilder {
-    StackBuilder::start({
-        let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_coloristouchingcolor);
+    start({
+        let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_distancetomenu);
         b.add_field("DISTANCETOMENU", what).set_shadow(true);
         b
     })
 }
 
-pub fn ask_and_wait(prompt_message: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn ask_and_wait(prompt_message: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_askandwait);
-        b.add_input("QUESTION", prompt_message);
+        b.add_input("QUESTION", prompt_message.into());
         b
     })
 }
 
 pub fn answer() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::sensing_answer))
+    start(BlockNormalBuilder::new(StandardOpCode::sensing_answer))
 }
 
 /// <br/>
@@ -887,7 +943,7 @@ pub fn answer() -> StackBuilder {
 ///  - Number 0 - 9
 ///  - Letter a - z
 pub fn key_pressed(key: Bib) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_keypressed);
         b.add_input("KEY_OPTION", key);
         b
@@ -905,20 +961,24 @@ pub fn key_pressed(key: Bib) -> StackBuilder {
 ///  - `"down arrow"`
 ///  - Number 0 - 9
 ///  - Letter a - z
-pub fn key_menu(key: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn key_menu(key: Bfb) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_keyoptions);
-        b.add_input("KEY_OPTION", key).set_shadow(true);
+        b.add_field("KEY_OPTION", key).set_shadow(true);
         b
     })
 }
 
 pub fn mouse_down() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::sensing_mousedown))
+    start(BlockNormalBuilder::new(StandardOpCode::sensing_mousedown))
 }
 
 pub fn mouse_x() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::sensing_mousex))
+    start(BlockNormalBuilder::new(StandardOpCode::sensing_mousex))
+}
+
+pub fn mouse_y() -> StackBuilder {
+    start(BlockNormalBuilder::new(StandardOpCode::sensing_mousey))
 }
 
 /// <br/>
@@ -926,7 +986,7 @@ pub fn mouse_x() -> StackBuilder {
 ///  - `"not draggable"`
 ///  - `"draggable"`
 pub fn set_drag_mode(mode: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_setdragmode);
         b.add_field("DRAG_MODE", mode);
         b
@@ -934,15 +994,15 @@ pub fn set_drag_mode(mode: Bfb) -> StackBuilder {
 }
 
 pub fn loudness() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::sensing_loudness))
+    start(BlockNormalBuilder::new(StandardOpCode::sensing_loudness))
 }
 
 pub fn timer() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::sensing_timer))
+    start(BlockNormalBuilder::new(StandardOpCode::sensing_timer))
 }
 
 pub fn reset_timer() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::sensing_resettimer))
+    start(BlockNormalBuilder::new(StandardOpCode::sensing_resettimer))
 }
 
 /// <br/>
@@ -970,7 +1030,7 @@ pub fn reset_timer() -> StackBuilder {
 ///      - `"size"`
 ///      - `"volume"`
 pub fn var_of(var: Bfb, what: Bib) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_of);
         b.add_input("OBJECT", what).add_field("PROPERTY", var);
         b
@@ -983,7 +1043,7 @@ pub fn var_of(var: Bfb, what: Bib) -> StackBuilder {
 ///   - Sprite name
 ///   - `"_stage_"`
 pub fn var_of_object_menu(what: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_of_object_menu);
         b.add_field("OBJECT", what).set_shadow(true);
         b
@@ -1000,7 +1060,7 @@ pub fn var_of_object_menu(what: Bfb) -> StackBuilder {
 ///  - `"MONTH"`
 ///  - `"YEAR"`
 pub fn current_datetime(format: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sensing_current);
         b.add_field("CURRENTMENU", format);
         b
@@ -1008,13 +1068,13 @@ pub fn current_datetime(format: Bfb) -> StackBuilder {
 }
 
 pub fn days_since_2000() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(
+    start(BlockNormalBuilder::new(
         StandardOpCode::sensing_dayssince2000,
     ))
 }
 
 pub fn username() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::sensing_username))
+    start(BlockNormalBuilder::new(StandardOpCode::sensing_username))
 }
 
 // Sound =======================================================================
@@ -1023,7 +1083,7 @@ pub fn username() -> StackBuilder {
 /// Accepts:
 ///  - Sound name
 pub fn play_sound_until_done(sound: Bib) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sound_playuntildone);
         b.add_input("SOUND_MENU", sound);
         b
@@ -1034,7 +1094,7 @@ pub fn play_sound_until_done(sound: Bib) -> StackBuilder {
 /// Accepts:
 ///  - Sound name
 pub fn play_sound(sound: Bib) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sound_play);
         b.add_input("SOUND_MENU", sound);
         b
@@ -1046,7 +1106,7 @@ pub fn play_sound(sound: Bib) -> StackBuilder {
 /// Accepts:
 ///  - Sound name
 pub fn sound_menu(sound: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sound_sounds_menu);
         b.add_field("SOUND_MENU", sound).set_shadow(true);
         b
@@ -1054,17 +1114,17 @@ pub fn sound_menu(sound: Bfb) -> StackBuilder {
 }
 
 pub fn stop_all_sound() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::sound_stopallsounds))
+    start(BlockNormalBuilder::new(StandardOpCode::sound_stopallsounds))
 }
 
 /// <br/>
 /// Accepts:
 ///  - `"PITCH"`
 ///  - `"PAN"`
-pub fn change_sound_effect_by(effect: Bfb, by: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn change_sound_effect_by(effect: Bfb, by: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sound_changeeffectby);
-        b.add_input("VALUE", by).add_field("EFFECT", effect);
+        b.add_input("VALUE", by.into()).add_field("EFFECT", effect);
         b
     })
 }
@@ -1073,36 +1133,36 @@ pub fn change_sound_effect_by(effect: Bfb, by: Bib) -> StackBuilder {
 /// Accepts:
 ///  - `"PITCH"`
 ///  - `"PAN"`
-pub fn set_sound_effect_to(effect: Bfb, to: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn set_sound_effect_to(effect: Bfb, to: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sound_seteffectto);
-        b.add_input("VALUE", to).add_field("EFFECT", effect);
+        b.add_input("VALUE", to.into()).add_field("EFFECT", effect);
         b
     })
 }
 
 pub fn clear_sound_effects() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::sound_cleareffects))
+    start(BlockNormalBuilder::new(StandardOpCode::sound_cleareffects))
 }
 
-pub fn set_volume_to(volume: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn set_volume_to(volume: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::sound_setvolumeto);
-        b.add_input("VOLUME", volume);
+        b.add_input("VOLUME", volume.into());
         b
     })
 }
 
-pub fn change_volume_by(by: Bib) -> StackBuilder {
-    StackBuilder::start({
-        let mut b = BlockNormalBuilder::new(StandardOpCode::sound_changeeffectby);
-        b.add_input("VOLUME", by);
+pub fn change_volume_by(by: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(StandardOpCode::sound_changevolumeby);
+        b.add_input("VOLUME", by.into());
         b
     })
 }
 
 pub fn volume() -> StackBuilder {
-    StackBuilder::start(BlockNormalBuilder::new(StandardOpCode::sound_volume))
+    start(BlockNormalBuilder::new(StandardOpCode::sound_volume))
 }
 
 // Data ========================================================================
@@ -1122,24 +1182,34 @@ pub fn global_list<S: Into<String>>(name: S) -> StackBuilder {
     StackBuilder::start_varlist(BlockVarListBuilder::global_list(name))
 }
 
-pub fn set_var_to(var: Bfb, to: Bib) -> StackBuilder {
-    StackBuilder::start({
+/// Sprite's variable or else the stage's
+pub fn var<S: Into<String>>(name: S) -> StackBuilder {
+    StackBuilder::start_varlist(BlockVarListBuilder::var(name))
+}
+
+/// Sprite's list or else the stage's
+pub fn list<S: Into<String>>(name: S) -> StackBuilder {
+    StackBuilder::start_varlist(BlockVarListBuilder::list(name))
+}
+
+pub fn set_var_to(var: Bfb, to: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_setvariableto);
-        b.add_input("VALUE", to).add_field("VARIABLE", var);
+        b.add_input("VALUE", to.into()).add_field("VARIABLE", var);
         b
     })
 }
 
-pub fn change_var_by(var: Bfb, by: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn change_var_by(var: Bfb, by: impl Into<Number>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_changevariableby);
-        b.add_input("VALUE", by).add_field("VARIABLE", var);
+        b.add_input("VALUE", by.into()).add_field("VARIABLE", var);
         b
     })
 }
 
 pub fn show_var(var: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_showvariable);
         b.add_field("VARIABLE", var);
         b
@@ -1147,91 +1217,91 @@ pub fn show_var(var: Bfb) -> StackBuilder {
 }
 
 pub fn hide_var(var: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_hidevariable);
         b.add_field("VARIABLE", var);
         b
     })
 }
 
-pub fn add_to_list(item: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn add_to_list(list: Bfb, item: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_addtolist);
-        b.add_input("ITEM", item);
+        b.add_input("ITEM", item.into()).add_field("LIST", list);
         b
     })
 }
 
-pub fn delete_in_list(list: Bfb, idx: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn delete_in_list(list: Bfb, idx: impl Into<Integer>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_deleteoflist);
-        b.add_input("INDEX", idx).add_field("LIST", list);
+        b.add_input("INDEX", idx.into()).add_field("LIST", list);
         b
     })
 }
 
 pub fn delete_all_in_list(list: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_deletealloflist);
         b.add_field("LIST", list);
         b
     })
 }
 
-pub fn insert_in_list(list: Bfb, idx: Bib, item: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn insert_in_list(list: Bfb, idx: impl Into<Integer>, item: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_insertatlist);
-        b.add_input("INDEX", idx)
-            .add_input("ITEM", item)
+        b.add_input("INDEX", idx.into())
+            .add_input("ITEM", item.into())
             .add_field("LIST", list);
         b
     })
 }
 
-pub fn replace_in_list(list: Bfb, idx: Bib, item: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn replace_in_list(list: Bfb, idx: impl Into<Integer>, item: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_replaceitemoflist);
-        b.add_input("INDEX", idx)
-            .add_input("ITEM", item)
+        b.add_input("INDEX", idx.into())
+            .add_input("ITEM", item.into())
             .add_field("LIST", list);
         b
     })
 }
 
-pub fn item_in_list(list: Bfb, idx: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn item_in_list(list: Bfb, idx: impl Into<Integer>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_itemoflist);
-        b.add_input("INDEX", idx).add_field("LIST", list);
+        b.add_input("INDEX", idx.into()).add_field("LIST", list);
         b
     })
 }
 
-pub fn count_of_item_in_list(list: Bfb, item: Bib) -> StackBuilder {
-    StackBuilder::start({
-        let mut b = BlockNormalBuilder::new(StandardOpCode::data_itemoflist);
-        b.add_input("ITEM", item).add_field("LIST", list);
+pub fn count_of_item_in_list(list: Bfb, item: impl Into<Text>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(StandardOpCode::data_itemnumoflist);
+        b.add_input("ITEM", item.into()).add_field("LIST", list);
         b
     })
 }
 
 pub fn length_of_list(list: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_lengthoflist);
         b.add_field("LIST", list);
         b
     })
 }
 
-pub fn list_contains(list: Bfb, item: Bib) -> StackBuilder {
-    StackBuilder::start({
+pub fn list_contains(list: Bfb, item: impl Into<Text>) -> StackBuilder {
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_listcontainsitem);
-        b.add_input("ITEM", item).add_field("LIST", list);
+        b.add_input("ITEM", item.into()).add_field("LIST", list);
         b
     })
 }
 
 pub fn show_list(list: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_showlist);
         b.add_field("LIST", list);
         b
@@ -1239,9 +1309,286 @@ pub fn show_list(list: Bfb) -> StackBuilder {
 }
 
 pub fn hide_list(list: Bfb) -> StackBuilder {
-    StackBuilder::start({
+    start({
         let mut b = BlockNormalBuilder::new(StandardOpCode::data_hidelist);
         b.add_field("LIST", list);
         b
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{BlockBuilder, StackOrValue};
+
+    fn field() -> Bfb {
+        Bfb::new("a".to_owned())
+    }
+
+    /// Every block in the stack and in its inputs
+    fn check(stack: &StackBuilder) {
+        for block in &stack.stack {
+            let BlockBuilder::Normal(block) = block else {
+                continue;
+            };
+            let mismatches: Vec<_> = schema::check_block(block)
+                .into_iter()
+                .filter(|violation| violation.is_key_mismatch())
+                .collect();
+            assert!(mismatches.is_empty(), "`{}` {mismatches:?}", block.opcode());
+            for input in block.inputs().values() {
+                for value in input.values.iter().flatten() {
+                    if let StackOrValue::Stack(stack) = value {
+                        check(stack);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn helpers_match_schema() {
+        let stacks = [
+            wait(1),
+            repeat(1, None),
+            forever(None),
+            if_(Bib::new(), None),
+            if_else(Bib::new(), None, None),
+            wait_until(Bib::new()),
+            repeat_until(Bib::new(), None),
+            stop(field(), false),
+            when_i_start_as_a_clone(),
+            create_clone_of(Bib::new()),
+            create_clone_of_menu(field()),
+            delete_this_clone(),
+            when_flag_clicked(),
+            when_key_pressed(field()),
+            when_this_sprite_clicked(),
+            when_backdrop_switches_to(field()),
+            when_greater_than(field(), 1),
+            when_broadcast_received(field()),
+            broadcast("a"),
+            broadcast_and_wait("a"),
+            think("a"),
+            think_for_secs("a", 1),
+            say("a"),
+            say_for_secs("a", 1),
+            switch_costume_to(Bib::new()),
+            costume_menu(field()),
+            next_costume(),
+            switch_backdrop_to(Bib::new()),
+            backdrop_menu(field()),
+            next_backdrop(),
+            change_size_by(1),
+            set_size_to(1),
+            change_looks_effect_by(field(), 1),
+            set_looks_effect_to(field(), 1),
+            clear_graphic_effects(),
+            show(),
+            hide(),
+            go_to_layer(field()),
+            change_layer(field(), 1),
+            costume(field()),
+            backdrop(field()),
+            size(),
+            move_steps(1),
+            turn_right(1),
+            turn_left(1),
+            go_to(Bib::new()),
+            go_to_menu(field()),
+            goto_xy(1, 1),
+            glide_to(1, Bib::new()),
+            glide_to_menu(field()),
+            glide_to_xy(1, 1, 1),
+            point_in_direction(1),
+            point_towards(Bib::new()),
+            point_towards_menu(field()),
+            set_x(1),
+            set_y(1),
+            change_x_by(1),
+            change_y_by(1),
+            if_on_edge_bounce(),
+            set_rotation_style(field()),
+            direction(),
+            y_position(),
+            x_position(),
+            add(1, 1),
+            sub(1, 1),
+            mul(1, 1),
+            div(1, 1),
+            random(1, 1),
+            less_than("a", "a"),
+            greater_than("a", "a"),
+            equals("a", "a"),
+            and(Bib::new(), Bib::new()),
+            or(Bib::new(), Bib::new()),
+            not(Bib::new()),
+            join("a", "a"),
+            letter_of(1, "a"),
+            length_of("a"),
+            contains("a", "a"),
+            modulo(1, 1),
+            round(1),
+            math_op(field(), 1),
+            touching(Bib::new()),
+            touching_menu(field()),
+            touching_color("#ff0000"),
+            color_touching_color("#ff0000", "#ff0000"),
+            distance_to(Bib::new()),
+            distance_to_menu(field()),
+            ask_and_wait("a"),
+            answer(),
+            key_pressed(Bib::new()),
+            key_menu(field()),
+            mouse_down(),
+            mouse_x(),
+            mouse_y(),
+            set_drag_mode(field()),
+            loudness(),
+            timer(),
+            reset_timer(),
+            var_of(field(), Bib::new()),
+            var_of_object_menu(field()),
+            current_datetime(field()),
+            days_since_2000(),
+            username(),
+            play_sound_until_done(Bib::new()),
+            play_sound(Bib::new()),
+            sound_menu(field()),
+            stop_all_sound(),
+            change_sound_effect_by(field(), 1),
+            set_sound_effect_to(field(), 1),
+            clear_sound_effects(),
+            set_volume_to(1),
+            change_volume_by(1),
+            volume(),
+            set_var_to(field(), "a"),
+            change_var_by(field(), 1),
+            show_var(field()),
+            hide_var(field()),
+            add_to_list(field(), "a"),
+            delete_in_list(field(), 1),
+            delete_all_in_list(field()),
+            insert_in_list(field(), 1, "a"),
+            replace_in_list(field(), 1, "a"),
+            item_in_list(field(), 1),
+            count_of_item_in_list(field(), "a"),
+            length_of_list(field()),
+            list_contains(field(), "a"),
+            show_list(field()),
+            hide_list(field()),
+            ev3::motor_turn_clockwise(Bib::new(), 1),
+            ev3::motor_turn_counter_clockwise(Bib::new(), 1),
+            ev3::motor_set_power(Bib::new(), 1),
+            ev3::motor_position(Bib::new()),
+            ev3::motor_ports_menu(field()),
+            ev3::when_button_pressed(Bib::new()),
+            ev3::button_pressed(Bib::new()),
+            ev3::sensor_ports_menu(field()),
+            ev3::when_distance_less_than(1),
+            ev3::when_brightness_less_than(1),
+            ev3::distance(),
+            ev3::brightness(),
+            ev3::beep_note(1, 1),
+            gdxfor::when_gesture(Bib::new()),
+            gdxfor::gesture_menu(field()),
+            gdxfor::when_force_pushed_or_pulled(Bib::new()),
+            gdxfor::push_pull_menu(field()),
+            gdxfor::force(),
+            gdxfor::when_tilted(Bib::new()),
+            gdxfor::is_tilted(Bib::new()),
+            gdxfor::tilt_any_menu(field()),
+            gdxfor::tilt_angle(Bib::new()),
+            gdxfor::tilt_menu(field()),
+            gdxfor::is_free_falling(),
+            gdxfor::spin_speed(Bib::new()),
+            gdxfor::acceleration(Bib::new()),
+            gdxfor::axis_menu(field()),
+            makeymakey::when_key_pressed(Bib::new()),
+            makeymakey::key_menu(field()),
+            makeymakey::when_code_pressed(Bib::new()),
+            makeymakey::sequence_menu(field()),
+            microbit::when_button_pressed(Bib::new()),
+            microbit::is_button_pressed(Bib::new()),
+            microbit::buttons_menu(field()),
+            microbit::when_gesture(Bib::new()),
+            microbit::gestures_menu(field()),
+            microbit::display_symbol(Bib::new()),
+            microbit::matrix(field()),
+            microbit::display_text("a"),
+            microbit::clear_display(),
+            microbit::when_tilted(Bib::new()),
+            microbit::is_tilted(Bib::new()),
+            microbit::tilt_direction_any_menu(field()),
+            microbit::tilt_angle(Bib::new()),
+            microbit::tilt_direction_menu(field()),
+            microbit::when_pin_connected(Bib::new()),
+            microbit::touch_pins_menu(field()),
+            music::play_drum_for_beats(Bib::new(), 1),
+            music::drum_menu(field()),
+            music::rest_for_beats(1),
+            music::play_note_for_beats(Bib::new(), 1),
+            music::note(field()),
+            music::set_instrument(Bib::new()),
+            music::instrument_menu(field()),
+            music::set_tempo(1),
+            music::change_tempo_by(1),
+            music::tempo(),
+            pen::clear(),
+            pen::stamp(),
+            pen::pen_down(),
+            pen::pen_up(),
+            pen::set_pen_color_to_color("#ff0000"),
+            pen::change_pen_color_param_by(Bib::new(), 1),
+            pen::set_pen_color_param_to(Bib::new(), 1),
+            pen::color_param_menu(field()),
+            pen::change_pen_size_by(1),
+            pen::set_pen_size_to(1),
+            pen::set_pen_shade_to(1),
+            pen::change_pen_shade_by(1),
+            pen::set_pen_hue_to(1),
+            pen::change_pen_hue_by(1),
+            text2speech::speak_and_wait("a"),
+            text2speech::set_voice(Bib::new()),
+            text2speech::voices_menu(field()),
+            text2speech::set_language(Bib::new()),
+            text2speech::languages_menu(field()),
+            translate::translate_to("a", Bib::new()),
+            translate::languages_menu(field()),
+            translate::language(),
+            video_sensing::when_video_motion_greater_than(1),
+            video_sensing::video_on(Bib::new(), Bib::new()),
+            video_sensing::attribute_menu(field()),
+            video_sensing::subject_menu(field()),
+            video_sensing::turn_video(Bib::new()),
+            video_sensing::video_state_menu(field()),
+            video_sensing::set_video_transparency_to(1),
+            wedo2::turn_motor_on_for(Bib::new(), 1),
+            wedo2::turn_motor_on(Bib::new()),
+            wedo2::turn_motor_off(Bib::new()),
+            wedo2::set_motor_power(Bib::new(), 1),
+            wedo2::set_motor_direction(Bib::new(), Bib::new()),
+            wedo2::motor_id_menu(field()),
+            wedo2::motor_direction_menu(field()),
+            wedo2::set_light_color(1),
+            wedo2::play_note_for(1, 1),
+            wedo2::when_distance(Bib::new(), 1),
+            wedo2::op_menu(field()),
+            wedo2::when_tilted(Bib::new()),
+            wedo2::is_tilted(Bib::new()),
+            wedo2::tilt_direction_any_menu(field()),
+            wedo2::distance(),
+            wedo2::tilt_angle(Bib::new()),
+            wedo2::tilt_direction_menu(field()),
+            var("a"),
+            list("a"),
+            sprite_var("a"),
+            sprite_list("a"),
+            global_var("a"),
+            global_list("a"),
+        ];
+        for stack in &stacks {
+            check(stack);
+        }
+    }
+}
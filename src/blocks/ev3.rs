@@ -0,0 +1,139 @@
+//! LEGO MINDSTORMS EV3 extension
+//!
+//! Project that uses any of these blocks will have `"ev3"` in its extensions when built.
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder},
+    input::Number,
+    opcode::Ev3OpCode,
+    stack::StackBuilder,
+};
+
+use super::start;
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+/// `port` accepts:
+///  - [`motor_ports_menu`]
+pub fn motor_turn_clockwise(port: Bib, time: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_motorTurnClockwise);
+        b.add_input("PORT", port).add_input("TIME", time.into());
+        b
+    })
+}
+
+/// `port` accepts:
+///  - [`motor_ports_menu`]
+pub fn motor_turn_counter_clockwise(port: Bib, time: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_motorTurnCounterClockwise);
+        b.add_input("PORT", port).add_input("TIME", time.into());
+        b
+    })
+}
+
+/// `port` accepts:
+///  - [`motor_ports_menu`]
+pub fn motor_set_power(port: Bib, power: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_motorSetPower);
+        b.add_input("PORT", port).add_input("POWER", power.into());
+        b
+    })
+}
+
+/// `port` accepts:
+///  - [`motor_ports_menu`]
+pub fn motor_position(port: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_getMotorPosition);
+        b.add_input("PORT", port);
+        b
+    })
+}
+
+/// Uses as an argument to motor blocks
+///
+/// Accepts:
+///  - `"0"` port A
+///  - `"1"` port B
+///  - `"2"` port C
+///  - `"3"` port D
+pub fn motor_ports_menu(port: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_menu_motorPorts);
+        b.add_field("motorPorts", port).set_shadow(true);
+        b
+    })
+}
+
+/// `port` accepts:
+///  - [`sensor_ports_menu`]
+pub fn when_button_pressed(port: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_whenButtonPressed);
+        b.add_input("PORT", port);
+        b
+    })
+}
+
+/// `port` accepts:
+///  - [`sensor_ports_menu`]
+pub fn button_pressed(port: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_buttonPressed);
+        b.add_input("PORT", port);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_button_pressed`] and [`button_pressed`]
+///
+/// Accepts:
+///  - `"0"` port 1
+///  - `"1"` port 2
+///  - `"2"` port 3
+///  - `"3"` port 4
+pub fn sensor_ports_menu(port: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_menu_sensorPorts);
+        b.add_field("sensorPorts", port).set_shadow(true);
+        b
+    })
+}
+
+pub fn when_distance_less_than(distance: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_whenDistanceLessThan);
+        b.add_input("DISTANCE", distance.into());
+        b
+    })
+}
+
+pub fn when_brightness_less_than(brightness: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_whenBrightnessLessThan);
+        // Not a typo, Scratch calls this input "DISTANCE" too
+        b.add_input("DISTANCE", brightness.into());
+        b
+    })
+}
+
+pub fn distance() -> StackBuilder {
+    start(BlockNormalBuilder::new(Ev3OpCode::ev3_getDistance))
+}
+
+pub fn brightness() -> StackBuilder {
+    start(BlockNormalBuilder::new(Ev3OpCode::ev3_getBrightness))
+}
+
+pub fn beep_note(note: impl Into<Number>, time: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Ev3OpCode::ev3_beep);
+        b.add_input("NOTE", note.into())
+            .add_input("TIME", time.into());
+        b
+    })
+}
@@ -0,0 +1,165 @@
+//! Go Direct Force & Acceleration extension
+//!
+//! Project that uses any of these blocks will have `"gdxfor"` in its extensions when built.
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder},
+    opcode::GdxforOpCode,
+    stack::StackBuilder,
+};
+
+use super::start;
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+/// `gesture` accepts:
+///  - [`gesture_menu`]
+pub fn when_gesture(gesture: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_whenGesture);
+        b.add_input("GESTURE", gesture);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_gesture`]
+///
+/// Accepts:
+///  - `"shaken"`
+///  - `"startedFalling"`
+///  - `"turnedFaceUp"`
+///  - `"turnedFaceDown"`
+pub fn gesture_menu(gesture: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_menu_gestureOptions);
+        b.add_field("gestureOptions", gesture).set_shadow(true);
+        b
+    })
+}
+
+/// `push_pull` accepts:
+///  - [`push_pull_menu`]
+pub fn when_force_pushed_or_pulled(push_pull: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_whenForcePushedOrPulled);
+        b.add_input("PUSH_PULL", push_pull);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_force_pushed_or_pulled`]
+///
+/// Accepts:
+///  - `"pushed"`
+///  - `"pulled"`
+pub fn push_pull_menu(push_pull: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_menu_pushPullOptions);
+        b.add_field("pushPullOptions", push_pull).set_shadow(true);
+        b
+    })
+}
+
+pub fn force() -> StackBuilder {
+    start(BlockNormalBuilder::new(GdxforOpCode::gdxfor_getForce))
+}
+
+/// `tilt` accepts:
+///  - [`tilt_any_menu`]
+pub fn when_tilted(tilt: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_whenTilted);
+        b.add_input("TILT", tilt);
+        b
+    })
+}
+
+/// `tilt` accepts:
+///  - [`tilt_any_menu`]
+pub fn is_tilted(tilt: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_isTilted);
+        b.add_input("TILT", tilt);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_tilted`] and [`is_tilted`]
+///
+/// Accepts:
+///  - `"front"`
+///  - `"back"`
+///  - `"left"`
+///  - `"right"`
+///  - `"any"`
+pub fn tilt_any_menu(tilt: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_menu_tiltAnyOptions);
+        b.add_field("tiltAnyOptions", tilt).set_shadow(true);
+        b
+    })
+}
+
+/// `tilt` accepts:
+///  - [`tilt_menu`]
+pub fn tilt_angle(tilt: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_getTilt);
+        b.add_input("TILT", tilt);
+        b
+    })
+}
+
+/// Uses as an argument to [`tilt_angle`]
+///
+/// Accepts:
+///  - `"front"`
+///  - `"back"`
+///  - `"left"`
+///  - `"right"`
+pub fn tilt_menu(tilt: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_menu_tiltOptions);
+        b.add_field("tiltOptions", tilt).set_shadow(true);
+        b
+    })
+}
+
+pub fn is_free_falling() -> StackBuilder {
+    start(BlockNormalBuilder::new(GdxforOpCode::gdxfor_isFreeFalling))
+}
+
+/// `direction` accepts:
+///  - [`axis_menu`]
+pub fn spin_speed(direction: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_getSpinSpeed);
+        b.add_input("DIRECTION", direction);
+        b
+    })
+}
+
+/// `direction` accepts:
+///  - [`axis_menu`]
+pub fn acceleration(direction: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_getAcceleration);
+        b.add_input("DIRECTION", direction);
+        b
+    })
+}
+
+/// Uses as an argument to [`spin_speed`] and [`acceleration`]
+///
+/// Accepts:
+///  - `"x"`
+///  - `"y"`
+///  - `"z"`
+pub fn axis_menu(axis: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(GdxforOpCode::gdxfor_menu_axisOptions);
+        b.add_field("axisOptions", axis).set_shadow(true);
+        b
+    })
+}
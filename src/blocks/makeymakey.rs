@@ -0,0 +1,76 @@
+//! Makey Makey extension
+//!
+//! Project that uses any of these blocks will have `"makeymakey"` in its extensions when built.
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder},
+    opcode::MakeyMakeyOpCode,
+    stack::StackBuilder,
+};
+
+use super::start;
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+/// `key` accepts:
+///  - [`key_menu`]
+pub fn when_key_pressed(key: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MakeyMakeyOpCode::makeymakey_whenMakeyKeyPressed);
+        b.add_input("KEY", key);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_key_pressed`]
+///
+/// Accepts:
+///  - `"SPACE"`
+///  - `"LEFT"`
+///  - `"RIGHT"`
+///  - `"UP"`
+///  - `"DOWN"`
+///  - `"w"`
+///  - `"a"`
+///  - `"s"`
+///  - `"d"`
+///  - `"f"`
+///  - `"g"`
+pub fn key_menu(key: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MakeyMakeyOpCode::makeymakey_menu_KEY);
+        b.add_field("KEY", key).set_shadow(true);
+        b
+    })
+}
+
+/// `sequence` accepts:
+///  - [`sequence_menu`]
+pub fn when_code_pressed(sequence: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MakeyMakeyOpCode::makeymakey_whenCodePressed);
+        b.add_input("SEQUENCE", sequence);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_code_pressed`]
+///
+/// Accepts:
+///  - `"LEFT UP RIGHT"`
+///  - `"RIGHT UP LEFT"`
+///  - `"LEFT RIGHT"`
+///  - `"RIGHT LEFT"`
+///  - `"UP DOWN"`
+///  - `"DOWN UP"`
+///  - `"UP RIGHT DOWN LEFT"`
+///  - `"UP LEFT DOWN RIGHT"`
+///  - `"UP UP DOWN DOWN LEFT RIGHT LEFT RIGHT"`
+pub fn sequence_menu(sequence: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MakeyMakeyOpCode::makeymakey_menu_SEQUENCE);
+        b.add_field("SEQUENCE", sequence).set_shadow(true);
+        b
+    })
+}
@@ -0,0 +1,195 @@
+//! micro:bit extension
+//!
+//! Project that uses any of these blocks will have `"microbit"` in its extensions when built.
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder},
+    input::Text,
+    opcode::MicrobitOpCode,
+    stack::StackBuilder,
+};
+
+use super::start;
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+/// `button` accepts:
+///  - [`buttons_menu`]
+pub fn when_button_pressed(button: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_whenButtonPressed);
+        b.add_input("BTN", button);
+        b
+    })
+}
+
+/// `button` accepts:
+///  - [`buttons_menu`]
+pub fn is_button_pressed(button: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_isButtonPressed);
+        b.add_input("BTN", button);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_button_pressed`] and [`is_button_pressed`]
+///
+/// Accepts:
+///  - `"A"`
+///  - `"B"`
+///  - `"any"`
+pub fn buttons_menu(button: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_menu_buttons);
+        b.add_field("buttons", button).set_shadow(true);
+        b
+    })
+}
+
+/// `gesture` accepts:
+///  - [`gestures_menu`]
+pub fn when_gesture(gesture: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_whenGesture);
+        b.add_input("GESTURE", gesture);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_gesture`]
+///
+/// Accepts:
+///  - `"moved"`
+///  - `"shaken"`
+///  - `"jumped"`
+pub fn gestures_menu(gesture: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_menu_gestures);
+        b.add_field("gestures", gesture).set_shadow(true);
+        b
+    })
+}
+
+/// `matrix` accepts:
+///  - [`matrix`]
+pub fn display_symbol(matrix: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_displaySymbol);
+        b.add_input("MATRIX", matrix);
+        b
+    })
+}
+
+/// LED matrix picker. Uses as an argument to [`display_symbol`]
+///
+/// Accepts:
+///  - 25 of `'0'` (off) or `'1'` (on), row by row from the top left.
+///    Ex. `"0101010101100010101000100"` is a heart.
+pub fn matrix(matrix: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::matrix);
+        b.add_field("MATRIX", matrix).set_shadow(true);
+        b
+    })
+}
+
+pub fn display_text(text: impl Into<Text>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_displayText);
+        b.add_input("TEXT", text.into());
+        b
+    })
+}
+
+pub fn clear_display() -> StackBuilder {
+    start(BlockNormalBuilder::new(
+        MicrobitOpCode::microbit_displayClear,
+    ))
+}
+
+/// `direction` accepts:
+///  - [`tilt_direction_any_menu`]
+pub fn when_tilted(direction: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_whenTilted);
+        b.add_input("DIRECTION", direction);
+        b
+    })
+}
+
+/// `direction` accepts:
+///  - [`tilt_direction_any_menu`]
+pub fn is_tilted(direction: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_isTilted);
+        b.add_input("DIRECTION", direction);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_tilted`] and [`is_tilted`]
+///
+/// Accepts:
+///  - `"front"`
+///  - `"back"`
+///  - `"left"`
+///  - `"right"`
+///  - `"any"`
+pub fn tilt_direction_any_menu(direction: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_menu_tiltDirectionAny);
+        b.add_field("tiltDirectionAny", direction).set_shadow(true);
+        b
+    })
+}
+
+/// `direction` accepts:
+///  - [`tilt_direction_menu`]
+pub fn tilt_angle(direction: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_getTiltAngle);
+        b.add_input("DIRECTION", direction);
+        b
+    })
+}
+
+/// Uses as an argument to [`tilt_angle`]
+///
+/// Accepts:
+///  - `"front"`
+///  - `"back"`
+///  - `"left"`
+///  - `"right"`
+pub fn tilt_direction_menu(direction: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_menu_tiltDirection);
+        b.add_field("tiltDirection", direction).set_shadow(true);
+        b
+    })
+}
+
+/// `pin` accepts:
+///  - [`touch_pins_menu`]
+pub fn when_pin_connected(pin: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_whenPinConnected);
+        b.add_input("PIN", pin);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_pin_connected`]
+///
+/// Accepts:
+///  - `"0"`
+///  - `"1"`
+///  - `"2"`
+pub fn touch_pins_menu(pin: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MicrobitOpCode::microbit_menu_touchPins);
+        b.add_field("touchPins", pin).set_shadow(true);
+        b
+    })
+}
@@ -0,0 +1,147 @@
+//! Music extension
+//!
+//! Project that uses any of these blocks will have `"music"` in its extensions when built.
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder},
+    input::Number,
+    opcode::MusicOpCode,
+    stack::StackBuilder,
+};
+
+use super::start;
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+/// `drum` accepts:
+///  - [`drum_menu`]
+pub fn play_drum_for_beats(drum: Bib, beats: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MusicOpCode::music_playDrumForBeats);
+        b.add_input("DRUM", drum).add_input("BEATS", beats.into());
+        b
+    })
+}
+
+/// Uses as an argument to [`play_drum_for_beats`]
+///
+/// Accepts:
+///  - `"1"` Snare Drum
+///  - `"2"` Bass Drum
+///  - `"3"` Side Stick
+///  - `"4"` Crash Cymbal
+///  - `"5"` Open Hi-Hat
+///  - `"6"` Closed Hi-Hat
+///  - `"7"` Tambourine
+///  - `"8"` Hand Clap
+///  - `"9"` Claves
+///  - `"10"` Wood Block
+///  - `"11"` Cowbell
+///  - `"12"` Triangle
+///  - `"13"` Bongo
+///  - `"14"` Conga
+///  - `"15"` Cabasa
+///  - `"16"` Guiro
+///  - `"17"` Vibraslap
+///  - `"18"` Cuica
+pub fn drum_menu(drum: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MusicOpCode::music_menu_DRUM);
+        b.add_field("DRUM", drum).set_shadow(true);
+        b
+    })
+}
+
+pub fn rest_for_beats(beats: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MusicOpCode::music_restForBeats);
+        b.add_input("BEATS", beats.into());
+        b
+    })
+}
+
+/// `note` accepts:
+///  - [`note`]
+///  - Midi note number
+pub fn play_note_for_beats(note: Bib, beats: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MusicOpCode::music_playNoteForBeats);
+        b.add_input("NOTE", note).add_input("BEATS", beats.into());
+        b
+    })
+}
+
+/// Note picker. Uses as an argument to [`play_note_for_beats`]
+///
+/// Accepts:
+///  - Midi note number from `"0"` to `"130"`, `"60"` is middle C
+pub fn note(note: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MusicOpCode::note);
+        b.add_field("NOTE", note).set_shadow(true);
+        b
+    })
+}
+
+/// `instrument` accepts:
+///  - [`instrument_menu`]
+pub fn set_instrument(instrument: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MusicOpCode::music_setInstrument);
+        b.add_input("INSTRUMENT", instrument);
+        b
+    })
+}
+
+/// Uses as an argument to [`set_instrument`]
+///
+/// Accepts:
+///  - `"1"` Piano
+///  - `"2"` Electric Piano
+///  - `"3"` Organ
+///  - `"4"` Guitar
+///  - `"5"` Electric Guitar
+///  - `"6"` Bass
+///  - `"7"` Pizzicato
+///  - `"8"` Cello
+///  - `"9"` Trombone
+///  - `"10"` Clarinet
+///  - `"11"` Saxophone
+///  - `"12"` Flute
+///  - `"13"` Wooden Flute
+///  - `"14"` Bassoon
+///  - `"15"` Choir
+///  - `"16"` Vibraphone
+///  - `"17"` Music Box
+///  - `"18"` Steel Drum
+///  - `"19"` Marimba
+///  - `"20"` Synth Lead
+///  - `"21"` Synth Pad
+pub fn instrument_menu(instrument: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MusicOpCode::music_menu_INSTRUMENT);
+        b.add_field("INSTRUMENT", instrument).set_shadow(true);
+        b
+    })
+}
+
+pub fn set_tempo(tempo: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MusicOpCode::music_setTempo);
+        b.add_input("TEMPO", tempo.into());
+        b
+    })
+}
+
+pub fn change_tempo_by(by: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(MusicOpCode::music_changeTempo);
+        b.add_input("TEMPO", by.into());
+        b
+    })
+}
+
+pub fn tempo() -> StackBuilder {
+    start(BlockNormalBuilder::new(MusicOpCode::music_getTempo))
+}
@@ -0,0 +1,131 @@
+//! Pen extension
+//!
+//! Project that uses any of these blocks will have `"pen"` in its extensions when built.
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder},
+    input::{Color, Number},
+    opcode::PenExtensionOpCode,
+    stack::StackBuilder,
+};
+
+use super::start;
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+pub fn clear() -> StackBuilder {
+    start(BlockNormalBuilder::new(PenExtensionOpCode::pen_clear))
+}
+
+pub fn stamp() -> StackBuilder {
+    start(BlockNormalBuilder::new(PenExtensionOpCode::pen_stamp))
+}
+
+pub fn pen_down() -> StackBuilder {
+    start(BlockNormalBuilder::new(PenExtensionOpCode::pen_penDown))
+}
+
+pub fn pen_up() -> StackBuilder {
+    start(BlockNormalBuilder::new(PenExtensionOpCode::pen_penUp))
+}
+
+/// <br/>
+/// Accepts:
+///  - Color
+pub fn set_pen_color_to_color(color: impl Into<Color>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(PenExtensionOpCode::pen_setPenColorToColor);
+        b.add_input("COLOR", color.into());
+        b
+    })
+}
+
+/// `param` accepts:
+///  - [`color_param_menu`]
+pub fn change_pen_color_param_by(param: Bib, by: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(PenExtensionOpCode::pen_changePenColorParamBy);
+        b.add_input("COLOR_PARAM", param)
+            .add_input("VALUE", by.into());
+        b
+    })
+}
+
+/// `param` accepts:
+///  - [`color_param_menu`]
+pub fn set_pen_color_param_to(param: Bib, to: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(PenExtensionOpCode::pen_setPenColorParamTo);
+        b.add_input("COLOR_PARAM", param)
+            .add_input("VALUE", to.into());
+        b
+    })
+}
+
+/// Uses as an argument to [`change_pen_color_param_by`] and [`set_pen_color_param_to`]
+///
+/// Accepts:
+///  - `"color"`
+///  - `"saturation"`
+///  - `"brightness"`
+///  - `"transparency"`
+pub fn color_param_menu(param: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(PenExtensionOpCode::pen_menu_colorParam);
+        b.add_field("colorParam", param).set_shadow(true);
+        b
+    })
+}
+
+pub fn change_pen_size_by(by: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(PenExtensionOpCode::pen_changePenSizeBy);
+        b.add_input("SIZE", by.into());
+        b
+    })
+}
+
+pub fn set_pen_size_to(to: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(PenExtensionOpCode::pen_setPenSizeTo);
+        b.add_input("SIZE", to.into());
+        b
+    })
+}
+
+/// Legacy block from Scratch 2.0
+pub fn set_pen_shade_to(to: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(PenExtensionOpCode::pen_setPenShadeToNumber);
+        b.add_input("SHADE", to.into());
+        b
+    })
+}
+
+/// Legacy block from Scratch 2.0
+pub fn change_pen_shade_by(by: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(PenExtensionOpCode::pen_changePenShadeBy);
+        b.add_input("SHADE", by.into());
+        b
+    })
+}
+
+/// Legacy block from Scratch 2.0
+pub fn set_pen_hue_to(to: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(PenExtensionOpCode::pen_setPenHueToNumber);
+        b.add_input("HUE", to.into());
+        b
+    })
+}
+
+/// Legacy block from Scratch 2.0
+pub fn change_pen_hue_by(by: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(PenExtensionOpCode::pen_changePenHueBy);
+        b.add_input("HUE", by.into());
+        b
+    })
+}
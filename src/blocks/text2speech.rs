@@ -0,0 +1,75 @@
+//! Text to Speech extension
+//!
+//! Project that uses any of these blocks will have `"text2speech"` in its extensions when built.
+//! Default language is set by [`crate::target::StageBuilder::set_text_to_speech_language`].
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder},
+    input::Text,
+    opcode::Text2SpeechOpCode,
+    stack::StackBuilder,
+};
+
+use super::start;
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+pub fn speak_and_wait(words: impl Into<Text>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Text2SpeechOpCode::text2speech_speakAndWait);
+        b.add_input("WORDS", words.into());
+        b
+    })
+}
+
+/// `voice` accepts:
+///  - [`voices_menu`]
+pub fn set_voice(voice: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Text2SpeechOpCode::text2speech_setVoice);
+        b.add_input("VOICE", voice);
+        b
+    })
+}
+
+/// Uses as an argument to [`set_voice`]
+///
+/// Accepts:
+///  - `"ALTO"`
+///  - `"TENOR"`
+///  - `"SQUEAK"`
+///  - `"GIANT"`
+///  - `"KITTEN"`
+pub fn voices_menu(voice: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Text2SpeechOpCode::text2speech_menu_voices);
+        b.add_field("voices", voice).set_shadow(true);
+        b
+    })
+}
+
+/// `language` accepts:
+///  - [`languages_menu`]
+pub fn set_language(language: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Text2SpeechOpCode::text2speech_setLanguage);
+        b.add_input("LANGUAGE", language);
+        b
+    })
+}
+
+/// Uses as an argument to [`set_language`]
+///
+/// Accepts language code, ex.
+///  - `"en"`
+///  - `"ja"`
+///  - `"zh-cn"`
+///  - `"pt-br"`
+pub fn languages_menu(language: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Text2SpeechOpCode::text2speech_menu_languages);
+        b.add_field("languages", language).set_shadow(true);
+        b
+    })
+}
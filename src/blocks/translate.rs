@@ -0,0 +1,47 @@
+//! Translate extension
+//!
+//! Project that uses any of these blocks will have `"translate"` in its extensions when built.
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder},
+    input::Text,
+    opcode::TranslateOpCode,
+    stack::StackBuilder,
+};
+
+use super::start;
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+/// `language` accepts:
+///  - [`languages_menu`]
+pub fn translate_to(words: impl Into<Text>, language: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(TranslateOpCode::translate_getTranslate);
+        b.add_input("WORDS", words.into())
+            .add_input("LANGUAGE", language);
+        b
+    })
+}
+
+/// Uses as an argument to [`translate_to`]
+///
+/// Accepts language code, ex.
+///  - `"en"`
+///  - `"ja"`
+///  - `"zh-cn"`
+///  - `"es"`
+pub fn languages_menu(language: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(TranslateOpCode::translate_menu_languages);
+        b.add_field("languages", language).set_shadow(true);
+        b
+    })
+}
+
+pub fn language() -> StackBuilder {
+    start(BlockNormalBuilder::new(
+        TranslateOpCode::translate_getViewerLanguage,
+    ))
+}
@@ -0,0 +1,97 @@
+//! Video Sensing extension
+//!
+//! Project that uses any of these blocks will have `"videoSensing"` in its extensions when built.
+//! Camera state when the project starts is set by [`crate::target::StageBuilder::set_video_state`]
+//! and [`crate::target::StageBuilder::set_video_transparency`].
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder},
+    input::Number,
+    opcode::VideoSensingOpCode,
+    stack::StackBuilder,
+};
+
+use super::start;
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+pub fn when_video_motion_greater_than(reference: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(VideoSensingOpCode::videoSensing_whenMotionGreaterThan);
+        b.add_input("REFERENCE", reference.into());
+        b
+    })
+}
+
+/// `attribute` accepts:
+///  - [`attribute_menu`]
+///
+/// `subject` accepts:
+///  - [`subject_menu`]
+pub fn video_on(attribute: Bib, subject: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(VideoSensingOpCode::videoSensing_videoOn);
+        b.add_input("ATTRIBUTE", attribute)
+            .add_input("SUBJECT", subject);
+        b
+    })
+}
+
+/// Uses as an argument to [`video_on`]
+///
+/// Accepts:
+///  - `"motion"`
+///  - `"direction"`
+pub fn attribute_menu(attribute: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(VideoSensingOpCode::videoSensing_menu_ATTRIBUTE);
+        b.add_field("ATTRIBUTE", attribute).set_shadow(true);
+        b
+    })
+}
+
+/// Uses as an argument to [`video_on`]
+///
+/// Accepts:
+///  - `"sprite"`
+///  - `"stage"`
+pub fn subject_menu(subject: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(VideoSensingOpCode::videoSensing_menu_SUBJECT);
+        b.add_field("SUBJECT", subject).set_shadow(true);
+        b
+    })
+}
+
+/// `video_state` accepts:
+///  - [`video_state_menu`]
+pub fn turn_video(video_state: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(VideoSensingOpCode::videoSensing_videoToggle);
+        b.add_input("VIDEO_STATE", video_state);
+        b
+    })
+}
+
+/// Uses as an argument to [`turn_video`]
+///
+/// Accepts:
+///  - `"off"`
+///  - `"on"`
+///  - `"on-flipped"`
+pub fn video_state_menu(video_state: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(VideoSensingOpCode::videoSensing_menu_VIDEO_STATE);
+        b.add_field("VIDEO_STATE", video_state).set_shadow(true);
+        b
+    })
+}
+
+pub fn set_video_transparency_to(transparency: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(VideoSensingOpCode::videoSensing_setVideoTransparency);
+        b.add_input("TRANSPARENCY", transparency.into());
+        b
+    })
+}
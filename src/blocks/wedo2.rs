@@ -0,0 +1,208 @@
+//! LEGO Education WeDo 2.0 extension
+//!
+//! Project that uses any of these blocks will have `"wedo2"` in its extensions when built.
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder},
+    input::Number,
+    opcode::Wedo2OpCode,
+    stack::StackBuilder,
+};
+
+use super::start;
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+/// `motor` accepts:
+///  - [`motor_id_menu`]
+pub fn turn_motor_on_for(motor: Bib, duration: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_motorOnFor);
+        b.add_input("MOTOR_ID", motor)
+            .add_input("DURATION", duration.into());
+        b
+    })
+}
+
+/// `motor` accepts:
+///  - [`motor_id_menu`]
+pub fn turn_motor_on(motor: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_motorOn);
+        b.add_input("MOTOR_ID", motor);
+        b
+    })
+}
+
+/// `motor` accepts:
+///  - [`motor_id_menu`]
+pub fn turn_motor_off(motor: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_motorOff);
+        b.add_input("MOTOR_ID", motor);
+        b
+    })
+}
+
+/// `motor` accepts:
+///  - [`motor_id_menu`]
+pub fn set_motor_power(motor: Bib, power: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_startMotorPower);
+        b.add_input("MOTOR_ID", motor)
+            .add_input("POWER", power.into());
+        b
+    })
+}
+
+/// `motor` accepts:
+///  - [`motor_id_menu`]
+///
+/// `direction` accepts:
+///  - [`motor_direction_menu`]
+pub fn set_motor_direction(motor: Bib, direction: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_setMotorDirection);
+        b.add_input("MOTOR_ID", motor)
+            .add_input("MOTOR_DIRECTION", direction);
+        b
+    })
+}
+
+/// Uses as an argument to motor blocks
+///
+/// Accepts:
+///  - `"motor"`
+///  - `"motor A"`
+///  - `"motor B"`
+///  - `"all motors"`
+pub fn motor_id_menu(motor: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_menu_MOTOR_ID);
+        b.add_field("MOTOR_ID", motor).set_shadow(true);
+        b
+    })
+}
+
+/// Uses as an argument to [`set_motor_direction`]
+///
+/// Accepts:
+///  - `"this way"`
+///  - `"that way"`
+///  - `"reverse"`
+pub fn motor_direction_menu(direction: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_menu_MOTOR_DIRECTION);
+        b.add_field("MOTOR_DIRECTION", direction).set_shadow(true);
+        b
+    })
+}
+
+pub fn set_light_color(hue: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_setLightHue);
+        b.add_input("HUE", hue.into());
+        b
+    })
+}
+
+/// Hidden in the editor's palette but still works
+pub fn play_note_for(note: impl Into<Number>, duration: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_playNoteFor);
+        b.add_input("NOTE", note.into())
+            .add_input("DURATION", duration.into());
+        b
+    })
+}
+
+/// `op` accepts:
+///  - [`op_menu`]
+pub fn when_distance(op: Bib, reference: impl Into<Number>) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_whenDistance);
+        b.add_input("OP", op)
+            .add_input("REFERENCE", reference.into());
+        b
+    })
+}
+
+/// Uses as an argument to [`when_distance`]
+///
+/// Accepts:
+///  - `"<"`
+///  - `">"`
+pub fn op_menu(op: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_menu_OP);
+        b.add_field("OP", op).set_shadow(true);
+        b
+    })
+}
+
+/// `direction` accepts:
+///  - [`tilt_direction_any_menu`]
+pub fn when_tilted(direction: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_whenTilted);
+        b.add_input("TILT_DIRECTION_ANY", direction);
+        b
+    })
+}
+
+/// `direction` accepts:
+///  - [`tilt_direction_any_menu`]
+pub fn is_tilted(direction: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_isTilted);
+        b.add_input("TILT_DIRECTION_ANY", direction);
+        b
+    })
+}
+
+/// Uses as an argument to [`when_tilted`] and [`is_tilted`]
+///
+/// Accepts:
+///  - `"up"`
+///  - `"down"`
+///  - `"left"`
+///  - `"right"`
+///  - `"any"`
+pub fn tilt_direction_any_menu(direction: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_menu_TILT_DIRECTION_ANY);
+        b.add_field("TILT_DIRECTION_ANY", direction)
+            .set_shadow(true);
+        b
+    })
+}
+
+pub fn distance() -> StackBuilder {
+    start(BlockNormalBuilder::new(Wedo2OpCode::wedo2_getDistance))
+}
+
+/// `direction` accepts:
+///  - [`tilt_direction_menu`]
+pub fn tilt_angle(direction: Bib) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_getTiltAngle);
+        b.add_input("TILT_DIRECTION", direction);
+        b
+    })
+}
+
+/// Uses as an argument to [`tilt_angle`]
+///
+/// Accepts:
+///  - `"up"`
+///  - `"down"`
+///  - `"left"`
+///  - `"right"`
+pub fn tilt_direction_menu(direction: Bfb) -> StackBuilder {
+    start({
+        let mut b = BlockNormalBuilder::new(Wedo2OpCode::wedo2_menu_TILT_DIRECTION);
+        b.add_field("TILT_DIRECTION", direction).set_shadow(true);
+        b
+    })
+}
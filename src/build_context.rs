@@ -14,3 +14,19 @@ pub struct TargetContext<'a> {
     pub this_sprite_lists: &'a HashMap<String, Uid>,
     pub all_broadcasts: &'a HashMap<String, Uid>,
 }
+
+impl TargetContext<'_> {
+    /// Sprite's own variable first and then the stage's, same as how Scratch finds it by name
+    pub fn resolve_var(&self, name: &str) -> Option<&Uid> {
+        self.this_sprite_vars
+            .get(name)
+            .or_else(|| self.global_vars.get(name))
+    }
+
+    /// Same as [`TargetContext::resolve_var`] for list
+    pub fn resolve_list(&self, name: &str) -> Option<&Uid> {
+        self.this_sprite_lists
+            .get(name)
+            .or_else(|| self.global_lists.get(name))
+    }
+}
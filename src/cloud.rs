@@ -0,0 +1,356 @@
+//! Cloud variables and packing text into them
+//!
+//! Scratch only keeps cloud variables that are on the stage, named with the [`CLOUD_PREFIX`]
+//! and hold a number, at most [`MAX_CLOUD_VARIABLES`] of them in a project.
+//! [`crate::validate`] reports the ones that break these.
+//!
+//! Since they're numbers, text is packed as two digits for each character,
+//! its position in [`CHARSET`] counting from 1 plus 10. Ex. `"hi"` is `"1920"`.
+//! [`encode`] and [`decode`] do this in Rust and [`CloudCodec`] makes custom blocks that do it
+//! in the project, so both sides agree on the format.
+//! ```ignore
+//! let codec = CloudCodec::new();
+//! codec.add_to(&mut sprite.target)?;
+//! stage.add_cloud_variable("high score", 0.);
+//! // ...
+//! let high_score = BlockFieldBuilder::new_with_kind("high score".to_owned(), FieldKind::Variable);
+//! codec.encode(join("bob:", var("score")))
+//!     .next(set_var_to(high_score, CloudCodec::result()))
+//! ```
+
+use sb_sbity::value::Value;
+
+use crate::{
+    block::{BlockFieldBuilder, FieldKind},
+    block_definer::ProcedureBuilder,
+    blocks::{
+        add, change_var_by, count_of_item_in_list, div, item_in_list, join, length_of, letter_of,
+        repeat, set_var_to, sprite_var, sub,
+    },
+    data::{ListBuilder, VariableBuilder},
+    dsl::substack,
+    input::Text,
+    stack::{StackBuilder, StackError},
+    target::TargetBuilder,
+};
+
+type Bfb = BlockFieldBuilder;
+
+/// Scratch only treats a variable as a cloud variable when its name starts with this
+pub const CLOUD_PREFIX: &str = "☁ ";
+pub const MAX_CLOUD_VARIABLES: usize = 10;
+/// Longest value a cloud variable can hold
+pub const MAX_CLOUD_DIGITS: usize = 256;
+
+/// Characters that can be packed. Scratch compares text without case so there's no uppercase,
+/// uppercase letters are packed as lowercase.
+pub const CHARSET: &str = " abcdefghijklmnopqrstuvwxyz0123456789!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+/// Code of the first character in [`CHARSET`], so every code is two digits
+const CODE_OFFSET: usize = 10;
+
+/// Names of what [`CloudCodec`] adds to the sprite
+const RESULT: &str = "cloud result";
+const INDEX: &str = "cloud index";
+const CHARSET_LIST: &str = "cloud charset";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloudError {
+    /// Character that isn't in [`CHARSET`]
+    UnsupportedChar(char),
+    /// Packed text is longer than [`MAX_CLOUD_DIGITS`]
+    TooLong(usize),
+    /// Value that isn't made by [`encode`]
+    InvalidCode(String),
+}
+
+impl std::error::Error for CloudError {}
+
+impl std::fmt::Display for CloudError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CloudError::UnsupportedChar(c) => write!(f, "`{c}` can't be stored in cloud variable"),
+            CloudError::TooLong(digits) => write!(
+                f,
+                "{digits} digits is longer than the {MAX_CLOUD_DIGITS} that cloud variable can hold"
+            ),
+            CloudError::InvalidCode(value) => write!(f, "`{value}` isn't a packed text"),
+        }
+    }
+}
+
+/// Name with the [`CLOUD_PREFIX`], it's not added again when it's already there
+pub fn cloud_name(name: &str) -> String {
+    match name.starts_with(CLOUD_PREFIX) {
+        true => name.to_owned(),
+        false => format!("{CLOUD_PREFIX}{name}"),
+    }
+}
+
+/// Pack `text` into digits for a cloud variable
+pub fn encode(text: &str) -> Result<String, CloudError> {
+    let digits = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| {
+            let position = CHARSET
+                .chars()
+                .position(|charset_c| charset_c == c)
+                .ok_or(CloudError::UnsupportedChar(c))?;
+            Ok((position + 1 + CODE_OFFSET).to_string())
+        })
+        .collect::<Result<String, CloudError>>()?;
+    if digits.len() > MAX_CLOUD_DIGITS {
+        return Err(CloudError::TooLong(digits.len()));
+    }
+    Ok(digits)
+}
+
+/// Unpack text from digits made by [`encode`] or [`CloudCodec::encode`]
+pub fn decode(digits: &str) -> Result<String, CloudError> {
+    let invalid = || CloudError::InvalidCode(digits.to_owned());
+    if !digits.len().is_multiple_of(2) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    digits
+        .as_bytes()
+        .chunks(2)
+        .map(|code| {
+            // Always 2 ascii digits from the check above
+            let code: usize = std::str::from_utf8(code).unwrap().parse().unwrap();
+            code.checked_sub(CODE_OFFSET + 1)
+                .and_then(|position| CHARSET.chars().nth(position))
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+/// Custom blocks that [`encode`] and [`decode`] in the project.
+///
+/// Both put what they made in the `cloud result` sprite variable, see [`CloudCodec::result`].
+/// A character that isn't in [`CHARSET`] is packed as `10` and unpacked as nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloudCodec {
+    encode: ProcedureBuilder,
+    decode: ProcedureBuilder,
+}
+
+impl CloudCodec {
+    pub fn new() -> CloudCodec {
+        let mut encode = ProcedureBuilder::new("cloud encode %s", vec!["text"])
+            .expect("proccode has one placeholder");
+        encode.set_warp(true);
+        let mut decode = ProcedureBuilder::new("cloud decode %s", vec!["number"])
+            .expect("proccode has one placeholder");
+        decode.set_warp(true);
+        CloudCodec { encode, decode }
+    }
+
+    /// Add the custom blocks with the variables and list they use to `target`.
+    /// It should be a sprite since the variables are local.
+    pub fn add_to(&self, target: &mut TargetBuilder) -> Result<&Self, StackError> {
+        target
+            .add_variable(RESULT, VariableBuilder::new(Value::Text(String::new())))
+            .add_variable(INDEX, VariableBuilder::new(Value::Number(1_i64.into())))
+            .add_list(
+                CHARSET_LIST,
+                ListBuilder::new(
+                    CHARSET
+                        .chars()
+                        .map(|c| Value::Text(c.to_string()))
+                        .collect(),
+                ),
+            )
+            .add_block_stack(self.encode_definition()?)
+            .add_block_stack(self.decode_definition()?);
+        Ok(self)
+    }
+
+    /// Block that packs `text` into [`CloudCodec::result`]
+    pub fn encode(&self, text: impl Into<Text>) -> StackBuilder {
+        let text: Text = text.into();
+        self.encode
+            .call(vec![text.into()])
+            .expect("encode has one argument")
+    }
+
+    /// Block that unpacks `number` into [`CloudCodec::result`]
+    pub fn decode(&self, number: impl Into<Text>) -> StackBuilder {
+        let number: Text = number.into();
+        self.decode
+            .call(vec![number.into()])
+            .expect("decode has one argument")
+    }
+
+    /// Reporter of what the last encode or decode made
+    pub fn result() -> StackBuilder {
+        sprite_var(RESULT)
+    }
+
+    fn encode_definition(&self) -> Result<StackBuilder, StackError> {
+        let text = || self.encode.argument("text").unwrap();
+        let code = add(
+            count_of_item_in_list(list_field(), letter_of(sprite_var(INDEX), text())),
+            CODE_OFFSET as i32,
+        );
+        self.encode
+            .definition()
+            .try_next(set_var_to(var_field(RESULT), ""))?
+            .try_next(set_var_to(var_field(INDEX), 1))?
+            .try_next(repeat(
+                length_of(text()),
+                substack(vec![
+                    set_var_to(var_field(RESULT), join(sprite_var(RESULT), code)),
+                    change_var_by(var_field(INDEX), 1),
+                ])?,
+            ))
+    }
+
+    fn decode_definition(&self) -> Result<StackBuilder, StackError> {
+        let number = || self.decode.argument("number").unwrap();
+        let code = join(
+            letter_of(sprite_var(INDEX), number()),
+            letter_of(add(sprite_var(INDEX), 1), number()),
+        );
+        let c = item_in_list(list_field(), sub(code, CODE_OFFSET as i32));
+        self.decode
+            .definition()
+            .try_next(set_var_to(var_field(RESULT), ""))?
+            .try_next(set_var_to(var_field(INDEX), 1))?
+            .try_next(repeat(
+                div(length_of(number()), 2),
+                substack(vec![
+                    set_var_to(var_field(RESULT), join(sprite_var(RESULT), c)),
+                    change_var_by(var_field(INDEX), 2),
+                ])?,
+            ))
+    }
+}
+
+impl Default for CloudCodec {
+    fn default() -> Self {
+        CloudCodec::new()
+    }
+}
+
+fn var_field(name: &str) -> Bfb {
+    Bfb::new_with_kind(name.to_owned(), FieldKind::SpriteVariable)
+}
+
+fn list_field() -> Bfb {
+    Bfb::new_with_kind(CHARSET_LIST.to_owned(), FieldKind::SpriteList)
+}
+
+#[cfg(test)]
+mod tests {
+    use sb_sbity::block::BlockMutationEnum;
+
+    use super::*;
+    use crate::{
+        asset::{AssetBuilder, CostumeBuilder},
+        block::{BlockBuilder, StackOrValue},
+        project::ProjectBuilder,
+        resource::Resource,
+        target::SpriteBuilder,
+    };
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let text = "bob: 1st place! {100%}";
+        let digits = encode(text).unwrap();
+        assert!(digits.bytes().all(|b| b.is_ascii_digit()));
+        assert_eq!(decode(&digits).unwrap(), text);
+        assert_eq!(encode("hi").unwrap(), "1920");
+        assert_eq!(encode("").unwrap(), "");
+    }
+
+    #[test]
+    fn uppercase_is_packed_as_lowercase() {
+        assert_eq!(encode("Hi").unwrap(), encode("hi").unwrap());
+        assert_eq!(decode(&encode("HELLO").unwrap()).unwrap(), "hello");
+    }
+
+    #[test]
+    fn unsupported_char() {
+        assert_eq!(encode("café"), Err(CloudError::UnsupportedChar('é')));
+        assert_eq!(encode("a\nb"), Err(CloudError::UnsupportedChar('\n')));
+    }
+
+    #[test]
+    fn too_long_for_cloud_variable() {
+        let longest = "a".repeat(MAX_CLOUD_DIGITS / 2);
+        assert_eq!(encode(&longest).unwrap().len(), MAX_CLOUD_DIGITS);
+        assert_eq!(
+            encode(&format!("{longest}a")),
+            Err(CloudError::TooLong(MAX_CLOUD_DIGITS + 2))
+        );
+    }
+
+    #[test]
+    fn invalid_code() {
+        for digits in ["192", "19a0", "-1", "1910", "99"] {
+            assert_eq!(
+                decode(digits),
+                Err(CloudError::InvalidCode(digits.to_owned())),
+                "{digits}"
+            );
+        }
+    }
+
+    fn proccode(stack: &StackBuilder) -> String {
+        let BlockBuilder::Normal(block) = &stack.stack[0] else {
+            panic!("expected a normal block");
+        };
+        let block = match block.inputs().get("custom_block") {
+            // Definition, the proccode is in its prototype
+            Some(input) => match &input.values[0] {
+                Some(StackOrValue::Stack(prototype)) => return proccode(prototype),
+                other => panic!("expected the prototype, found {other:?}"),
+            },
+            None => block,
+        };
+        match &block.mutation().unwrap().mutation_enum {
+            BlockMutationEnum::ProceduresPrototype { proccode, .. }
+            | BlockMutationEnum::ProceduresCall { proccode, .. } => proccode.clone(),
+            other => panic!("expected a custom block mutation, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn codec_adds_what_its_blocks_use() {
+        let codec = CloudCodec::new();
+        let mut sprite = SpriteBuilder::default();
+        codec.add_to(&mut sprite.target).unwrap();
+        let target = &sprite.target;
+        assert!(target.variables.contains_key(RESULT));
+        assert!(target.variables.contains_key(INDEX));
+        // Item number in the list is the position in CHARSET counting from 1, same as `encode`
+        let charset: Vec<Value> = CHARSET
+            .chars()
+            .map(|c| Value::Text(c.to_string()))
+            .collect();
+        assert_eq!(target.lists[CHARSET_LIST].values, charset);
+
+        let definitions: Vec<String> = target.block_stackes.iter().map(proccode).collect();
+        assert_eq!(definitions, ["cloud encode %s", "cloud decode %s"]);
+        assert_eq!(proccode(&codec.encode("hi")), definitions[0]);
+        assert_eq!(proccode(&codec.decode("1920")), definitions[1]);
+
+        sprite
+            .target
+            .add_block_stack(codec.encode("hi").next(codec.decode(CloudCodec::result())));
+        let costume = || {
+            let svg = Resource::new("svg".to_owned(), b"<svg/>".to_vec()).unwrap();
+            CostumeBuilder::new(AssetBuilder::new("costume", svg))
+        };
+        sprite
+            .target
+            .set_name("Sprite1")
+            .set_layer_order(1)
+            .add_costume(costume());
+        let mut project = ProjectBuilder::default();
+        project.add_sprite(sprite);
+        project.stage_builder.target.add_costume(costume());
+        assert_eq!(project.validate(), []);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::uid::Uid;
+use crate::{uid::Uid, value::number_to_f64};
 use sb_sbity::comment::Comment;
 
 #[rustfmt::skip]
@@ -89,6 +89,28 @@ impl CommentBuilder {
             text: content,
         }
     }
+
+    /// Reverse of [`CommentBuilder::build`]
+    pub fn from_comment(comment: Comment) -> CommentBuilder {
+        let Comment {
+            block_id,
+            x,
+            y,
+            width,
+            height,
+            minimized,
+            text,
+        } = comment;
+        CommentBuilder {
+            block_uid: block_id.map(Uid::new),
+            x: x.as_ref().map(number_to_f64),
+            y: y.as_ref().map(number_to_f64),
+            width: number_to_f64(&width).max(0.) as u64,
+            height: number_to_f64(&height).max(0.) as u64,
+            minimized,
+            content: text,
+        }
+    }
 }
 
 impl Default for CommentBuilder {
@@ -1,4 +1,4 @@
-use crate::uid::Uid;
+use crate::uid::{Uid, UidGenerator};
 use sb_sbity::{list::List, value::Value, variable::Variable};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -33,12 +33,12 @@ impl VariableBuilder {
         self
     }
 
-    pub fn build(self, name_for_this_var: String) -> (Variable, Uid) {
+    pub fn build(self, name_for_this_var: String, uid_gen: &mut UidGenerator) -> (Variable, Uid) {
         let VariableBuilder {
             value,
             is_cloud_variable,
         } = self;
-        let my_uid = Uid::generate();
+        let my_uid = uid_gen.generate();
         let var = Variable {
             name: name_for_this_var,
             value,
@@ -58,9 +58,9 @@ impl ListBuilder {
         ListBuilder { values }
     }
 
-    pub fn build(self, name_for_this_list: String) -> (List, Uid) {
+    pub fn build(self, name_for_this_list: String, uid_gen: &mut UidGenerator) -> (List, Uid) {
         let ListBuilder { values } = self;
-        let my_uid = Uid::generate();
+        let my_uid = uid_gen.generate();
         let list = List {
             name: name_for_this_list,
             values,
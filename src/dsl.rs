@@ -0,0 +1,659 @@
+//! [`scratch!`](crate::scratch) macro that writes scripts in a readable block syntax
+//!
+//! Functions in here are used by the macro's expansion, you don't need to call them yourself.
+
+use sb_sbity::block::ShadowInputType;
+
+use crate::{
+    block::{BlockFieldBuilder, BlockInputBuilder, FieldKind, StackOrValue},
+    stack::{StackBuilder, StackError},
+};
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+/// Chain the hat and its body into one script.
+/// Error when the blocks can't connect, see [`StackBuilder::try_next`].
+#[doc(hidden)]
+pub fn script(
+    hat: Option<StackBuilder>,
+    body: Vec<StackBuilder>,
+) -> Result<StackBuilder, StackError> {
+    let mut body = body.into_iter();
+    let first = match hat {
+        Some(hat) => hat,
+        None => body.next().unwrap_or_default(),
+    };
+    body.try_fold(first, StackBuilder::try_next)
+}
+
+/// Body of a C block, None when it's empty
+#[doc(hidden)]
+pub fn substack(body: Vec<StackBuilder>) -> Result<Option<Bib>, StackError> {
+    if body.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Bib::stack(script(None, body)?)))
+}
+
+/// Menu shadow in an input, the same as when it's picked in the editor
+#[doc(hidden)]
+pub fn menu(menu: StackBuilder) -> Bib {
+    let mut b = Bib::new();
+    b.set_shadow(ShadowInputType::Shadow)
+        .add_input(Some(StackOrValue::Stack(menu)));
+    b
+}
+
+#[doc(hidden)]
+pub fn field(value: &str, kind: FieldKind) -> Bfb {
+    Bfb::new_with_kind(value.to_owned(), kind)
+}
+
+/// Build scripts from a readable block syntax, it expands to a `Result<Vec<StackBuilder>, StackError>`
+/// with a [`StackBuilder`] for each script. Error when blocks in a body can't connect,
+/// see [`StackBuilder::try_next`].
+/// ```ignore
+/// let x = 2;
+/// let scripts = scratch! {
+///     when flag clicked {
+///         say (join "hi " (x * 2));
+///         forever {
+///             move 10;
+///             if (key "space" pressed) {
+///                 change var "score" by 1;
+///             }
+///         }
+///     }
+///     when I receive "game over" {
+///         stop all;
+///     }
+/// }?;
+/// ```
+/// Script is a hat followed by its body in `{}`. Body without a hat is a script on its own.
+/// Blocks in a body are separated by `;`, C blocks don't need one after their `}`.
+///
+/// Input is a literal, a Rust variable, a Rust expression in `{}`, or a reporter in `()`.
+/// Reporters are nested with `()` too since there's no operator precedence,
+/// ex. `((1 + 2) * 3)`. Negative number needs to be in `()` like `(-5)`.
+///
+/// Variables and lists are written as `var "name"` and `list "name"` for the sprite's one
+/// or else the stage's like [`crate::blocks::var`],
+/// or `sprite_var "name"` and `sprite_list "name"` for ones only in the sprite.
+///
+/// Unknown block is a compile error.
+/// ```compile_fail
+/// let scripts = sb_itchy::scratch! {
+///     when flag clicked {
+///         fly 10;
+///     }
+/// };
+/// ```
+/// Each block in a body is one level of macro recursion,
+/// add `#![recursion_limit = "256"]` to your crate when a body is very long.
+#[macro_export]
+macro_rules! scratch {
+    // Scripts =================================================================
+    (@scripts $v:ident;) => {};
+    (@scripts $v:ident; $($rest:tt)+) => { $crate::scratch!(@script $v; [] $($rest)+); };
+    (@script $v:ident; [$($hat:tt)*] { $($body:tt)* } $($rest:tt)*) => {
+        $v.push($crate::dsl::script(
+            $crate::scratch!(@hat $($hat)*),
+            $crate::scratch!(@body $($body)*),
+        )?);
+        $crate::scratch!(@scripts $v; $($rest)*);
+    };
+    (@script $v:ident; [$($hat:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::scratch!(@script $v; [$($hat)* $t] $($rest)*);
+    };
+    (@script $v:ident; [$($hat:tt)*]) => {
+        compile_error!(concat!("missing `{}` after `", stringify!($($hat)*), "`"));
+    };
+
+    // Hats ====================================================================
+    (@hat) => { None };
+    (@hat when flag clicked) => { Some($crate::blocks::when_flag_clicked()) };
+    (@hat when this sprite clicked) => { Some($crate::blocks::when_this_sprite_clicked()) };
+    (@hat when I start as a clone) => { Some($crate::blocks::when_i_start_as_a_clone()) };
+    (@hat when I receive $m:literal) => {
+        Some($crate::blocks::when_broadcast_received(
+            $crate::dsl::field($m, $crate::block::FieldKind::Broadcast),
+        ))
+    };
+    (@hat when $k:literal key pressed) => {
+        Some($crate::blocks::when_key_pressed(
+            $crate::dsl::field($k, $crate::block::FieldKind::NoRef),
+        ))
+    };
+    (@hat when backdrop switches to $b:literal) => {
+        Some($crate::blocks::when_backdrop_switches_to(
+            $crate::dsl::field($b, $crate::block::FieldKind::NoRef),
+        ))
+    };
+    (@hat when loudness > $e:tt) => {
+        Some($crate::blocks::when_greater_than(
+            $crate::dsl::field("LOUDNESS", $crate::block::FieldKind::NoRef),
+            $crate::scratch!(@expr $e),
+        ))
+    };
+    (@hat when timer > $e:tt) => {
+        Some($crate::blocks::when_greater_than(
+            $crate::dsl::field("TIMER", $crate::block::FieldKind::NoRef),
+            $crate::scratch!(@expr $e),
+        ))
+    };
+    (@hat $($t:tt)*) => { compile_error!(concat!("unknown hat block `", stringify!($($t)*), "`")) };
+
+    // Body ====================================================================
+    (@body) => { ::std::vec::Vec::<$crate::stack::StackBuilder>::new() };
+    (@body $($t:tt)*) => {{
+        let mut stmts: ::std::vec::Vec<$crate::stack::StackBuilder> = ::std::vec::Vec::new();
+        $crate::scratch!(@stmts stmts; $($t)* ;);
+        stmts
+    }};
+    (@stmts $v:ident;) => {};
+    (@stmts $v:ident; ; $($rest:tt)*) => { $crate::scratch!(@stmts $v; $($rest)*); };
+
+    // Control
+    (@stmts $v:ident; forever { $($b:tt)* } $($rest:tt)*) => {
+        $v.push($crate::blocks::forever($crate::dsl::substack($crate::scratch!(@body $($b)*))?));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; repeat until $c:tt { $($b:tt)* } $($rest:tt)*) => {
+        $v.push($crate::blocks::repeat_until($crate::scratch!(@expr $c), $crate::dsl::substack($crate::scratch!(@body $($b)*))?));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; repeat $n:tt { $($b:tt)* } $($rest:tt)*) => {
+        $v.push($crate::blocks::repeat($crate::scratch!(@expr $n), $crate::dsl::substack($crate::scratch!(@body $($b)*))?));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; if $c:tt { $($t:tt)* } else { $($f:tt)* } $($rest:tt)*) => {
+        $v.push($crate::blocks::if_else(
+            $crate::scratch!(@expr $c),
+            $crate::dsl::substack($crate::scratch!(@body $($t)*))?,
+            $crate::dsl::substack($crate::scratch!(@body $($f)*))?,
+        ));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; if $c:tt { $($t:tt)* } $($rest:tt)*) => {
+        $v.push($crate::blocks::if_($crate::scratch!(@expr $c), $crate::dsl::substack($crate::scratch!(@body $($t)*))?));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; wait until $c:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::wait_until($crate::scratch!(@expr $c)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; wait $e:tt $(seconds)?; $($rest:tt)*) => {
+        $v.push($crate::blocks::wait($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; stop all; $($rest:tt)*) => {
+        $v.push($crate::blocks::stop($crate::dsl::field("all", $crate::block::FieldKind::NoRef), false));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; stop this script; $($rest:tt)*) => {
+        $v.push($crate::blocks::stop($crate::dsl::field("this script", $crate::block::FieldKind::NoRef), false));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; stop other scripts in sprite; $($rest:tt)*) => {
+        $v.push($crate::blocks::stop($crate::dsl::field("other scripts in sprite", $crate::block::FieldKind::NoRef), true));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; create clone of myself; $($rest:tt)*) => {
+        $crate::scratch!(@stmts $v; create clone of "_myself_"; $($rest)*);
+    };
+    (@stmts $v:ident; create clone of $s:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::create_clone_of($crate::dsl::menu($crate::blocks::create_clone_of_menu($crate::dsl::field($s, $crate::block::FieldKind::NoRef)))));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; delete this clone; $($rest:tt)*) => {
+        $v.push($crate::blocks::delete_this_clone());
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+
+    // Event
+    (@stmts $v:ident; broadcast $m:tt and wait; $($rest:tt)*) => {
+        $v.push($crate::blocks::broadcast_and_wait($crate::scratch!(@expr $m)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; broadcast $m:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::broadcast($crate::scratch!(@expr $m)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+
+    // Motion
+    (@stmts $v:ident; move $e:tt $(steps)?; $($rest:tt)*) => {
+        $v.push($crate::blocks::move_steps($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; turn right $e:tt $(degrees)?; $($rest:tt)*) => {
+        $v.push($crate::blocks::turn_right($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; turn left $e:tt $(degrees)?; $($rest:tt)*) => {
+        $v.push($crate::blocks::turn_left($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; go to x: $x:tt y: $y:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::goto_xy($crate::scratch!(@expr $x), $crate::scratch!(@expr $y)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; go to front layer; $($rest:tt)*) => {
+        $v.push($crate::blocks::go_to_layer($crate::dsl::field("front", $crate::block::FieldKind::NoRef)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; go to back layer; $($rest:tt)*) => {
+        $v.push($crate::blocks::go_to_layer($crate::dsl::field("back", $crate::block::FieldKind::NoRef)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; go to $s:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::go_to($crate::dsl::menu($crate::blocks::go_to_menu($crate::dsl::field($s, $crate::block::FieldKind::NoRef)))));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; glide $s:tt secs to x: $x:tt y: $y:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::glide_to_xy($crate::scratch!(@expr $s), $crate::scratch!(@expr $x), $crate::scratch!(@expr $y)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; point in direction $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::point_in_direction($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; change x by $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::change_x_by($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; set x to $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::set_x($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; change y by $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::change_y_by($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; set y to $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::set_y($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; if on edge, bounce; $($rest:tt)*) => {
+        $v.push($crate::blocks::if_on_edge_bounce());
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; set rotation style $s:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::set_rotation_style($crate::dsl::field($s, $crate::block::FieldKind::NoRef)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+
+    // Looks
+    (@stmts $v:ident; say $m:tt for $s:tt $(seconds)?; $($rest:tt)*) => {
+        $v.push($crate::blocks::say_for_secs($crate::scratch!(@expr $m), $crate::scratch!(@expr $s)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; say $m:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::say($crate::scratch!(@expr $m)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; think $m:tt for $s:tt $(seconds)?; $($rest:tt)*) => {
+        $v.push($crate::blocks::think_for_secs($crate::scratch!(@expr $m), $crate::scratch!(@expr $s)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; think $m:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::think($crate::scratch!(@expr $m)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; switch costume to $c:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::switch_costume_to($crate::dsl::menu($crate::blocks::costume_menu($crate::dsl::field($c, $crate::block::FieldKind::NoRef)))));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; switch costume to $c:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::switch_costume_to($crate::scratch!(@expr $c).into()));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; next costume; $($rest:tt)*) => {
+        $v.push($crate::blocks::next_costume());
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; switch backdrop to $b:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::switch_backdrop_to($crate::dsl::menu($crate::blocks::backdrop_menu($crate::dsl::field($b, $crate::block::FieldKind::NoRef)))));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; switch backdrop to $b:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::switch_backdrop_to($crate::scratch!(@expr $b).into()));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; next backdrop; $($rest:tt)*) => {
+        $v.push($crate::blocks::next_backdrop());
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; change size by $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::change_size_by($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; set size to $e:tt $(%)?; $($rest:tt)*) => {
+        $v.push($crate::blocks::set_size_to($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; change $fx:literal effect by $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::change_looks_effect_by($crate::dsl::field(&$fx.to_uppercase(), $crate::block::FieldKind::NoRef), $crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; set $fx:literal effect to $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::set_looks_effect_to($crate::dsl::field(&$fx.to_uppercase(), $crate::block::FieldKind::NoRef), $crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; clear graphic effects; $($rest:tt)*) => {
+        $v.push($crate::blocks::clear_graphic_effects());
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; show; $($rest:tt)*) => {
+        $v.push($crate::blocks::show());
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; hide; $($rest:tt)*) => {
+        $v.push($crate::blocks::hide());
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; go forward $n:tt layers; $($rest:tt)*) => {
+        $v.push($crate::blocks::change_layer($crate::dsl::field("forward", $crate::block::FieldKind::NoRef), $crate::scratch!(@expr $n)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; go backward $n:tt layers; $($rest:tt)*) => {
+        $v.push($crate::blocks::change_layer($crate::dsl::field("backward", $crate::block::FieldKind::NoRef), $crate::scratch!(@expr $n)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+
+    // Sound
+    (@stmts $v:ident; play sound $s:literal until done; $($rest:tt)*) => {
+        $v.push($crate::blocks::play_sound_until_done($crate::dsl::menu($crate::blocks::sound_menu($crate::dsl::field($s, $crate::block::FieldKind::NoRef)))));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; play sound $s:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::play_sound($crate::dsl::menu($crate::blocks::sound_menu($crate::dsl::field($s, $crate::block::FieldKind::NoRef)))));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; stop all sounds; $($rest:tt)*) => {
+        $v.push($crate::blocks::stop_all_sound());
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; change volume by $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::change_volume_by($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; set volume to $e:tt $(%)?; $($rest:tt)*) => {
+        $v.push($crate::blocks::set_volume_to($crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; clear sound effects; $($rest:tt)*) => {
+        $v.push($crate::blocks::clear_sound_effects());
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+
+    // Sensing
+    (@stmts $v:ident; ask $q:tt and wait; $($rest:tt)*) => {
+        $v.push($crate::blocks::ask_and_wait($crate::scratch!(@expr $q)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; reset timer; $($rest:tt)*) => {
+        $v.push($crate::blocks::reset_timer());
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; set drag mode draggable; $($rest:tt)*) => {
+        $v.push($crate::blocks::set_drag_mode($crate::dsl::field("draggable", $crate::block::FieldKind::NoRef)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; set drag mode not draggable; $($rest:tt)*) => {
+        $v.push($crate::blocks::set_drag_mode($crate::dsl::field("not draggable", $crate::block::FieldKind::NoRef)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+
+    // Data
+    (@stmts $v:ident; set $kind:ident $n:literal to $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::set_var_to($crate::scratch!(@var $kind $n), $crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; change $kind:ident $n:literal by $e:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::change_var_by($crate::scratch!(@var $kind $n), $crate::scratch!(@expr $e)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; show $kind:ident $n:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::show_var($crate::scratch!(@var $kind $n)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; hide $kind:ident $n:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::hide_var($crate::scratch!(@var $kind $n)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; add $item:tt to $kind:ident $n:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::add_to_list($crate::scratch!(@list $kind $n), $crate::scratch!(@expr $item)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; delete all of $kind:ident $n:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::delete_all_in_list($crate::scratch!(@list $kind $n)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; delete $i:tt of $kind:ident $n:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::delete_in_list($crate::scratch!(@list $kind $n), $crate::scratch!(@expr $i)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; insert $item:tt at $i:tt of $kind:ident $n:literal; $($rest:tt)*) => {
+        $v.push($crate::blocks::insert_in_list($crate::scratch!(@list $kind $n), $crate::scratch!(@expr $i), $crate::scratch!(@expr $item)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+    (@stmts $v:ident; replace item $i:tt of $kind:ident $n:literal with $item:tt; $($rest:tt)*) => {
+        $v.push($crate::blocks::replace_in_list($crate::scratch!(@list $kind $n), $crate::scratch!(@expr $i), $crate::scratch!(@expr $item)));
+        $crate::scratch!(@stmts $v; $($rest)*);
+    };
+
+    (@stmts $v:ident; $($t:tt)*) => { compile_error!(concat!("unknown block `", stringify!($($t)*), "`")); };
+
+    // Variables and lists =====================================================
+    (@var var $n:literal) => { $crate::dsl::field($n, $crate::block::FieldKind::Variable) };
+    (@var sprite_var $n:literal) => { $crate::dsl::field($n, $crate::block::FieldKind::SpriteVariable) };
+    (@var $kind:ident $n:literal) => {
+        compile_error!(concat!("expected `var` or `sprite_var`, found `", stringify!($kind), "`"))
+    };
+    (@list list $n:literal) => { $crate::dsl::field($n, $crate::block::FieldKind::List) };
+    (@list sprite_list $n:literal) => { $crate::dsl::field($n, $crate::block::FieldKind::SpriteList) };
+    (@list $kind:ident $n:literal) => {
+        compile_error!(concat!("expected `list` or `sprite_list`, found `", stringify!($kind), "`"))
+    };
+
+    // Reporters ===============================================================
+    (@expr $l:literal) => { $l };
+    (@expr $i:ident) => { $i };
+    (@expr { $($rust:tt)* }) => { { $($rust)* } };
+    (@expr ($l:literal)) => { $l };
+    (@expr (($($t:tt)*))) => { $crate::scratch!(@expr ($($t)*)) };
+
+    (@expr (var $n:literal)) => { $crate::blocks::var($n) };
+    (@expr (sprite_var $n:literal)) => { $crate::blocks::sprite_var($n) };
+    (@expr (list $n:literal)) => { $crate::blocks::list($n) };
+    (@expr (sprite_list $n:literal)) => { $crate::blocks::sprite_list($n) };
+    (@expr (item $i:tt of $kind:ident $n:literal)) => {
+        $crate::blocks::item_in_list($crate::scratch!(@list $kind $n), $crate::scratch!(@expr $i))
+    };
+    (@expr (item # of $item:tt in $kind:ident $n:literal)) => {
+        $crate::blocks::count_of_item_in_list($crate::scratch!(@list $kind $n), $crate::scratch!(@expr $item))
+    };
+    (@expr (length of $kind:ident $n:literal)) => {
+        $crate::blocks::length_of_list($crate::scratch!(@list $kind $n))
+    };
+    (@expr ($kind:ident $n:literal contains $item:tt)) => {
+        $crate::blocks::list_contains($crate::scratch!(@list $kind $n), $crate::scratch!(@expr $item))
+    };
+
+    (@expr ($a:tt + $b:tt)) => { $crate::blocks::add($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr ($a:tt - $b:tt)) => { $crate::blocks::sub($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr ($a:tt * $b:tt)) => { $crate::blocks::mul($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr ($a:tt / $b:tt)) => { $crate::blocks::div($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr ($a:tt mod $b:tt)) => { $crate::blocks::modulo($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr ($a:tt < $b:tt)) => { $crate::blocks::less_than($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr ($a:tt > $b:tt)) => { $crate::blocks::greater_than($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr ($a:tt = $b:tt)) => { $crate::blocks::equals($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr ($a:tt == $b:tt)) => { $crate::blocks::equals($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr ($a:tt and $b:tt)) => { $crate::blocks::and($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr ($a:tt or $b:tt)) => { $crate::blocks::or($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr (not $a:tt)) => { $crate::blocks::not($crate::scratch!(@expr $a)) };
+    (@expr (join $a:tt $b:tt)) => { $crate::blocks::join($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr (letter $i:tt of $s:tt)) => { $crate::blocks::letter_of($crate::scratch!(@expr $i), $crate::scratch!(@expr $s)) };
+    (@expr (length of $s:tt)) => { $crate::blocks::length_of($crate::scratch!(@expr $s)) };
+    (@expr ($a:tt contains $b:tt)) => { $crate::blocks::contains($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr (pick random $a:tt to $b:tt)) => { $crate::blocks::random($crate::scratch!(@expr $a), $crate::scratch!(@expr $b)) };
+    (@expr (round $a:tt)) => { $crate::blocks::round($crate::scratch!(@expr $a)) };
+    (@expr ($op:ident of $a:tt)) => {
+        $crate::blocks::math_op($crate::dsl::field(stringify!($op), $crate::block::FieldKind::NoRef), $crate::scratch!(@expr $a))
+    };
+
+    (@expr (x position)) => { $crate::blocks::x_position() };
+    (@expr (y position)) => { $crate::blocks::y_position() };
+    (@expr (direction)) => { $crate::blocks::direction() };
+    (@expr (size)) => { $crate::blocks::size() };
+    (@expr (costume number)) => { $crate::blocks::costume($crate::dsl::field("number", $crate::block::FieldKind::NoRef)) };
+    (@expr (costume name)) => { $crate::blocks::costume($crate::dsl::field("name", $crate::block::FieldKind::NoRef)) };
+    (@expr (backdrop number)) => { $crate::blocks::backdrop($crate::dsl::field("number", $crate::block::FieldKind::NoRef)) };
+    (@expr (backdrop name)) => { $crate::blocks::backdrop($crate::dsl::field("name", $crate::block::FieldKind::NoRef)) };
+    (@expr (volume)) => { $crate::blocks::volume() };
+    (@expr (answer)) => { $crate::blocks::answer() };
+    (@expr (timer)) => { $crate::blocks::timer() };
+    (@expr (loudness)) => { $crate::blocks::loudness() };
+    (@expr (username)) => { $crate::blocks::username() };
+    (@expr (days since 2000)) => { $crate::blocks::days_since_2000() };
+    (@expr (mouse x)) => { $crate::blocks::mouse_x() };
+    (@expr (mouse y)) => { $crate::blocks::mouse_y() };
+    (@expr (mouse down)) => { $crate::blocks::mouse_down() };
+    (@expr (key $k:literal pressed)) => {
+        $crate::blocks::key_pressed($crate::dsl::menu($crate::blocks::key_menu($crate::dsl::field($k, $crate::block::FieldKind::NoRef))))
+    };
+    (@expr (touching color $c:tt)) => { $crate::blocks::touching_color($crate::scratch!(@expr $c)) };
+    (@expr (touching $s:literal)) => {
+        $crate::blocks::touching($crate::dsl::menu($crate::blocks::touching_menu($crate::dsl::field($s, $crate::block::FieldKind::NoRef))))
+    };
+    (@expr (distance to $s:literal)) => {
+        $crate::blocks::distance_to($crate::dsl::menu($crate::blocks::distance_to_menu($crate::dsl::field($s, $crate::block::FieldKind::NoRef))))
+    };
+    (@expr (- $n:tt)) => { $crate::blocks::sub(0, $crate::scratch!(@expr $n)) };
+
+    (@expr $($t:tt)*) => { compile_error!(concat!("unknown reporter `", stringify!($($t)*), "`")) };
+
+    // Entry ===================================================================
+    ($($t:tt)*) => {
+        (|| -> ::std::result::Result<::std::vec::Vec<$crate::stack::StackBuilder>, $crate::stack::StackError> {
+            let mut scripts: ::std::vec::Vec<$crate::stack::StackBuilder> = ::std::vec::Vec::new();
+            $crate::scratch!(@scripts scripts; $($t)*);
+            ::std::result::Result::Ok(scripts)
+        })()
+    };
+}
+
+#[cfg(test)]
+// scratch! pushes each block into a new Vec
+#[allow(clippy::vec_init_then_push)]
+mod tests {
+    use super::*;
+    use crate::blocks::*;
+
+    fn nr(value: &str) -> Bfb {
+        field(value, FieldKind::NoRef)
+    }
+
+    #[test]
+    fn script_is_the_same_as_helpers() {
+        let x = 2;
+        let scripts = scratch! {
+            when flag clicked {
+                say (join "hi " (x * 2));
+                forever {
+                    move 10;
+                    if (key "space" pressed) {
+                        change var "score" by 1;
+                    }
+                }
+            }
+            when I receive "game over" {
+                stop all;
+            }
+        }
+        .unwrap();
+        let expected = vec![
+            when_flag_clicked()
+                .next(say(join("hi ", mul(x, 2))))
+                .next(forever(Some(Bib::stack(move_steps(10).next(if_(
+                    key_pressed(menu(key_menu(nr("space")))),
+                    Some(Bib::stack(change_var_by(
+                        field("score", FieldKind::Variable),
+                        1,
+                    ))),
+                )))))),
+            when_broadcast_received(field("game over", FieldKind::Broadcast))
+                .next(stop(nr("all"), false)),
+        ];
+        assert_eq!(scripts, expected);
+    }
+
+    #[test]
+    fn reporters_nest_without_precedence() {
+        let scripts = scratch! {
+            {
+                set sprite_var "n" to ((1 + 2) * (-5));
+                say (round { 1.5 + 1. });
+                think (sqrt of (sprite_list "l" contains "a"));
+            }
+        }
+        .unwrap();
+        let expected = vec![
+            set_var_to(field("n", FieldKind::SpriteVariable), mul(add(1, 2), -5))
+                .next(say(round(1.5 + 1.)))
+                .next(think(math_op(
+                    nr("sqrt"),
+                    list_contains(field("l", FieldKind::SpriteList), "a"),
+                ))),
+        ];
+        assert_eq!(scripts, expected);
+    }
+
+    #[test]
+    fn var_and_list_are_the_sprites_or_else_the_stages() {
+        let scripts = scratch! {
+            {
+                set var "score" to (length of list "names");
+                add (var "score") to list "names";
+                say (list "names");
+            }
+        }
+        .unwrap();
+        let expected = vec![set_var_to(
+            field("score", FieldKind::Variable),
+            length_of_list(field("names", FieldKind::List)),
+        )
+        .next(add_to_list(field("names", FieldKind::List), var("score")))
+        .next(say(list("names")))];
+        assert_eq!(scripts, expected);
+    }
+
+    #[test]
+    fn c_blocks_with_empty_body() {
+        let scripts = scratch! {
+            when this sprite clicked {
+                repeat 3 {}
+                if (mouse down) {} else {
+                    hide;
+                }
+            }
+        }
+        .unwrap();
+        let expected = vec![when_this_sprite_clicked()
+            .next(repeat(3, None))
+            .next(if_else(mouse_down(), None, Some(Bib::stack(hide()))))];
+        assert_eq!(scripts, expected);
+    }
+
+    #[test]
+    fn block_after_cap_is_error() {
+        let result = scratch! {
+            when flag clicked {
+                forever {}
+                say "unreachable";
+            }
+        };
+        assert!(matches!(result, Err(StackError::NoNext { .. })));
+    }
+}
@@ -2,12 +2,19 @@ use std::fs::File as FsFile;
 use std::path::{Path, PathBuf};
 
 use crate::project::ProjectBuilder;
+use crate::resource::Resource;
+use crate::target::{SpriteBuilder, StageReferences};
+use crate::uid::{UidGenerator, UidMode};
+use crate::validate::{Diagnostic, Severity};
 use std::io::{Error as IoError, Seek, Write};
 
 #[derive(Debug)]
 pub enum ExportError {
     Io(IoError),
     Zip(zip::result::ZipError),
+    /// Errors found by [`ProjectBuilder::validate`].
+    /// Plain export only refuses the ones of [`Diagnostic::is_refused_by_export`].
+    Invalid(Vec<Diagnostic>),
 }
 
 impl std::error::Error for ExportError {}
@@ -17,6 +24,13 @@ impl std::fmt::Display for ExportError {
         match self {
             ExportError::Io(io) => write!(f, "{io}"),
             ExportError::Zip(zip) => write!(f, "{zip}"),
+            ExportError::Invalid(diagnostics) => {
+                write!(f, "project is invalid:")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n  {diagnostic}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -32,36 +46,237 @@ impl From<zip::result::ZipError> for ExportError {
     }
 }
 
+/// How files are written into the archive
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZipOptions {
+    /// Store files that are already compressed (PNG, JPEG, GIF and MP3) as is instead of deflating them again.
+    /// Makes exporting faster for barely any bigger archive.
+    pub store_compressed: bool,
+}
+
+impl ZipOptions {
+    pub fn set_store_compressed(&mut self, store_compressed: bool) -> &mut Self {
+        self.store_compressed = store_compressed;
+        self
+    }
+
+    fn compression_for(&self, extension: &str) -> zip::CompressionMethod {
+        let is_compressed = matches!(
+            extension.to_ascii_lowercase().as_str(),
+            "png" | "jpg" | "jpeg" | "gif" | "mp3"
+        );
+        match self.store_compressed && is_compressed {
+            true => zip::CompressionMethod::Stored,
+            false => zip::CompressionMethod::Deflated,
+        }
+    }
+}
+
 /// Return amount written
 pub fn write_zip<W: Write + Seek>(
     writer: W,
     project: ProjectBuilder,
 ) -> Result<(), zip::result::ZipError> {
+    write_zip_with_options(writer, project, ZipOptions::default())
+}
+
+/// Same as [`write_zip`] with [`ZipOptions`]
+pub fn write_zip_with_options<W: Write + Seek>(
+    writer: W,
+    project: ProjectBuilder,
+    options: ZipOptions,
+) -> Result<(), zip::result::ZipError> {
+    let (json, res_buf) = to_json(project);
+    write_archive(writer, res_buf, "project", json, options)
+}
+
+/// Build the project into the content of `project.json` and the resources it uses,
+/// for uploading assets separately ex. to an asset server.
+/// Assets in the project refer to the resources by [`Resource::generate_file_name`].
+///
+/// Keys are written sorted, so the same project with the same uids is always the same text.
+pub fn to_json(project: ProjectBuilder) -> (String, Vec<Resource>) {
     let mut res_buf = vec![];
     let project = project.build(&mut res_buf);
+    // Maps in the project are hash maps, going through `Value` sorts them
+    let json = serde_json::to_value(&project).unwrap().to_string();
+    (json, res_buf)
+}
+
+/// Mistakes that the build can't fix or report itself, ex. variable that can't be resolved
+/// would build with an `__unknown__` id. See [`Diagnostic::is_refused_by_export`].
+fn check_export(project: &ProjectBuilder) -> Result<(), ExportError> {
+    let refused: Vec<Diagnostic> = project
+        .validate()
+        .into_iter()
+        .filter(Diagnostic::is_refused_by_export)
+        .collect();
+    match refused.is_empty() {
+        true => Ok(()),
+        false => Err(ExportError::Invalid(refused)),
+    }
+}
+
+/// Write `project.json` and the assets unpacked into the `path` directory.
+/// The directory is created if it doesn't exist, files already in it are overwritten.
+/// Refuses the same mistakes as [`export`].
+pub fn export_dir<P: AsRef<Path>>(project: ProjectBuilder, path: P) -> Result<(), ExportError> {
+    check_export(&project)?;
+    let path = path.as_ref();
+    std::fs::create_dir_all(path)?;
+    let (json, res_buf) = to_json(project);
+    for mut res in res_buf {
+        let mut file = FsFile::create(path.join(res.generate_file_name()))?;
+        std::io::copy(&mut res.reader()?, &mut file)?;
+    }
+    std::fs::write(path.join("project.json"), json)?;
+    Ok(())
+}
+
+/// Write the sprite alone as `.sprite3` which can be uploaded into any project.
+/// See [`SpriteBuilder::build_standalone`] for what happens to its references to the stage.
+pub fn write_sprite3<W: Write + Seek>(
+    writer: W,
+    sprite: SpriteBuilder,
+) -> Result<(), zip::result::ZipError> {
+    write_sprite3_with_options(
+        writer,
+        sprite,
+        StageReferences::default(),
+        UidMode::default(),
+        ZipOptions::default(),
+    )
+}
+
+/// Same as [`write_sprite3`] with what to do with the stage's variables and lists,
+/// the [`UidMode`] of the sprite's uids and [`ZipOptions`]
+pub fn write_sprite3_with_options<W: Write + Seek>(
+    writer: W,
+    sprite: SpriteBuilder,
+    stage_references: StageReferences,
+    uid_mode: UidMode,
+    options: ZipOptions,
+) -> Result<(), zip::result::ZipError> {
+    let mut res_buf = vec![];
+    let sprite = sprite.build_standalone(
+        stage_references,
+        &mut res_buf,
+        &mut UidGenerator::new(uid_mode),
+    );
+    // Keys are sorted like in `to_json`
+    let json = serde_json::to_value(&sprite).unwrap().to_string();
+    write_archive(writer, res_buf, "sprite", json, options)
+}
+
+/// Resources and then `<json_name>.json`.
+/// Each file is in `res_buf` only once,
+/// [`crate::asset::AssetBuilder::build`] doesn't push the same file twice.
+/// Streamed resources are copied from their file as they're written.
+/// Every file has the same modified time so the same project always makes the same archive.
+fn write_archive<W: Write + Seek>(
+    writer: W,
+    res_buf: Vec<Resource>,
+    json_name: &str,
+    json: String,
+    options: ZipOptions,
+) -> Result<(), zip::result::ZipError> {
     let mut zip = zip::ZipWriter::new(writer);
+    let file_options =
+        zip::write::FileOptions::default().last_modified_time(zip::DateTime::default());
     for mut res in res_buf {
         zip.start_file(
             res.generate_file_name().to_str().unwrap(),
-            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated),
+            file_options.compression_method(options.compression_for(res.extension())),
         )?;
-        let _written = zip.write(res.content())?;
+        std::io::copy(&mut res.reader()?, &mut zip)?;
     }
     zip.start_file(
-        PathBuf::from("project")
+        PathBuf::from(json_name)
             .with_extension("json")
             .to_str()
             .unwrap(),
-        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated),
+        file_options.compression_method(zip::CompressionMethod::Deflated),
     )?;
-    let _written = zip.write(&serde_json::to_string(&project).unwrap().into_bytes())?;
+    zip.write_all(json.as_bytes())?;
     Ok(())
 }
 
+/// Write the project into a `.sb3` file at `path`.
+/// Refuses variables and lists that can't be resolved and cloud variables in a sprite,
+/// see [`Diagnostic::is_refused_by_export`]. [`export_checked`] refuses every error.
+///
+/// These used to be written anyway and only broke once the project was opened in Scratch,
+/// so code that expected `export` to always write the file should handle [`ExportError::Invalid`].
+/// [`write_zip`] still writes whatever it's given.
 pub fn export<P: AsRef<Path>>(
     project: ProjectBuilder,
     path: P,
     create_new: bool,
+) -> Result<(), ExportError> {
+    export_with_options(project, path, create_new, ZipOptions::default())
+}
+
+/// Same as [`export`] with [`ZipOptions`]
+pub fn export_with_options<P: AsRef<Path>>(
+    project: ProjectBuilder,
+    path: P,
+    create_new: bool,
+    options: ZipOptions,
+) -> Result<(), ExportError> {
+    check_export(&project)?;
+    let zip_file = FsFile::options()
+        .write(true)
+        .create(true)
+        .create_new(create_new)
+        .truncate(true)
+        .open(path)?;
+    write_zip_with_options(zip_file, project, options)?;
+    Ok(())
+}
+
+/// Same as [`export`] but refuse to export when [`ProjectBuilder::validate`] finds any error.
+/// Warnings are ignored.
+pub fn export_checked<P: AsRef<Path>>(
+    project: ProjectBuilder,
+    path: P,
+    create_new: bool,
+) -> Result<(), ExportError> {
+    let errors: Vec<Diagnostic> = project
+        .validate()
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity() == Severity::Error)
+        .collect();
+    if !errors.is_empty() {
+        return Err(ExportError::Invalid(errors));
+    }
+    export(project, path, create_new)
+}
+
+/// Write the sprite into a `.sprite3` file at `path`, see [`write_sprite3`].
+/// Fails when the file exists and `create_new` is true, otherwise it's overwritten.
+pub fn export_sprite3<P: AsRef<Path>>(
+    sprite: SpriteBuilder,
+    path: P,
+    create_new: bool,
+) -> Result<(), ExportError> {
+    export_sprite3_with_options(
+        sprite,
+        path,
+        create_new,
+        StageReferences::default(),
+        UidMode::default(),
+        ZipOptions::default(),
+    )
+}
+
+/// Same as [`export_sprite3`] with the options of [`write_sprite3_with_options`]
+pub fn export_sprite3_with_options<P: AsRef<Path>>(
+    sprite: SpriteBuilder,
+    path: P,
+    create_new: bool,
+    stage_references: StageReferences,
+    uid_mode: UidMode,
+    options: ZipOptions,
 ) -> Result<(), ExportError> {
     let zip_file = FsFile::options()
         .write(true)
@@ -69,6 +284,328 @@ pub fn export<P: AsRef<Path>>(
         .create_new(create_new)
         .truncate(true)
         .open(path)?;
-    write_zip(zip_file, project)?;
+    write_sprite3_with_options(zip_file, sprite, stage_references, uid_mode, options)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sb_sbity::value::Value;
+    use std::io::Cursor;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{
+        asset::{AssetBuilder, CostumeBuilder, SoundBuilder},
+        block::{BlockFieldBuilder, FieldKind},
+        blocks::{add_to_list, change_var_by, global_var, say, sprite_var, var},
+        data::VariableBuilder,
+        import::read_sprite3,
+        target::StageBuilder,
+        validate::DiagnosticKind,
+    };
+
+    /// Path in the temp directory that no other test or test run uses
+    fn temp_path(name: &str) -> PathBuf {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        std::env::temp_dir().join(format!(
+            "sb-itchy-{}-{}-{name}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    fn cat() -> SpriteBuilder {
+        let score = BlockFieldBuilder::new_with_kind("score".to_owned(), FieldKind::GlobalVariable);
+        let names = BlockFieldBuilder::new_with_kind("names".to_owned(), FieldKind::GlobalList);
+        let mut script = change_var_by(score, sprite_var("speed"))
+            .next(add_to_list(names, "cat"))
+            .next(say(global_var("score")));
+        // Built stack always has a position
+        script.set_top_block_position(0., 0.);
+        let costume = Resource::new("svg".to_owned(), b"<svg width='2' height='2'/>".to_vec());
+        let mut sprite = SpriteBuilder::default();
+        sprite
+            .target
+            .set_name("Cat")
+            .add_variable("speed", VariableBuilder::new(Value::Number(2_i64.into())))
+            .add_costume(CostumeBuilder::new(AssetBuilder::new(
+                "cat",
+                costume.unwrap(),
+            )))
+            .add_block_stack(script);
+        sprite
+    }
+
+    fn sprite3(stage_references: StageReferences) -> Vec<u8> {
+        let mut zip = std::io::Cursor::new(vec![]);
+        write_sprite3_with_options(
+            &mut zip,
+            cat(),
+            stage_references,
+            UidMode::Sequential,
+            ZipOptions::default(),
+        )
+        .unwrap();
+        zip.into_inner()
+    }
+
+    fn sprite_json(sprite3: &[u8]) -> serde_json::Value {
+        let mut zip = zip::ZipArchive::new(std::io::Cursor::new(sprite3)).unwrap();
+        serde_json::from_reader(zip.by_name("sprite.json").unwrap()).unwrap()
+    }
+
+    /// Names of the variables or lists in `sprite.json`
+    fn names(sprite: &serde_json::Value, key: &str) -> Vec<String> {
+        let mut names: Vec<String> = sprite[key]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|value| value["name"].as_str().unwrap().to_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn sprite3_refers_to_stage_by_name() {
+        let sprite3 = sprite3(StageReferences::ByName);
+        let json = sprite_json(&sprite3);
+        assert_eq!(names(&json, "variables"), ["speed"]);
+        assert_eq!(names(&json, "lists"), Vec::<String>::new());
+
+        let sprite = read_sprite3(std::io::Cursor::new(sprite3)).unwrap();
+        assert_eq!(
+            sprite.target.variables.keys().collect::<Vec<_>>(),
+            ["speed"]
+        );
+        assert!(sprite.target.lists.is_empty());
+        assert_eq!(sprite.target.block_stackes, cat().target.block_stackes);
+        assert_eq!(sprite.target.costumes, cat().target.costumes);
+    }
+
+    #[test]
+    fn sprite3_with_local_stage_references() {
+        let sprite3 = sprite3(StageReferences::Local);
+        let json = sprite_json(&sprite3);
+        assert_eq!(names(&json, "variables"), ["score", "speed"]);
+        assert_eq!(names(&json, "lists"), ["names"]);
+
+        let sprite = read_sprite3(std::io::Cursor::new(sprite3)).unwrap();
+        let mut variables: Vec<_> = sprite.target.variables.keys().collect();
+        variables.sort();
+        assert_eq!(variables, ["score", "speed"]);
+        assert_eq!(sprite.target.costumes, cat().target.costumes);
+    }
+
+    fn archive(project: ProjectBuilder, options: ZipOptions) -> zip::ZipArchive<Cursor<Vec<u8>>> {
+        let mut zip = Cursor::new(vec![]);
+        write_zip_with_options(&mut zip, project, options).unwrap();
+        zip::ZipArchive::new(zip).unwrap()
+    }
+
+    fn costume(resource: Resource) -> CostumeBuilder {
+        CostumeBuilder::new(AssetBuilder::new("costume", resource))
+    }
+
+    fn svg() -> Resource {
+        Resource::new("svg".to_owned(), b"<svg width='2' height='2'/>".to_vec()).unwrap()
+    }
+
+    /// File names in the archive, sorted
+    fn file_names(zip: &zip::ZipArchive<Cursor<Vec<u8>>>) -> Vec<&str> {
+        let mut names: Vec<&str> = zip.file_names().collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn shared_costume_is_written_once() {
+        let mut project = ProjectBuilder::default();
+        project.stage_builder.target.add_costume(costume(svg()));
+        for name in ["Cat", "Dog"] {
+            let mut sprite = SpriteBuilder::default();
+            sprite.target.set_name(name).add_costume(costume(svg()));
+            project.add_sprite(sprite);
+        }
+        let md5ext = svg().md5ext();
+        let zip = archive(project, ZipOptions::default());
+        assert_eq!(file_names(&zip), [md5ext.as_str(), "project.json"]);
+    }
+
+    #[test]
+    fn store_compressed() {
+        use zip::CompressionMethod::{Deflated, Stored};
+
+        let project = || {
+            let png = Resource::new("png".to_owned(), b"png".to_vec()).unwrap();
+            let mp3 = Resource::new("mp3".to_owned(), b"mp3".to_vec()).unwrap();
+            let mut project = ProjectBuilder::default();
+            project
+                .stage_builder
+                .target
+                .add_costume(costume(svg()))
+                .add_costume(costume(png))
+                .add_sound(SoundBuilder::new(AssetBuilder::new("pop", mp3), 48000, 1));
+            project
+        };
+        let compression = |zip: &mut zip::ZipArchive<Cursor<Vec<u8>>>| {
+            let mut compression: Vec<(String, zip::CompressionMethod)> = (0..zip.len())
+                .map(|i| {
+                    let file = zip.by_index(i).unwrap();
+                    let extension = file.name().rsplit('.').next().unwrap().to_owned();
+                    (extension, file.compression())
+                })
+                .collect();
+            compression.sort_by(|a, b| a.0.cmp(&b.0));
+            compression
+        };
+        let stored = compression(&mut archive(
+            project(),
+            *ZipOptions::default().set_store_compressed(true),
+        ));
+        assert_eq!(
+            stored,
+            [
+                ("json".to_owned(), Deflated),
+                ("mp3".to_owned(), Stored),
+                ("png".to_owned(), Stored),
+                ("svg".to_owned(), Deflated),
+            ]
+        );
+        let deflated = compression(&mut archive(project(), ZipOptions::default()));
+        assert!(deflated.iter().all(|(_, method)| *method == Deflated));
+    }
+
+    #[test]
+    fn streamed_resource_is_same_as_preloaded() {
+        let path = temp_path("costume.svg");
+        std::fs::write(&path, svg().content()).unwrap();
+        let mut streamed = Resource::open(&path).unwrap();
+        let mut preloaded = Resource::load(&path).unwrap();
+        assert!(streamed.is_streamed());
+        assert!(!preloaded.is_streamed());
+        assert_eq!(streamed.md5ext(), preloaded.md5ext());
+        assert_eq!(streamed.md5ext(), svg().md5ext());
+
+        let content = |resource: Resource| {
+            let md5ext = resource.clone().md5ext();
+            let mut project = ProjectBuilder::default();
+            project.stage_builder.target.add_costume(costume(resource));
+            let mut zip = archive(project, ZipOptions::default());
+            let mut content = vec![];
+            std::io::Read::read_to_end(&mut zip.by_name(&md5ext).unwrap(), &mut content).unwrap();
+            content
+        };
+        assert_eq!(content(streamed), content(preloaded));
+        assert_eq!(content(svg()), svg().content());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// Names of the files in the directory, sorted
+    fn dir_entries(path: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn export_dir_writes_json_and_assets() {
+        let png = Resource::new("png".to_owned(), b"png".to_vec()).unwrap();
+        let mut project = ProjectBuilder::default();
+        project
+            .stage_builder
+            .target
+            .add_costume(costume(svg()))
+            .add_costume(costume(png.clone()));
+        let path = temp_path("dir");
+        export_dir(project, &path).unwrap();
+
+        let mut expected = vec!["project.json".to_owned()];
+        for mut resource in [png, svg()] {
+            let md5ext = resource.md5ext();
+            assert_eq!(
+                std::fs::read(path.join(&md5ext)).unwrap(),
+                resource.content()
+            );
+            expected.push(md5ext);
+        }
+        expected.sort();
+        assert_eq!(dir_entries(&path), expected);
+        let json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(path.join("project.json")).unwrap()).unwrap();
+        let costumes = json["targets"][0]["costumes"].as_array().unwrap();
+        for costume in costumes {
+            let md5ext = costume["md5ext"].as_str().unwrap();
+            assert!(path.join(md5ext).exists());
+        }
+        assert_eq!(costumes.len(), 2);
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn export_dir_refused_leaves_dir_untouched() {
+        let project = || {
+            let mut sprite = SpriteBuilder::default();
+            sprite.target.add_block_stack(var("missing"));
+            let mut project = ProjectBuilder::default();
+            project.add_sprite(sprite);
+            project
+        };
+        let path = temp_path("refused-dir");
+        let Err(ExportError::Invalid(_)) = export_dir(project(), &path) else {
+            panic!("expected the export to be refused");
+        };
+        assert!(!path.exists());
+
+        std::fs::create_dir(&path).unwrap();
+        std::fs::write(path.join("project.json"), "{}").unwrap();
+        let Err(ExportError::Invalid(_)) = export_dir(project(), &path) else {
+            panic!("expected the export to be refused");
+        };
+        assert_eq!(dir_entries(&path), ["project.json"]);
+        assert_eq!(std::fs::read(path.join("project.json")).unwrap(), b"{}");
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn export_refuses_unresolved_scope_variable() {
+        let mut sprite = SpriteBuilder::default();
+        sprite.target.add_block_stack(var("missing"));
+        let mut project = ProjectBuilder::default();
+        project
+            .set_stage(StageBuilder::default())
+            .add_sprite(sprite);
+        let path = temp_path("unresolved.sb3");
+        let Err(ExportError::Invalid(diagnostics)) = export(project, &path, false) else {
+            panic!("expected the export to be refused");
+        };
+        assert!(diagnostics.iter().all(Diagnostic::is_unresolved_reference));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn export_refuses_cloud_variable_in_sprite() {
+        let mut sprite = SpriteBuilder::default();
+        sprite.target.add_variable(
+            "score",
+            VariableBuilder::new_cloud_variable(Value::Number(0_i64.into())),
+        );
+        let mut project = ProjectBuilder::default();
+        project
+            .set_stage(StageBuilder::default())
+            .add_sprite(sprite);
+        let path = temp_path("sprite-cloud.sb3");
+        let Err(ExportError::Invalid(diagnostics)) = export(project, &path, false) else {
+            panic!("expected the export to be refused");
+        };
+        assert_eq!(
+            diagnostics[0].kind,
+            DiagnosticKind::CloudVariableNotOnStage("score".to_owned())
+        );
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,388 @@
+//! Reading the size of costume images from their headers
+//!
+//! Only what Scratch can load as a costume is supported: SVG, PNG, JPEG and GIF.
+//! Nothing but the size is decoded, and the file is only read up to where the size is.
+
+use std::io::{Error as IoError, Read};
+
+use crate::resource::Resource;
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(IoError),
+    /// Extension of a file that isn't an image Scratch supports
+    UnsupportedFormat(String),
+    /// Header of this format can't be read
+    Malformed(&'static str),
+}
+
+impl From<IoError> for ImageError {
+    fn from(value: IoError) -> Self {
+        ImageError::Io(value)
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::Io(io) => write!(f, "{io}"),
+            ImageError::UnsupportedFormat(extension) => {
+                write!(f, "`{extension}` isn't an image Scratch supports")
+            }
+            ImageError::Malformed(format) => write!(f, "can't read the size of the {format} image"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+    Jpeg,
+    Gif,
+}
+
+impl ImageFormat {
+    pub fn from_extension(extension: &str) -> Option<ImageFormat> {
+        match extension.to_ascii_lowercase().as_str() {
+            "svg" => Some(ImageFormat::Svg),
+            "png" => Some(ImageFormat::Png),
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "gif" => Some(ImageFormat::Gif),
+            _ => None,
+        }
+    }
+
+    /// Everything but SVG
+    pub fn is_bitmap(&self) -> bool {
+        *self != ImageFormat::Svg
+    }
+}
+
+/// Format from the resource's extension and the size from its content.
+/// SVG's size is its `viewBox` or else its `width` and `height`, in its own units.
+pub fn read_size(resource: &Resource) -> Result<(ImageFormat, ImageSize), ImageError> {
+    let format = ImageFormat::from_extension(resource.extension())
+        .ok_or_else(|| ImageError::UnsupportedFormat(resource.extension().to_owned()))?;
+    let reader = resource.reader()?;
+    let size = match format {
+        ImageFormat::Svg => svg_size(&read_svg_tag(reader)?),
+        ImageFormat::Png => png_size(&read_prefix(reader, 24)?),
+        ImageFormat::Jpeg => jpeg_size(reader),
+        ImageFormat::Gif => gif_size(&read_prefix(reader, 10)?),
+    };
+    Ok((format, size?))
+}
+
+/// First `length` bytes, or less when the file is shorter
+fn read_prefix(reader: impl Read, length: u64) -> Result<Vec<u8>, IoError> {
+    let mut prefix = vec![];
+    reader.take(length).read_to_end(&mut prefix)?;
+    Ok(prefix)
+}
+
+/// Content up to the end of the `<svg>` tag, the rest of the file isn't needed
+fn read_svg_tag(mut reader: impl Read) -> Result<Vec<u8>, IoError> {
+    let mut content = vec![];
+    let mut buf = [0; 4096];
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            return Ok(content);
+        }
+        content.extend_from_slice(&buf[..read]);
+        let tag_end = find(&content, b"<svg")
+            .and_then(|start| find(&content[start..], b">").map(|end| start + end));
+        if let Some(tag_end) = tag_end {
+            // Cut so a character split by the read isn't left at the end
+            content.truncate(tag_end + 1);
+            return Ok(content);
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn png_size(content: &[u8]) -> Result<ImageSize, ImageError> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    // IHDR is always the first chunk, its width and height are right after its type
+    match content.get(..24) {
+        Some(header) if header.starts_with(SIGNATURE) && &header[12..16] == b"IHDR" => {
+            Ok(ImageSize {
+                width: u32::from_be_bytes(header[16..20].try_into().unwrap()) as f64,
+                height: u32::from_be_bytes(header[20..24].try_into().unwrap()) as f64,
+            })
+        }
+        _ => Err(ImageError::Malformed("PNG")),
+    }
+}
+
+fn gif_size(content: &[u8]) -> Result<ImageSize, ImageError> {
+    match content.get(..10) {
+        Some(header) if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") => {
+            Ok(ImageSize {
+                width: u16::from_le_bytes([header[6], header[7]]) as f64,
+                height: u16::from_le_bytes([header[8], header[9]]) as f64,
+            })
+        }
+        _ => Err(ImageError::Malformed("GIF")),
+    }
+}
+
+/// Size is in the start of frame segment, the other segments are skipped over
+fn jpeg_size(mut reader: impl Read) -> Result<ImageSize, ImageError> {
+    let malformed = || ImageError::Malformed("JPEG");
+    if read_jpeg(&mut reader)? != [0xFF, 0xD8] {
+        return Err(malformed());
+    }
+    loop {
+        if read_jpeg::<1>(&mut reader)? != [0xFF] {
+            return Err(malformed());
+        }
+        // Any number of 0xFF can pad before the marker
+        let mut marker = 0xFF;
+        while marker == 0xFF {
+            [marker] = read_jpeg(&mut reader)?;
+        }
+        // Markers without a segment
+        if matches!(marker, 0x01 | 0xD0..=0xD7) {
+            continue;
+        }
+        if matches!(marker, 0xD9 | 0xDA) {
+            return Err(malformed());
+        }
+        let length = u16::from_be_bytes(read_jpeg(&mut reader)?) as u64;
+        // Every SOF except DHT, JPG and DAC, which share the range
+        if matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            // Precision, then height and width
+            let sof: [u8; 5] = read_jpeg(&mut reader)?;
+            return Ok(ImageSize {
+                width: u16::from_be_bytes([sof[3], sof[4]]) as f64,
+                height: u16::from_be_bytes([sof[1], sof[2]]) as f64,
+            });
+        }
+        // Rest of the segment after its length
+        let rest = length.checked_sub(2).ok_or_else(malformed)?;
+        if std::io::copy(&mut reader.by_ref().take(rest), &mut std::io::sink())? != rest {
+            return Err(malformed());
+        }
+    }
+}
+
+/// Next `N` bytes of the JPEG, the file ending before them is malformed
+fn read_jpeg<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], ImageError> {
+    let mut bytes = [0; N];
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => Ok(bytes),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            Err(ImageError::Malformed("JPEG"))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn svg_size(content: &[u8]) -> Result<ImageSize, ImageError> {
+    let malformed = || ImageError::Malformed("SVG");
+    let text = std::str::from_utf8(content).map_err(|_| malformed())?;
+    let start = text.find("<svg").ok_or_else(malformed)?;
+    let end = text[start..].find('>').ok_or_else(malformed)? + start;
+    let attributes = svg_attributes(&text[start + "<svg".len()..end]);
+    let attribute = |name: &str| {
+        attributes
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    };
+    let view_box = attribute("viewBox").and_then(|view_box| {
+        let numbers: Vec<f64> = view_box
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|n| !n.is_empty())
+            .map(|n| n.parse().ok())
+            .collect::<Option<_>>()?;
+        match numbers[..] {
+            [_, _, width, height] => Some(ImageSize { width, height }),
+            _ => None,
+        }
+    });
+    if let Some(size) = view_box {
+        return Ok(size);
+    }
+    // Percentages or other units don't tell the size
+    let length = |name| {
+        let value = attribute(name)?.trim();
+        value.strip_suffix("px").unwrap_or(value).parse().ok()
+    };
+    match (length("width"), length("height")) {
+        (Some(width), Some(height)) => Ok(ImageSize { width, height }),
+        _ => Err(malformed()),
+    }
+}
+
+/// `name="value"` pairs in the inside of a tag
+fn svg_attributes(tag: &str) -> Vec<(&str, &str)> {
+    let mut attributes = vec![];
+    let mut rest = tag;
+    while let Some(eq) = rest.find('=') {
+        let name = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some(close) = value[1..].find(quote) else {
+            break;
+        };
+        attributes.push((name, &value[1..close + 1]));
+        rest = &value[close + 2..];
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(width: f64, height: f64) -> ImageSize {
+        ImageSize { width, height }
+    }
+
+    fn svg(content: &str) -> Result<ImageSize, ImageError> {
+        svg_size(&read_svg_tag(content.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn png_header() {
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend(640_u32.to_be_bytes());
+        png.extend(360_u32.to_be_bytes());
+        assert_eq!(png_size(&png).unwrap(), size(640., 360.));
+        assert!(matches!(
+            png_size(&png[..20]),
+            Err(ImageError::Malformed("PNG"))
+        ));
+    }
+
+    #[test]
+    fn gif_header() {
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend(32_u16.to_le_bytes());
+        gif.extend(16_u16.to_le_bytes());
+        assert_eq!(gif_size(&gif).unwrap(), size(32., 16.));
+        gif[..6].copy_from_slice(b"GIF88a");
+        assert!(matches!(gif_size(&gif), Err(ImageError::Malformed("GIF"))));
+    }
+
+    /// Segment with `marker` and `content` after its length
+    fn segment(marker: u8, content: &[u8]) -> Vec<u8> {
+        let mut segment = vec![0xFF, marker];
+        segment.extend((content.len() as u16 + 2).to_be_bytes());
+        segment.extend(content);
+        segment
+    }
+
+    #[test]
+    fn jpeg_start_of_frame() {
+        // Precision, height 120 and width 200
+        let sof = [8, 0, 120, 0, 200, 3];
+        let jpeg = [
+            vec![0xFF, 0xD8],
+            segment(0xE0, b"JFIF\0"),
+            // DHT and DAC are in the range of SOF but aren't
+            segment(0xC4, &[0, 1, 2, 3, 4]),
+            segment(0xCC, &[0, 1]),
+            // Fill bytes before the marker and a marker without a segment
+            vec![0xFF, 0xFF, 0xFF, 0xD0],
+            vec![0xFF, 0xFF],
+            segment(0xC2, &sof),
+        ]
+        .concat();
+        assert_eq!(jpeg_size(jpeg.as_slice()).unwrap(), size(200., 120.));
+    }
+
+    #[test]
+    fn jpeg_without_start_of_frame() {
+        let ends_early = [vec![0xFF, 0xD8], segment(0xE0, b"JFIF\0")].concat();
+        assert!(matches!(
+            jpeg_size(ends_early.as_slice()),
+            Err(ImageError::Malformed("JPEG"))
+        ));
+        let scan_first = [vec![0xFF, 0xD8], segment(0xDA, &[0; 4])].concat();
+        assert!(matches!(
+            jpeg_size(scan_first.as_slice()),
+            Err(ImageError::Malformed("JPEG"))
+        ));
+        assert!(matches!(
+            jpeg_size(b"\x89PNG".as_slice()),
+            Err(ImageError::Malformed("JPEG"))
+        ));
+    }
+
+    #[test]
+    fn svg_view_box_over_width_and_height() {
+        assert_eq!(
+            svg(r#"<?xml version="1.0"?><svg width="10" height="10" viewBox="0 0 48.5 20">"#)
+                .unwrap(),
+            size(48.5, 20.)
+        );
+        assert_eq!(
+            svg(r#"<svg viewBox="-5,-5, 30,40"></svg>"#).unwrap(),
+            size(30., 40.)
+        );
+    }
+
+    #[test]
+    fn svg_width_and_height() {
+        assert_eq!(
+            svg(r#"<svg width="96px" height='48'>"#).unwrap(),
+            size(96., 48.)
+        );
+        // Invalid view box falls back to width and height
+        assert_eq!(
+            svg(r#"<svg viewBox="0 0 auto" width="3" height="4">"#).unwrap(),
+            size(3., 4.)
+        );
+        assert!(matches!(
+            svg(r#"<svg width="100%" height="100%">"#),
+            Err(ImageError::Malformed("SVG"))
+        ));
+        assert!(matches!(svg("<html>"), Err(ImageError::Malformed("SVG"))));
+    }
+
+    #[test]
+    fn svg_tag_after_long_prolog() {
+        let content = format!(
+            "<!-- {} --><svg width=\"5\" height=\"6\">{}</svg>",
+            "é".repeat(5000),
+            "ü".repeat(5000)
+        );
+        let resource = Resource::new("svg".to_owned(), content.into_bytes()).unwrap();
+        let (format, found) = read_size(&resource).unwrap();
+        assert_eq!(format, ImageFormat::Svg);
+        assert_eq!(found, size(5., 6.));
+    }
+
+    #[test]
+    fn svg_attributes_with_either_quote() {
+        assert_eq!(
+            svg_attributes(r#" xmlns="http://www.w3.org/2000/svg" width = '2' data-x="a=b""#),
+            [
+                ("xmlns", "http://www.w3.org/2000/svg"),
+                ("width", "2"),
+                ("data-x", "a=b")
+            ]
+        );
+        // Unquoted value stops the attributes
+        assert_eq!(svg_attributes(r#" width=2 height="3""#), []);
+    }
+}
@@ -1 +1,494 @@
-// TODO: this
+use std::collections::HashMap;
+use std::fs::File as FsFile;
+use std::io::{Error as IoError, Read, Seek};
+use std::path::Path;
+
+use sb_sbity::{
+    asset::{Asset, Costume, Sound},
+    block::{Block, BlockField, BlockInputValue, ListOrVariable, UidOrValue},
+    project::Project,
+    target::{Sprite, SpriteOrStage, Stage, Target},
+};
+
+use crate::{
+    asset::{AssetBuilder, BitmapResolution, CostumeBuilder, SoundBuilder},
+    build_context::{GlobalVarListContext, TargetContext},
+    comment::CommentBuilder,
+    data::{ListBuilder, VariableBuilder},
+    monitor::MonitorBuilder,
+    project::ProjectBuilder,
+    resource::{Resource, ResourceError},
+    stack::StackBuilder,
+    target::{SpriteBuilder, StageBuilder, TargetBuilder},
+    uid::Uid,
+    value::{number_to_f64, number_to_i64, value_to_string},
+};
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(IoError),
+    Zip(zip::result::ZipError),
+    Json(serde_json::Error),
+    Resource(ResourceError),
+    /// Project doesn't have any Stage in its targets
+    MissingStage,
+    /// Asset's md5ext that is referenced in `project.json` but isn't in the archive
+    MissingAsset(String),
+    /// Costume's bitmap resolution that isn't 1 or 2
+    InvalidBitmapResolution(u64),
+}
+
+impl std::error::Error for ImportError {}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Io(io) => write!(f, "{io}"),
+            ImportError::Zip(zip) => write!(f, "{zip}"),
+            ImportError::Json(json) => write!(f, "{json}"),
+            ImportError::Resource(res) => write!(f, "{res}"),
+            ImportError::MissingStage => write!(f, "project doesn't have a stage"),
+            ImportError::MissingAsset(md5ext) => write!(f, "missing asset `{md5ext}`"),
+            ImportError::InvalidBitmapResolution(resolution) => {
+                write!(
+                    f,
+                    "bitmap resolution can only be 1 or 2, found {resolution}"
+                )
+            }
+        }
+    }
+}
+
+impl From<IoError> for ImportError {
+    fn from(value: IoError) -> Self {
+        ImportError::Io(value)
+    }
+}
+impl From<zip::result::ZipError> for ImportError {
+    fn from(value: zip::result::ZipError) -> Self {
+        ImportError::Zip(value)
+    }
+}
+impl From<serde_json::Error> for ImportError {
+    fn from(value: serde_json::Error) -> Self {
+        ImportError::Json(value)
+    }
+}
+impl From<ResourceError> for ImportError {
+    fn from(value: ResourceError) -> Self {
+        ImportError::Resource(value)
+    }
+}
+
+/// Read `.sb3` archive from `reader`.
+/// Every file in the archive except `project.json` is loaded into memory as an asset.
+pub fn read_zip<R: Read + Seek>(reader: R) -> Result<ProjectBuilder, ImportError> {
+    let mut zip = zip::ZipArchive::new(reader)?;
+    let project: Project = serde_json::from_reader(zip.by_name("project.json")?)?;
+    let assets = read_assets(&mut zip, "project.json")?;
+    from_project(project, &assets)
+}
+
+pub fn import<P: AsRef<Path>>(path: P) -> Result<ProjectBuilder, ImportError> {
+    let zip_file = FsFile::options().read(true).open(path)?;
+    read_zip(zip_file)
+}
+
+/// Read `.sprite3` archive from `reader`.
+/// Variables and lists it refers to that aren't its own are taken as the stage's,
+/// see [`crate::target::StageReferences::ByName`].
+pub fn read_sprite3<R: Read + Seek>(reader: R) -> Result<SpriteBuilder, ImportError> {
+    let mut zip = zip::ZipArchive::new(reader)?;
+    let sprite: Sprite = serde_json::from_reader(zip.by_name("sprite.json")?)?;
+    let assets = read_assets(&mut zip, "sprite.json")?;
+    let varlist_ctx = stage_references(&sprite.target);
+    let broadcasts: HashMap<String, Uid> = sprite
+        .target
+        .broadcasts
+        .0
+        .iter()
+        .map(|(uid, broadcast)| (broadcast.name.clone(), Uid::new(uid)))
+        .collect();
+    sprite_builder_from(sprite, &assets, &varlist_ctx, &broadcasts)
+}
+
+pub fn import_sprite3<P: AsRef<Path>>(path: P) -> Result<SpriteBuilder, ImportError> {
+    let zip_file = FsFile::options().read(true).open(path)?;
+    read_sprite3(zip_file)
+}
+
+/// Every file in the archive except `json_name`
+fn read_assets<R: Read + Seek>(
+    zip: &mut zip::ZipArchive<R>,
+    json_name: &str,
+) -> Result<HashMap<String, Vec<u8>>, ImportError> {
+    let mut assets = HashMap::with_capacity(zip.len());
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        if file.is_dir() || file.name() == json_name {
+            continue;
+        }
+        let mut content = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut content)?;
+        assets.insert(file.name().to_owned(), content);
+    }
+    Ok(assets)
+}
+
+/// Reconstruct [`ProjectBuilder`] from already deserialized [`Project`].
+///
+/// `assets` is a map of md5ext (`"<md5 hash>.<extension>"`) to the file content.
+pub fn from_project(
+    project: Project,
+    assets: &HashMap<String, Vec<u8>>,
+) -> Result<ProjectBuilder, ImportError> {
+    let Project {
+        meta,
+        monitors,
+        targets,
+        ..
+    } = project;
+
+    let Some(global_varlist_ctx) = targets.iter().find_map(|target| match target {
+        SpriteOrStage::Stage(stage) => Some(varlist_context(&stage.target)),
+        SpriteOrStage::Sprite(_) => None,
+    }) else {
+        return Err(ImportError::MissingStage);
+    };
+    let all_broadcasts: HashMap<String, Uid> = targets
+        .iter()
+        .flat_map(|target| match target {
+            SpriteOrStage::Stage(stage) => &stage.target.broadcasts.0,
+            SpriteOrStage::Sprite(sprite) => &sprite.target.broadcasts.0,
+        })
+        .map(|(uid, broadcast)| (broadcast.name.clone(), Uid::new(uid)))
+        .collect();
+
+    let mut stage_builder = None;
+    let mut sprite_builders = Vec::with_capacity(targets.len());
+    for target in targets {
+        match target {
+            SpriteOrStage::Stage(stage) => {
+                stage_builder = Some(stage_builder_from(stage, assets, &all_broadcasts)?)
+            }
+            SpriteOrStage::Sprite(sprite) => sprite_builders.push(sprite_builder_from(
+                sprite,
+                assets,
+                &global_varlist_ctx,
+                &all_broadcasts,
+            )?),
+        }
+    }
+    let Some(stage_builder) = stage_builder else {
+        unreachable!("stage is already found when making global var list context")
+    };
+
+    Ok(ProjectBuilder {
+        stage_builder,
+        sprite_builders,
+        monitors: monitors
+            .into_iter()
+            .map(MonitorBuilder::from_monitor)
+            .collect(),
+        meta,
+        ..Default::default()
+    })
+}
+
+fn stage_builder_from(
+    stage: Stage,
+    assets: &HashMap<String, Vec<u8>>,
+    all_broadcasts: &HashMap<String, Uid>,
+) -> Result<StageBuilder, ImportError> {
+    Ok(StageBuilder {
+        target: target_builder_from(stage.target, assets, None, all_broadcasts)?,
+        tempo: number_to_i64(&stage.tempo),
+        video_state: stage.video_state,
+        video_transparency: number_to_i64(&stage.video_transparency),
+        text_to_speech_language: stage.text_to_speech_language,
+    })
+}
+
+fn sprite_builder_from(
+    sprite: Sprite,
+    assets: &HashMap<String, Vec<u8>>,
+    global_varlist_ctx: &GlobalVarListContext,
+    all_broadcasts: &HashMap<String, Uid>,
+) -> Result<SpriteBuilder, ImportError> {
+    Ok(SpriteBuilder {
+        target: target_builder_from(
+            sprite.target,
+            assets,
+            Some(global_varlist_ctx),
+            all_broadcasts,
+        )?,
+        visible: sprite.visible,
+        x: number_to_f64(&sprite.x),
+        y: number_to_f64(&sprite.y),
+        size: number_to_f64(&sprite.size),
+        direction: number_to_f64(&sprite.direction),
+        draggable: sprite.draggable,
+        rotation_style: sprite.rotation_style,
+    })
+}
+
+/// Variable and list of `target` by their name
+pub(crate) fn varlist_context(target: &Target) -> GlobalVarListContext {
+    GlobalVarListContext {
+        vars: target
+            .variables
+            .0
+            .iter()
+            .map(|(uid, var)| (var.name.clone(), Uid::new(uid)))
+            .collect(),
+        lists: target
+            .lists
+            .0
+            .iter()
+            .map(|(uid, list)| (list.name.clone(), Uid::new(uid)))
+            .collect(),
+    }
+}
+
+/// Variables and lists that blocks of `target` refer to by an id that `target` doesn't have
+fn stage_references(target: &Target) -> GlobalVarListContext {
+    let own = varlist_context(target);
+    let mut refs = GlobalVarListContext {
+        vars: HashMap::new(),
+        lists: HashMap::new(),
+    };
+    let mut add = |kind: &ListOrVariable, name: &str, id: &str| {
+        let (own, refs) = match kind {
+            ListOrVariable::Variable => (&own.vars, &mut refs.vars),
+            ListOrVariable::List => (&own.lists, &mut refs.lists),
+        };
+        if own.get(name).map(Uid::inner) != Some(id) {
+            refs.insert(name.to_owned(), Uid::new(id));
+        }
+    };
+    for block in target.blocks.0.values() {
+        let block = match block {
+            Block::VarList(vl) => {
+                add(&vl.kind, &vl.name, &vl.id);
+                continue;
+            }
+            Block::Normal(block) => block,
+        };
+        for (key, field) in &block.fields.0 {
+            let kind = match key.as_str() {
+                "VARIABLE" => ListOrVariable::Variable,
+                "LIST" => ListOrVariable::List,
+                _ => continue,
+            };
+            if let BlockField::WithId {
+                value,
+                id: Some(id),
+            } = field
+            {
+                add(&kind, &value_to_string(value), id);
+            }
+        }
+        for value in block.inputs.0.values().flat_map(|input| &input.inputs) {
+            match value {
+                Some(UidOrValue::Value(BlockInputValue::Variable { name, id })) => {
+                    add(&ListOrVariable::Variable, name, id)
+                }
+                Some(UidOrValue::Value(BlockInputValue::List { name, id })) => {
+                    add(&ListOrVariable::List, name, id)
+                }
+                _ => {}
+            }
+        }
+    }
+    refs
+}
+
+/// `global_varlist_ctx` suppose to be none when importing the Stage itself. Same as [`TargetBuilder::build`].
+fn target_builder_from(
+    target: Target,
+    assets: &HashMap<String, Vec<u8>>,
+    global_varlist_ctx: Option<&GlobalVarListContext>,
+    all_broadcasts: &HashMap<String, Uid>,
+) -> Result<TargetBuilder, ImportError> {
+    let this_varlist_ctx = varlist_context(&target);
+    let global_varlist_ctx = global_varlist_ctx.unwrap_or(&this_varlist_ctx);
+    let block_stackes = StackBuilder::from_target_blocks(
+        &target.blocks.0,
+        &target.comments.0,
+        &TargetContext {
+            global_vars: &global_varlist_ctx.vars,
+            global_lists: &global_varlist_ctx.lists,
+            this_sprite_vars: &this_varlist_ctx.vars,
+            this_sprite_lists: &this_varlist_ctx.lists,
+            all_broadcasts,
+        },
+    );
+    let variables = target
+        .variables
+        .0
+        .into_values()
+        .map(|var| {
+            (
+                var.name,
+                VariableBuilder {
+                    value: var.value,
+                    is_cloud_variable: var.is_cloud_variable,
+                },
+            )
+        })
+        .collect();
+    let lists = target
+        .lists
+        .0
+        .into_values()
+        .map(|list| (list.name, ListBuilder::new(list.values)))
+        .collect();
+    let broadcasts = target
+        .broadcasts
+        .0
+        .into_values()
+        .map(|broadcast| broadcast.name)
+        .collect();
+    // Comments that attached to a block are already in `block_stackes`
+    let comments = target
+        .comments
+        .0
+        .into_values()
+        .filter(|comment| comment.block_id.is_none())
+        .map(CommentBuilder::from_comment)
+        .collect();
+    let costumes = target
+        .costumes
+        .into_iter()
+        .map(|costume| costume_builder_from(costume, assets))
+        .collect::<Result<_, _>>()?;
+    let sounds = target
+        .sounds
+        .into_iter()
+        .map(|sound| sound_builder_from(sound, assets))
+        .collect::<Result<_, _>>()?;
+    Ok(TargetBuilder {
+        name: target.name,
+        variables,
+        lists,
+        broadcasts,
+        block_stackes,
+        comments,
+        costumes,
+        sounds,
+        current_costume: target.current_costume.max(0) as u64,
+        layer_order: target.layer_order.max(0) as u64,
+        volume: number_to_f64(&target.volume),
+        auto_layout: false,
+    })
+}
+
+fn costume_builder_from(
+    costume: Costume,
+    assets: &HashMap<String, Vec<u8>>,
+) -> Result<CostumeBuilder, ImportError> {
+    Ok(CostumeBuilder {
+        rotation_center_x: number_to_i64(&costume.rotation_center_x),
+        rotation_center_y: number_to_i64(&costume.rotation_center_y),
+        bitmap_resolution: match costume.bitmap_resolution {
+            Some(resolution) => BitmapResolution::from_u64(resolution)
+                .ok_or(ImportError::InvalidBitmapResolution(resolution))?,
+            None => BitmapResolution::Single,
+        },
+        asset: asset_builder_from(costume.asset, assets)?,
+    })
+}
+
+fn sound_builder_from(
+    sound: Sound,
+    assets: &HashMap<String, Vec<u8>>,
+) -> Result<SoundBuilder, ImportError> {
+    Ok(SoundBuilder {
+        rate: sound.rate,
+        sample_count: sound.sample_count,
+        format: sound.format,
+        asset: asset_builder_from(sound.asset, assets)?,
+    })
+}
+
+fn asset_builder_from(
+    asset: Asset,
+    assets: &HashMap<String, Vec<u8>>,
+) -> Result<AssetBuilder, ImportError> {
+    let Asset {
+        asset_id,
+        name,
+        md5ext,
+        data_format,
+    } = asset;
+    let md5ext = md5ext.unwrap_or_else(|| asset_id + "." + &data_format);
+    let Some(content) = assets.get(&md5ext) else {
+        return Err(ImportError::MissingAsset(md5ext));
+    };
+    let resource = Resource::new(data_format, content.clone())?;
+    Ok(AssetBuilder::new(name, resource))
+}
+
+#[cfg(test)]
+mod tests {
+    use sb_sbity::value::Value;
+
+    use super::*;
+    use crate::{
+        block::{BlockFieldBuilder, FieldKind},
+        blocks::{change_var_by, global_var, say, sprite_var, when_flag_clicked},
+        export::write_zip,
+        target::TargetBuilder,
+    };
+
+    fn costume(name: &str, svg: &str) -> CostumeBuilder {
+        let resource = Resource::new("svg".to_owned(), svg.as_bytes().to_vec()).unwrap();
+        let mut costume = CostumeBuilder::new(AssetBuilder::new(name, resource));
+        costume.set_rotation_center(1, 1);
+        costume
+    }
+
+    fn project() -> ProjectBuilder {
+        let score = BlockFieldBuilder::new_with_kind("score".to_owned(), FieldKind::GlobalVariable);
+        let mut script = when_flag_clicked()
+            .next(change_var_by(score, sprite_var("speed")))
+            .next(say(global_var("score")));
+        script.set_top_block_position(10., 20.);
+
+        let mut stage = StageBuilder::default();
+        stage
+            .target
+            .add_variable("score", VariableBuilder::new(Value::Number(3_i64.into())))
+            .add_list(
+                "names",
+                ListBuilder::new(vec![Value::Text("cat".to_owned())]),
+            )
+            .add_costume(costume("backdrop1", "<svg width='2' height='2'/>"));
+        let mut target = TargetBuilder::default();
+        target
+            .set_name("Cat")
+            .set_layer_order(1)
+            .add_variable("speed", VariableBuilder::new(Value::Number(2_i64.into())))
+            .add_costume(costume("cat", "<svg width='4' height='4'/>"))
+            .add_block_stack(script);
+        let mut sprite = SpriteBuilder::default();
+        sprite.set_target(target).set_pos(30., -40.);
+        let mut project = ProjectBuilder::default();
+        project
+            .set_stage(stage)
+            .add_sprite(sprite)
+            .add_monitor(MonitorBuilder::variable("score"));
+        project
+    }
+
+    #[test]
+    fn sb3_round_trip() {
+        let mut zip = std::io::Cursor::new(vec![]);
+        write_zip(&mut zip, project()).unwrap();
+        zip.set_position(0);
+        let read = read_zip(zip).unwrap();
+        assert_eq!(read.stage_builder, project().stage_builder);
+        assert_eq!(read.sprite_builders, project().sprite_builders);
+        assert_eq!(read.monitors, project().monitors);
+    }
+}
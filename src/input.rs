@@ -0,0 +1,259 @@
+//! Typed inputs for the helpers in [`crate::blocks`]
+//!
+//! Each type is an input slot of a certain kind, and it always builds with the same shadow
+//! that Scratch's editor would put in that slot. Literals convert straight into them:
+//! ```ignore
+//! move_steps(10).next(say("hi mom")).next(turn_right(22.5))
+//! ```
+//! Reporter ([`StackBuilder`]) is dropped on top of the slot's shadow like in the editor.
+//! [`BlockInputBuilder`] converts into any of them as-is when you need something else.
+
+use sb_sbity::{block::BlockInputValue, value::Value};
+
+use crate::{block::BlockInputBuilder, stack::StackBuilder, value::number_to_f64};
+
+type Bib = BlockInputBuilder;
+
+macro_rules! typed_inputs {
+    ($($(#[$meta:meta])* $name:ident => $shadow:expr;)*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct $name(Bib);
+
+            impl $name {
+                /// Shadow that this input has when it's left as is in the editor
+                pub fn shadow() -> Option<BlockInputValue> {
+                    $shadow
+                }
+
+                pub fn into_inner(self) -> Bib {
+                    self.0
+                }
+            }
+
+            impl From<StackBuilder> for $name {
+                fn from(reporter: StackBuilder) -> $name {
+                    $name(match $name::shadow() {
+                        Some(shadow) => Bib::stack_with_value_obscured(reporter, shadow),
+                        None => Bib::stack(reporter),
+                    })
+                }
+            }
+
+            impl From<Bib> for $name {
+                fn from(input: Bib) -> $name {
+                    $name(input)
+                }
+            }
+
+            impl From<$name> for Bib {
+                fn from(input: $name) -> Bib {
+                    input.0
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! from_literals {
+    ($name:ident, $variant:ident, $fix:ident; $($ty:ty)*) => {
+        impl $name {
+            /// Literal typed into the slot, number is fixed like the editor does and text is kept as is
+            pub(crate) fn literal(value: Value) -> BlockInputValue {
+                let value = match value {
+                    Value::Number(n) => number($fix(number_to_f64(&n))),
+                    text => text,
+                };
+                BlockInputValue::$variant { value }
+            }
+        }
+
+        $(
+            impl From<$ty> for $name {
+                fn from(n: $ty) -> $name {
+                    $name(Bib::value(BlockInputValue::$variant { value: number($fix(n as f64)) }))
+                }
+            }
+        )*
+    };
+}
+
+typed_inputs! {
+    /// Any number
+    Number => Some(BlockInputValue::Number { value: number(0.) });
+    /// Number that is 0 or more, ex. duration
+    PositiveNumber => Some(BlockInputValue::PositiveNumber { value: number(0.) });
+    /// Integer that is 0 or more, ex. times to repeat
+    WholeNumber => Some(BlockInputValue::PositiveInteger { value: number(0.) });
+    /// Any integer, ex. item index
+    Integer => Some(BlockInputValue::Integer { value: number(0.) });
+    /// Angle in degrees, it has a dial in the editor
+    Angle => Some(BlockInputValue::Angle { value: number(0.) });
+    /// Color picker. Converts from `"#rrggbb"` or `0xrrggbb`
+    Color => Some(BlockInputValue::Color { value: Value::Text("#000000".to_owned()) });
+    /// Any text
+    Text => Some(BlockInputValue::String { value: Value::Text("".to_owned()) });
+    /// Broadcast picker. Converts from the broadcast name.
+    /// Reporter covers `message1` like the editor, which is declared when building.
+    Broadcast => Some(BlockInputValue::Broadcast { name: "message1".to_owned(), id: "".to_owned() });
+}
+
+from_literals! { Number, Number, any; i32 i64 u32 f64 }
+from_literals! { PositiveNumber, PositiveNumber, positive; i32 i64 u32 f64 }
+from_literals! { WholeNumber, PositiveInteger, whole; i32 i64 u32 u64 f64 }
+from_literals! { Integer, Integer, integer; i32 i64 u32 f64 }
+from_literals! { Angle, Angle, any; i32 i64 f64 }
+
+// Numbers are fixed the same way the editor does when they're typed in:
+// negative becomes 0 in a positive slot and fraction is rounded in an integer slot.
+fn any(n: f64) -> f64 {
+    n
+}
+
+fn positive(n: f64) -> f64 {
+    n.max(0.)
+}
+
+fn whole(n: f64) -> f64 {
+    n.round().max(0.)
+}
+
+fn integer(n: f64) -> f64 {
+    n.round()
+}
+
+impl From<u32> for Color {
+    fn from(rgb: u32) -> Color {
+        Color::from(format!("#{:06x}", rgb & 0xffffff))
+    }
+}
+
+impl From<String> for Color {
+    fn from(hex: String) -> Color {
+        Color(Bib::value(BlockInputValue::Color {
+            value: Value::Text(hex),
+        }))
+    }
+}
+
+impl From<&str> for Color {
+    fn from(hex: &str) -> Color {
+        Color::from(hex.to_owned())
+    }
+}
+
+impl From<String> for Text {
+    fn from(text: String) -> Text {
+        Text(Bib::value(BlockInputValue::String {
+            value: Value::Text(text),
+        }))
+    }
+}
+
+impl From<&str> for Text {
+    fn from(text: &str) -> Text {
+        Text::from(text.to_owned())
+    }
+}
+
+impl From<i32> for Text {
+    fn from(n: i32) -> Text {
+        Text::from(n.to_string())
+    }
+}
+
+impl From<i64> for Text {
+    fn from(n: i64) -> Text {
+        Text::from(n.to_string())
+    }
+}
+
+impl From<f64> for Text {
+    fn from(n: f64) -> Text {
+        Text::from(n.to_string())
+    }
+}
+
+/// Id is filled in from the name when built
+impl From<String> for Broadcast {
+    fn from(name: String) -> Broadcast {
+        Broadcast(Bib::value(BlockInputValue::Broadcast {
+            name,
+            id: "".to_owned(),
+        }))
+    }
+}
+
+impl From<&str> for Broadcast {
+    fn from(name: &str) -> Broadcast {
+        Broadcast::from(name.to_owned())
+    }
+}
+
+impl From<StackBuilder> for Bib {
+    fn from(stack: StackBuilder) -> Bib {
+        Bib::stack(stack)
+    }
+}
+
+/// Whole numbers are kept as integer so they don't show up as `10.0` in the editor
+fn number(n: f64) -> Value {
+    if n.fract() == 0. && n.abs() < i64::MAX as f64 {
+        Value::Number((n as i64).into())
+    } else {
+        Value::Number(n.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sb_sbity::block::ShadowInputType;
+
+    use super::*;
+    use crate::{block::StackOrValue, blocks::answer};
+
+    fn value<T: Into<Bib>>(input: T) -> BlockInputValue {
+        match input.into().values.remove(0) {
+            Some(StackOrValue::Value(value)) => value,
+            other => panic!("expected a value, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn numbers_are_fixed_for_their_slot() {
+        assert_eq!(
+            value(PositiveNumber::from(-1.5)),
+            BlockInputValue::PositiveNumber { value: number(0.) }
+        );
+        assert_eq!(
+            value(WholeNumber::from(-3)),
+            BlockInputValue::PositiveInteger { value: number(0.) }
+        );
+        assert_eq!(
+            value(WholeNumber::from(2.6)),
+            BlockInputValue::PositiveInteger { value: number(3.) }
+        );
+        assert_eq!(
+            value(Integer::from(-2.4)),
+            BlockInputValue::Integer { value: number(-2.) }
+        );
+        assert_eq!(
+            Integer::literal(Value::Text("abc".to_owned())),
+            BlockInputValue::Integer {
+                value: Value::Text("abc".to_owned())
+            }
+        );
+    }
+
+    #[test]
+    fn broadcast_reporter_covers_menu() {
+        let input: Bib = Broadcast::from(answer()).into();
+        assert_eq!(input.shadow, ShadowInputType::ShadowObscured);
+        assert!(matches!(input.values[0], Some(StackOrValue::Stack(_))));
+        assert!(matches!(
+            &input.values[1],
+            Some(StackOrValue::Value(BlockInputValue::Broadcast { name, .. })) if name == "message1"
+        ));
+    }
+}
@@ -0,0 +1,455 @@
+//! Arranging scripts on the workspace like "Clean up blocks" in the editor
+//!
+//! Sizes are estimated from the block tree, so they're close to but not exactly what the editor draws.
+//! Scripts go from top to bottom in a column and a new column starts when it gets too tall.
+//! Comments on blocks are put to the right of their script, level with their block.
+
+use crate::{
+    block::{BlockBuilder, BlockNormalBuilder, StackOrValue},
+    comment::CommentBuilder,
+    opcode::{self, BlockShape},
+    schema::{self, InputKind},
+    scratchblocks,
+    stack::StackBuilder,
+};
+
+/// Height of a stack block with nothing in its inputs
+const ROW_HEIGHT: f64 = 48.;
+/// Extra height of each level of reporter inside reporter
+const NESTING_HEIGHT: f64 = 8.;
+/// Height of the hat's bump
+const HAT_HEIGHT: f64 = 20.;
+const REPORTER_HEIGHT: f64 = 40.;
+/// Height of an empty C block mouth
+const EMPTY_SUBSTACK_HEIGHT: f64 = 24.;
+/// Height of the C block's bottom arm
+const ARM_HEIGHT: f64 = 32.;
+/// How far in the C block's body is
+const SUBSTACK_INDENT: f64 = 16.;
+const CHAR_WIDTH: f64 = 8.;
+const PADDING: f64 = 24.;
+
+/// Space between scripts and between a script and its comments
+const GAP: f64 = 48.;
+/// Column is wrapped when the next script would go below this
+const COLUMN_HEIGHT: f64 = 1600.;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Size {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Put `stacks` in columns so that they don't overlap, in the order they're in.
+pub fn layout(stacks: &mut [StackBuilder]) {
+    let mut x = 0.;
+    let mut y = 0.;
+    let mut column_width: f64 = 0.;
+    for stack in stacks.iter_mut().filter(|stack| !stack.stack.is_empty()) {
+        let size = stack_size(stack);
+        if y > 0. && y + size.height > COLUMN_HEIGHT {
+            x += column_width + GAP;
+            y = 0.;
+            column_width = 0.;
+        }
+        stack.set_top_block_position(x, y);
+        let comments_width = place_comments(&mut stack.stack, x + size.width + GAP, y);
+        let width = match comments_width {
+            Some(comments_width) => size.width + GAP + comments_width,
+            None => size.width,
+        };
+        column_width = column_width.max(width);
+        y += size.height + GAP;
+    }
+}
+
+pub fn stack_size(stack: &StackBuilder) -> Size {
+    stack_size_of(&stack.stack)
+}
+
+fn stack_size_of(blocks: &[BlockBuilder]) -> Size {
+    blocks.iter().fold(
+        Size {
+            width: 0.,
+            height: 0.,
+        },
+        |size, block| {
+            let block_size = block_size(block);
+            Size {
+                width: size.width.max(block_size.width),
+                height: size.height + block_size.height,
+            }
+        },
+    )
+}
+
+/// Size of the block together with the blocks inside its C
+pub fn block_size(block: &BlockBuilder) -> Size {
+    let b = match block {
+        BlockBuilder::Normal(b) => b,
+        BlockBuilder::VarList(vl) => {
+            return Size {
+                width: text_width(&vl.name),
+                height: REPORTER_HEIGHT,
+            }
+        }
+    };
+    let width = text_width(&scratchblocks::block_text(b));
+    let row_height = ROW_HEIGHT + NESTING_HEIGHT * reporter_depth(b) as f64;
+    match opcode::shape_of(b.opcode()) {
+        Some(BlockShape::Hat) => Size {
+            width,
+            height: row_height + HAT_HEIGHT,
+        },
+        Some(BlockShape::Reporter | BlockShape::Boolean) => Size {
+            width,
+            height: REPORTER_HEIGHT + NESTING_HEIGHT * reporter_depth(b) as f64,
+        },
+        Some(BlockShape::C | BlockShape::CCap) => {
+            let substacks: Vec<Size> = substack_keys(b)
+                .into_iter()
+                .map(|key| match substack(b, key) {
+                    Some(substack) => stack_size_of(&substack.stack),
+                    None => Size {
+                        width: 0.,
+                        height: EMPTY_SUBSTACK_HEIGHT,
+                    },
+                })
+                .collect();
+            // Each substack after the first one has an `else` row above it
+            let rows = substacks.len().max(1) as f64;
+            Size {
+                width: substacks.iter().fold(width, |width, substack| {
+                    width.max(SUBSTACK_INDENT + substack.width)
+                }),
+                height: row_height * rows
+                    + substacks
+                        .iter()
+                        .map(|substack| substack.height)
+                        .sum::<f64>()
+                    + ARM_HEIGHT,
+            }
+        }
+        _ => Size {
+            width,
+            height: row_height,
+        },
+    }
+}
+
+fn text_width(text: &str) -> f64 {
+    text.chars().count() as f64 * CHAR_WIDTH + PADDING
+}
+
+/// How many levels of reporters are inside each other in the inputs
+fn reporter_depth(b: &BlockNormalBuilder) -> usize {
+    b.inputs()
+        .iter()
+        .filter(|(key, _)| !substack_keys(b).contains(&key.as_str()))
+        .flat_map(|(_, input)| input.values.iter().flatten())
+        .map(|value| match value {
+            StackOrValue::Stack(stack) => match stack.stack.first() {
+                Some(BlockBuilder::Normal(reporter)) if !reporter.is_shadow() => {
+                    1 + reporter_depth(reporter)
+                }
+                Some(BlockBuilder::VarList(_)) => 1,
+                _ => 0,
+            },
+            StackOrValue::Value(_) => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Keys of the C block's substacks in order from the top
+fn substack_keys(b: &BlockNormalBuilder) -> Vec<&'static str> {
+    let Some(inputs) = schema::get(b.opcode()).and_then(|schema| schema.inputs.as_ref()) else {
+        return vec![];
+    };
+    ["SUBSTACK", "SUBSTACK2"]
+        .into_iter()
+        .filter(|key| inputs.get(*key) == Some(&InputKind::Substack))
+        .collect()
+}
+
+fn substack<'a>(b: &'a BlockNormalBuilder, key: &str) -> Option<&'a StackBuilder> {
+    match b.inputs().get(key)?.values.iter().flatten().next()? {
+        StackOrValue::Stack(stack) => Some(stack),
+        StackOrValue::Value(_) => None,
+    }
+}
+
+/// Move comments of the blocks in the stack that starts at `y` to `comment_x`.
+/// Returns the widest comment, None if there isn't any.
+fn place_comments(blocks: &mut [BlockBuilder], comment_x: f64, mut y: f64) -> Option<f64> {
+    let mut widest = None;
+    for block in blocks {
+        let height = block_size(block).height;
+        match block {
+            BlockBuilder::VarList(vl) => {
+                if let Some(comment) = &mut vl.comment {
+                    widest = wider(widest, place(comment, comment_x, y));
+                }
+            }
+            BlockBuilder::Normal(b) => {
+                let row_height = ROW_HEIGHT + NESTING_HEIGHT * reporter_depth(b) as f64;
+                let substack_keys = substack_keys(b);
+                if let Some(comment) = b.comment_mut() {
+                    widest = wider(widest, place(comment, comment_x, y));
+                }
+                for (key, input) in b.inputs_mut() {
+                    if substack_keys.contains(&key.as_str()) {
+                        continue;
+                    }
+                    for value in input.values.iter_mut().flatten() {
+                        if let StackOrValue::Stack(reporter) = value {
+                            // Comments on reporters are level with the block they're in
+                            widest = wider(widest, place_all(&mut reporter.stack, comment_x, y));
+                        }
+                    }
+                }
+                let mut substack_y = y + row_height;
+                for key in substack_keys {
+                    let substack = b
+                        .inputs_mut()
+                        .get_mut(key)
+                        .and_then(|input| input.values.iter_mut().flatten().next());
+                    let substack_height = match substack {
+                        Some(StackOrValue::Stack(substack)) => {
+                            let substack_height = stack_size(substack).height;
+                            widest = wider(
+                                widest,
+                                place_comments(&mut substack.stack, comment_x, substack_y),
+                            );
+                            substack_height
+                        }
+                        _ => EMPTY_SUBSTACK_HEIGHT,
+                    };
+                    substack_y += substack_height + row_height;
+                }
+            }
+        }
+        y += height;
+    }
+    widest
+}
+
+/// Move every comment in the reporters to the same place
+fn place_all(blocks: &mut [BlockBuilder], comment_x: f64, y: f64) -> Option<f64> {
+    let mut widest = None;
+    for block in blocks {
+        match block {
+            BlockBuilder::VarList(vl) => {
+                if let Some(comment) = &mut vl.comment {
+                    widest = wider(widest, place(comment, comment_x, y));
+                }
+            }
+            BlockBuilder::Normal(b) => {
+                if let Some(comment) = b.comment_mut() {
+                    widest = wider(widest, place(comment, comment_x, y));
+                }
+                for input in b.inputs_mut().values_mut() {
+                    for value in input.values.iter_mut().flatten() {
+                        if let StackOrValue::Stack(reporter) = value {
+                            widest = wider(widest, place_all(&mut reporter.stack, comment_x, y));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    widest
+}
+
+fn place(comment: &mut CommentBuilder, x: f64, y: f64) -> Option<f64> {
+    comment.set_pos(Some(x), Some(y));
+    Some(comment.width as f64)
+}
+
+fn wider(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block::BlockInputBuilder,
+        blocks::{join, move_steps, repeat, say, think, turn_right, when_flag_clicked},
+        build_context::TargetContext,
+        uid::{Uid, UidGenerator, UidMode},
+        value::number_to_f64,
+    };
+    use sb_sbity::{block::Block, comment::Comment};
+    use std::collections::HashMap;
+
+    #[derive(Debug)]
+    struct Rect {
+        x: f64,
+        y: f64,
+        size: Size,
+    }
+
+    impl Rect {
+        fn overlaps(&self, other: &Rect) -> bool {
+            self.x < other.x + other.size.width
+                && other.x < self.x + self.size.width
+                && self.y < other.y + other.size.height
+                && other.y < self.y + self.size.height
+        }
+    }
+
+    fn build(stack: &StackBuilder) -> (HashMap<Uid, Block>, HashMap<Uid, Comment>) {
+        let empty = HashMap::new();
+        let target_context = TargetContext {
+            global_vars: &empty,
+            global_lists: &empty,
+            this_sprite_vars: &empty,
+            this_sprite_lists: &empty,
+            all_broadcasts: &empty,
+        };
+        let mut comments = HashMap::new();
+        let blocks = stack.clone().build(
+            &Uid::new("top"),
+            &mut comments,
+            &target_context,
+            &mut UidGenerator::new(UidMode::Sequential),
+        );
+        (blocks, comments)
+    }
+
+    /// Where the top block is once the stack is built
+    fn position(stack: &StackBuilder) -> (f64, f64) {
+        let (blocks, _) = build(stack);
+        let Some(Block::Normal(top)) = blocks.get(&Uid::new("top")) else {
+            panic!("top block isn't a normal block");
+        };
+        (
+            number_to_f64(top.x.as_ref().unwrap()),
+            number_to_f64(top.y.as_ref().unwrap()),
+        )
+    }
+
+    fn rect(stack: &StackBuilder) -> Rect {
+        let (x, y) = position(stack);
+        Rect {
+            x,
+            y,
+            size: stack_size(stack),
+        }
+    }
+
+    fn comment_position(stack: &StackBuilder, content: &str) -> (f64, f64) {
+        let (_, comments) = build(stack);
+        let comment = comments
+            .values()
+            .find(|comment| comment.text == content)
+            .unwrap();
+        (
+            number_to_f64(comment.x.as_ref().unwrap()),
+            number_to_f64(comment.y.as_ref().unwrap()),
+        )
+    }
+
+    fn commented(mut stack: StackBuilder, content: &str) -> StackBuilder {
+        let BlockBuilder::Normal(b) = &mut stack.stack[0] else {
+            unreachable!()
+        };
+        b.set_comment(Some(CommentBuilder::new(content)));
+        stack
+    }
+
+    fn script(moves: usize) -> StackBuilder {
+        (0..moves).fold(when_flag_clicked(), |script, i| {
+            script.next(move_steps(i as i64))
+        })
+    }
+
+    #[test]
+    fn stacks_dont_overlap() {
+        let mut stacks = vec![
+            script(3),
+            when_flag_clicked().next(repeat(
+                10,
+                Some(BlockInputBuilder::stack(
+                    say(join("a long message ", join("inside ", "another join")))
+                        .next(turn_right(15)),
+                )),
+            )),
+            think("hmm"),
+            script(1),
+        ];
+        layout(&mut stacks);
+        let rects: Vec<Rect> = stacks.iter().map(rect).collect();
+        assert_eq!((rects[0].x, rects[0].y), (0., 0.));
+        for (i, a) in rects.iter().enumerate() {
+            for b in &rects[i + 1..] {
+                assert!(!a.overlaps(b), "{a:?} overlaps {b:?}");
+                assert!(b.y > a.y, "stacks keep their order down the column");
+            }
+        }
+    }
+
+    #[test]
+    fn columns_wrap_at_column_height() {
+        let mut stacks = vec![script(10); 8];
+        let height = stack_size(&stacks[0]).height;
+        layout(&mut stacks);
+        let rects: Vec<Rect> = stacks.iter().map(rect).collect();
+        let per_column = ((COLUMN_HEIGHT + GAP) / (height + GAP)) as usize;
+        assert!(per_column < rects.len());
+        for (i, rect) in rects.iter().enumerate() {
+            assert!(rect.y + rect.size.height <= COLUMN_HEIGHT);
+            assert_eq!(rect.y, (i % per_column) as f64 * (height + GAP));
+        }
+        assert_eq!(rects[per_column - 1].x, 0.);
+        assert_eq!(
+            rects[per_column].x,
+            rects[0].size.width + GAP,
+            "next column starts right of the widest script"
+        );
+        for (i, a) in rects.iter().enumerate() {
+            for b in &rects[i + 1..] {
+                assert!(!a.overlaps(b), "{a:?} overlaps {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn comments_are_right_of_their_script_level_with_their_block() {
+        let script = when_flag_clicked()
+            .next(commented(move_steps(10), "move"))
+            .next(repeat(
+                3,
+                Some(BlockInputBuilder::stack(
+                    turn_right(15).next(commented(say("hi"), "say")),
+                )),
+            ));
+        let mut stacks = vec![script, commented(think("hmm"), "think")];
+        layout(&mut stacks);
+        let width = stack_size(&stacks[0]).width;
+        let hat = ROW_HEIGHT + HAT_HEIGHT;
+        assert_eq!(comment_position(&stacks[0], "move"), (width + GAP, hat));
+        assert_eq!(
+            comment_position(&stacks[0], "say"),
+            (width + GAP, hat + ROW_HEIGHT * 3.)
+        );
+        let (x, y) = position(&stacks[1]);
+        assert_eq!(x, 0.);
+        assert_eq!(
+            comment_position(&stacks[1], "think"),
+            (stack_size(&stacks[1]).width + GAP, y)
+        );
+    }
+
+    #[test]
+    fn top_block_position_survives_build() {
+        let mut stack = script(2);
+        assert_eq!(position(&stack), (0., 0.));
+        stack.set_top_block_position(120., -35.);
+        assert_eq!(position(&stack), (120., -35.));
+    }
+}
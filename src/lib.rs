@@ -8,16 +8,24 @@
 //! Feel free to ask in github discussion. I will make sure to answer all of you questions if no one do so!
 
 pub mod asset;
+pub mod audio;
 pub mod block;
+pub mod cloud;
 pub mod comment;
 pub mod data;
+pub mod input;
+pub mod layout;
+pub mod monitor;
 pub mod project;
 pub mod stack;
 pub mod target;
 
+pub mod image;
 pub mod opcode;
 pub mod resource;
+pub mod schema;
 pub mod uid;
+mod value;
 
 pub mod build_context;
 
@@ -26,23 +34,30 @@ pub mod import;
 
 pub mod block_definer;
 pub mod blocks;
+pub mod dsl;
+pub mod scratchblocks;
+
+pub mod validate;
 
 pub mod prelude {
     pub use self::{
-        asset::{AssetBuilder, CostumeBuilder, SoundBuilder},
+        asset::{AssetBuilder, BitmapResolution, CostumeBuilder, SoundBuilder},
         block::{
             BlockBuilder, BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder,
             BlockVarListBuilder, FieldKind, StackOrValue, VarListFrom,
         },
+        block_definer::{ArgumentKind, ProcedureBuilder},
         build_context::{GlobalVarListContext, TargetContext},
         comment::CommentBuilder,
         data::{ListBuilder, VariableBuilder},
+        input::{Angle, Broadcast, Color, Integer, Number, PositiveNumber, Text, WholeNumber},
+        monitor::{MonitorBuilder, MonitorSource},
         opcode::StandardOpCode,
         project::ProjectBuilder,
         resource::{Resource, ResourceError},
         stack::StackBuilder,
-        target::{SpriteBuilder, StageBuilder, TargetBuilder},
-        uid::Uid,
+        target::{SpriteBuilder, StageBuilder, StageReferences, TargetBuilder},
+        uid::{Uid, UidGenerator, UidMode},
     };
     use super::*;
     pub use sb_sbity::block::{BlockInputValue, ShadowInputType};
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+
+use sb_sbity::{
+    monitor::{ListOrValue, Mode, Monitor},
+    string_hashmap::StringHashMap,
+    target::{SpriteOrStage, Target},
+    value::{OpCode, Value},
+};
+
+use crate::{
+    cloud,
+    opcode::StandardOpCode,
+    uid::{Uid, UidGenerator},
+    value::number_to_f64,
+};
+
+/// What the monitor is displaying
+#[derive(Debug, Clone, PartialEq)]
+pub enum MonitorSource {
+    /// Variable name
+    Variable(String),
+    /// List name
+    List(String),
+    /// Reporter that doesn't have any input like `sensing_timer` or `looks_size`.
+    /// `params` is the fields of the reporter, ex. `"NUMBER_NAME"` of `looks_costumenumbername`.
+    Reporter {
+        opcode: OpCode,
+        params: HashMap<String, String>,
+    },
+}
+
+#[rustfmt::skip]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorBuilder {
+    pub source:      MonitorSource,
+    /// Sprite that owns the variable, list or reporter. None is the Stage.
+    /// Variable or list that isn't in this sprite is looked up in the Stage.
+    pub sprite_name: Option<String>,
+    pub mode:        Mode,
+    pub x:           f64,
+    pub y:           f64,
+    /// 0 is automatically sized by Scratch
+    pub width:       f64,
+    /// 0 is automatically sized by Scratch
+    pub height:      f64,
+    pub slider_min:  f64,
+    pub slider_max:  f64,
+    /// Slider only moves by whole number
+    pub is_discrete: bool,
+    pub visible:     bool,
+}
+
+impl MonitorBuilder {
+    pub fn new(source: MonitorSource) -> MonitorBuilder {
+        MonitorBuilder {
+            mode: match source {
+                MonitorSource::List(_) => Mode::List,
+                _ => Mode::Default,
+            },
+            source,
+            ..Default::default()
+        }
+    }
+
+    pub fn variable<S: Into<String>>(name: S) -> MonitorBuilder {
+        MonitorBuilder::new(MonitorSource::Variable(name.into()))
+    }
+
+    pub fn list<S: Into<String>>(name: S) -> MonitorBuilder {
+        MonitorBuilder::new(MonitorSource::List(name.into()))
+    }
+
+    pub fn reporter<O: Into<OpCode>>(opcode: O) -> MonitorBuilder {
+        MonitorBuilder::new(MonitorSource::Reporter {
+            opcode: opcode.into(),
+            params: HashMap::default(),
+        })
+    }
+
+    /// Set a field of the reporter. Does nothing for variable and list.
+    pub fn add_param<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) -> &mut Self {
+        if let MonitorSource::Reporter { params, .. } = &mut self.source {
+            params.insert(key.into(), value.into());
+        }
+        self
+    }
+
+    pub fn set_sprite<S: Into<String>>(&mut self, sprite_name: Option<S>) -> &mut Self {
+        self.sprite_name = sprite_name.map(Into::into);
+        self
+    }
+
+    pub fn set_mode(&mut self, mode: Mode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn set_pos(&mut self, x: f64, y: f64) -> &mut Self {
+        self.x = x;
+        self.y = y;
+        self
+    }
+
+    pub fn set_size(&mut self, width: f64, height: f64) -> &mut Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn set_slider(&mut self, min: f64, max: f64, is_discrete: bool) -> &mut Self {
+        self.slider_min = min;
+        self.slider_max = max;
+        self.is_discrete = is_discrete;
+        self
+    }
+
+    pub fn set_visible(&mut self, visible: bool) -> &mut Self {
+        self.visible = visible;
+        self
+    }
+
+    /// `targets` is every built target in the project.
+    /// Variable and list ids are taken from there so this is built after all the targets.
+    pub fn build(self, targets: &[SpriteOrStage], uid_gen: &mut UidGenerator) -> Monitor {
+        let MonitorBuilder {
+            source,
+            sprite_name,
+            mode,
+            x,
+            y,
+            width,
+            height,
+            slider_min,
+            slider_max,
+            is_discrete,
+            visible,
+        } = self;
+        let stage = targets.iter().find_map(|target| match target {
+            SpriteOrStage::Stage(stage) => Some(&stage.target),
+            SpriteOrStage::Sprite(_) => None,
+        });
+        let sprite = sprite_name.as_ref().and_then(|sprite_name| {
+            targets.iter().find_map(|target| match target {
+                SpriteOrStage::Sprite(sprite) if &sprite.target.name == sprite_name => {
+                    Some(&sprite.target)
+                }
+                _ => None,
+            })
+        });
+        let is_variable = matches!(source, MonitorSource::Variable(_));
+
+        let (id, opcode, params, sprite_name, value) = match source {
+            MonitorSource::Variable(name) => {
+                let found = [sprite, stage].into_iter().flatten().find_map(|target| {
+                    target
+                        .variables
+                        .0
+                        .iter()
+                        // Cloud variable is also found without its prefix
+                        .find(|(_, var)| {
+                            var.name == name
+                                || (var.is_cloud_variable && var.name == cloud::cloud_name(&name))
+                        })
+                        .map(|(uid, var)| (target, uid.clone(), var.clone()))
+                });
+                let (sprite_name, id, var) = owner_of(found, sprite);
+                // Name of the variable itself, which has the prefix when it's a cloud variable
+                let (name, value) = match var {
+                    Some(var) => (var.name, var.value),
+                    None => (name, Value::Number(0.into())),
+                };
+                (
+                    id,
+                    StandardOpCode::data_variable.into(),
+                    HashMap::from([("VARIABLE".to_owned(), name)]),
+                    sprite_name,
+                    ListOrValue::Value(value),
+                )
+            }
+            MonitorSource::List(name) => {
+                let found = [sprite, stage].into_iter().flatten().find_map(|target| {
+                    target
+                        .lists
+                        .0
+                        .iter()
+                        .find(|(_, list)| list.name == name)
+                        .map(|(uid, list)| (target, uid.clone(), list.values.clone()))
+                });
+                let (sprite_name, id, values) = owner_of(found, sprite);
+                (
+                    id,
+                    StandardOpCode::data_listcontents.into(),
+                    HashMap::from([("LIST".to_owned(), name)]),
+                    sprite_name,
+                    ListOrValue::List(values.unwrap_or_default()),
+                )
+            }
+            MonitorSource::Reporter { opcode, params } => (
+                reporter_monitor_id(&opcode, &params, uid_gen),
+                opcode,
+                params,
+                sprite.map(|sprite| sprite.name.clone()),
+                ListOrValue::Value(Value::Number(0.into())),
+            ),
+        };
+        Monitor {
+            id,
+            mode,
+            opcode,
+            params: StringHashMap(params),
+            sprite_name,
+            value,
+            width: width.into(),
+            height: height.into(),
+            x: x.into(),
+            y: y.into(),
+            visible,
+            slider_min: is_variable.then(|| slider_min.into()),
+            slider_max: is_variable.then(|| slider_max.into()),
+            is_discrete: is_variable.then_some(is_discrete),
+        }
+    }
+
+    /// Reverse of [`MonitorBuilder::build`]
+    pub fn from_monitor(monitor: Monitor) -> MonitorBuilder {
+        let Monitor {
+            mode,
+            opcode,
+            params,
+            sprite_name,
+            width,
+            height,
+            x,
+            y,
+            visible,
+            slider_min,
+            slider_max,
+            is_discrete,
+            ..
+        } = monitor;
+        let mut params = params.0;
+        let source = match opcode.parse::<StandardOpCode>() {
+            Ok(StandardOpCode::data_variable) => {
+                MonitorSource::Variable(params.remove("VARIABLE").unwrap_or_default())
+            }
+            Ok(StandardOpCode::data_listcontents) => {
+                MonitorSource::List(params.remove("LIST").unwrap_or_default())
+            }
+            _ => MonitorSource::Reporter { opcode, params },
+        };
+        let default = MonitorBuilder::default();
+        MonitorBuilder {
+            source,
+            sprite_name,
+            mode,
+            x: number_to_f64(&x),
+            y: number_to_f64(&y),
+            width: number_to_f64(&width),
+            height: number_to_f64(&height),
+            slider_min: slider_min
+                .as_ref()
+                .map_or(default.slider_min, number_to_f64),
+            slider_max: slider_max
+                .as_ref()
+                .map_or(default.slider_max, number_to_f64),
+            is_discrete: is_discrete.unwrap_or(default.is_discrete),
+            visible,
+        }
+    }
+}
+
+/// Id that Scratch's editor gives the monitor of the reporter, so its checkbox in the palette is ticked.
+/// It's the opcode without the category, and the values of the fields after it like `current_year`.
+/// Reporters of each sprite like `motion_xposition` start with the id of the sprite which
+/// isn't saved in the project, Scratch replaces it when loading so a generated one stands in.
+fn reporter_monitor_id(
+    opcode: &str,
+    params: &HashMap<String, String>,
+    uid_gen: &mut UidGenerator,
+) -> String {
+    use StandardOpCode::*;
+    let mut id = match opcode.parse::<StandardOpCode>() {
+        Ok(
+            motion_xposition
+            | motion_yposition
+            | motion_direction
+            | looks_costumenumbername
+            | looks_size
+            | sound_volume,
+        ) => format!(
+            "{}_{}",
+            uid_gen.generate().inner(),
+            without_category(opcode)
+        ),
+        Ok(
+            looks_backdropnumbername
+            | sensing_answer
+            | sensing_loudness
+            | sensing_timer
+            | sensing_current
+            | sensing_dayssince2000
+            | sensing_username,
+        ) => without_category(opcode).to_owned(),
+        // Anything else like extensions' reporters is named by its opcode
+        _ => opcode.to_owned(),
+    };
+    let mut params: Vec<(&String, &String)> = params.iter().collect();
+    params.sort();
+    for (key, value) in params {
+        id.push('_');
+        match key.as_str() {
+            "CURRENTMENU" => id.push_str(&value.to_lowercase()),
+            _ => id.push_str(value),
+        }
+    }
+    id
+}
+
+fn without_category(opcode: &str) -> &str {
+    opcode.split_once('_').map_or(opcode, |(_, name)| name)
+}
+
+/// Sprite name (None if it's from the Stage), uid and value of the found variable or list.
+fn owner_of<T>(
+    found: Option<(&Target, String, T)>,
+    sprite: Option<&Target>,
+) -> (Option<String>, String, Option<T>) {
+    match found {
+        Some((target, uid, value)) => {
+            let is_sprite = sprite.is_some_and(|sprite| std::ptr::eq(sprite, target));
+            (is_sprite.then(|| target.name.clone()), uid, Some(value))
+        }
+        None => (
+            sprite.map(|sprite| sprite.name.clone()),
+            Uid::new("__unknown__").into_inner(),
+            None,
+        ),
+    }
+}
+
+impl Default for MonitorBuilder {
+    #[rustfmt::skip]
+    fn default() -> Self {
+        MonitorBuilder {
+            source:      MonitorSource::Variable("".to_owned()),
+            sprite_name: None,
+            mode:        Mode::Default,
+            x:           0.,
+            y:           0.,
+            width:       0.,
+            height:      0.,
+            slider_min:  0.,
+            slider_max:  100.,
+            is_discrete: true,
+            visible:     true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        export::to_json, project::ProjectBuilder, target::StageBuilder, uid::UidMode,
+        validate::DiagnosticKind,
+    };
+
+    #[test]
+    fn reporter_ids_are_from_opcode() {
+        let mut uid_gen = UidGenerator::new(UidMode::Sequential);
+        let mut id = |monitor: &MonitorBuilder| monitor.clone().build(&[], &mut uid_gen).id;
+        assert_eq!(id(&MonitorBuilder::reporter("sensing_timer")), "timer");
+        assert_eq!(
+            id(MonitorBuilder::reporter("sensing_current").add_param("CURRENTMENU", "YEAR")),
+            "current_year"
+        );
+        assert_eq!(
+            id(&MonitorBuilder::reporter("music_getTempo")),
+            "music_getTempo"
+        );
+        let costume =
+            id(MonitorBuilder::reporter("looks_costumenumbername")
+                .add_param("NUMBER_NAME", "number"));
+        assert!(costume.ends_with("_costumenumbername_number"), "{costume}");
+    }
+
+    #[test]
+    fn unresolved_monitor_is_reported() {
+        let mut project = ProjectBuilder::default();
+        project
+            .add_monitor(MonitorBuilder::variable("missing"))
+            .add_monitor(MonitorBuilder::reporter("sensing_timer"));
+        let unresolved: Vec<DiagnosticKind> = project
+            .validate()
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind)
+            .filter(|kind| matches!(kind, DiagnosticKind::UnresolvedMonitor(_)))
+            .collect();
+        assert_eq!(
+            unresolved,
+            vec![DiagnosticKind::UnresolvedMonitor(MonitorSource::Variable(
+                "missing".to_owned()
+            ))]
+        );
+    }
+
+    #[test]
+    fn cloud_variable_monitor_has_the_prefixed_name() {
+        let mut stage = StageBuilder::default();
+        stage.add_cloud_variable("high score", 10.);
+        let mut project = ProjectBuilder::default();
+        project
+            .set_stage(stage)
+            .add_monitor(MonitorBuilder::variable("high score"));
+        let (json, _) = to_json(project);
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let monitor = &json["monitors"][0];
+        assert_eq!(monitor["params"]["VARIABLE"], "☁ high score");
+        assert_eq!(monitor["value"], 10.);
+    }
+}
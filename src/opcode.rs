@@ -8,8 +8,87 @@
 
 #![allow(unused)]
 
+use std::str::FromStr;
+
 use sb_sbity::value::OpCode;
 
+use crate::schema;
+
+macro_rules! opcodes {
+    ($(#[$meta:meta])* pub enum $name:ident { $($(#[$vmeta:meta])* $variant:ident,)* }) => {
+        $(#[$meta])*
+        pub enum $name {
+            $($(#[$vmeta])* $variant,)*
+        }
+
+        impl $name {
+            pub const ALL: &'static [$name] = &[$($name::$variant,)*];
+        }
+
+        impl FromStr for $name {
+            type Err = UnknownOpCode;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $(stringify!($variant) => Ok($name::$variant),)*
+                    _ => Err(UnknownOpCode(s.to_owned())),
+                }
+            }
+        }
+    };
+}
+
+/// Opcode string that doesn't match any variant when parsing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownOpCode(pub String);
+
+impl std::error::Error for UnknownOpCode {}
+
+impl std::fmt::Display for UnknownOpCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown opcode `{}`", self.0)
+    }
+}
+
+/// How a block connects to other blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockShape {
+    /// Starts a script, ex. "when flag clicked"
+    Hat,
+    /// Ex. "move 10 steps"
+    Stack,
+    /// Ends a script, ex. "delete this clone"
+    Cap,
+    /// Stack block that wraps other blocks, ex. "repeat"
+    C,
+    /// C block that ends a script, ex. "forever"
+    CCap,
+    /// Reporter of true or false, ex. "touching mouse-pointer?"
+    Boolean,
+    /// Reporter of a value, ex. "x position". Menus are this too.
+    Reporter,
+}
+
+impl BlockShape {
+    pub fn is_reporter(&self) -> bool {
+        matches!(self, BlockShape::Boolean | BlockShape::Reporter)
+    }
+
+    /// Other blocks can go after this block
+    pub fn has_next(&self) -> bool {
+        matches!(self, BlockShape::Hat | BlockShape::Stack | BlockShape::C)
+    }
+
+    /// This block can go after other blocks
+    pub fn has_previous(&self) -> bool {
+        matches!(
+            self,
+            BlockShape::Stack | BlockShape::Cap | BlockShape::C | BlockShape::CCap
+        )
+    }
+}
+
+opcodes! {
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StandardOpCode {
@@ -63,16 +142,6 @@ pub enum StandardOpCode {
     event_broadcast,
     event_broadcastandwait,
     event_whenkeypressed,
-    extension_pen_down,
-    extension_music_drum,
-    extension_wedo_motor,
-    extension_wedo_hat,
-    extension_wedo_boolean,
-    extension_wedo_tilt_reporter,
-    extension_wedo_tilt_menu,
-    extension_music_reporter,
-    extension_microbit_display,
-    extension_music_play_note,
     looks_sayforsecs,
     looks_say,
     looks_thinkforsecs,
@@ -186,7 +255,51 @@ pub enum StandardOpCode {
     sound_setvolumeto,
     sound_volume,
 }
+}
+
+impl StandardOpCode {
+    // The `extension_*` opcodes used to be variants here but they're only demo blocks
+    // of scratch-blocks that Scratch can't run. Each points to the real block that does the same.
+
+    #[deprecated(note = "use `PenExtensionOpCode::pen_penDown`")]
+    #[allow(non_upper_case_globals)]
+    pub const extension_pen_down: PenExtensionOpCode = PenExtensionOpCode::pen_penDown;
+    #[deprecated(note = "use `MusicOpCode::music_playDrumForBeats`")]
+    #[allow(non_upper_case_globals)]
+    pub const extension_music_drum: MusicOpCode = MusicOpCode::music_playDrumForBeats;
+    #[deprecated(note = "use `MusicOpCode::music_getTempo`")]
+    #[allow(non_upper_case_globals)]
+    pub const extension_music_reporter: MusicOpCode = MusicOpCode::music_getTempo;
+    #[deprecated(note = "use `MusicOpCode::music_playNoteForBeats`")]
+    #[allow(non_upper_case_globals)]
+    pub const extension_music_play_note: MusicOpCode = MusicOpCode::music_playNoteForBeats;
+    #[deprecated(note = "use `Wedo2OpCode::wedo2_motorOnFor`")]
+    #[allow(non_upper_case_globals)]
+    pub const extension_wedo_motor: Wedo2OpCode = Wedo2OpCode::wedo2_motorOnFor;
+    #[deprecated(note = "use `Wedo2OpCode::wedo2_whenTilted`")]
+    #[allow(non_upper_case_globals)]
+    pub const extension_wedo_hat: Wedo2OpCode = Wedo2OpCode::wedo2_whenTilted;
+    #[deprecated(note = "use `Wedo2OpCode::wedo2_isTilted`")]
+    #[allow(non_upper_case_globals)]
+    pub const extension_wedo_boolean: Wedo2OpCode = Wedo2OpCode::wedo2_isTilted;
+    #[deprecated(note = "use `Wedo2OpCode::wedo2_getTiltAngle`")]
+    #[allow(non_upper_case_globals)]
+    pub const extension_wedo_tilt_reporter: Wedo2OpCode = Wedo2OpCode::wedo2_getTiltAngle;
+    #[deprecated(note = "use `Wedo2OpCode::wedo2_menu_TILT_DIRECTION`")]
+    #[allow(non_upper_case_globals)]
+    pub const extension_wedo_tilt_menu: Wedo2OpCode = Wedo2OpCode::wedo2_menu_TILT_DIRECTION;
+    #[deprecated(note = "use `MicrobitOpCode::microbit_displaySymbol`")]
+    #[allow(non_upper_case_globals)]
+    pub const extension_microbit_display: MicrobitOpCode = MicrobitOpCode::microbit_displaySymbol;
+
+    /// Block that returns a value (including boolean) instead of being in a stack.
+    /// Menus count as reporter too.
+    pub fn is_reporter(&self) -> bool {
+        self.shape().is_reporter()
+    }
+}
 
+opcodes! {
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PenExtensionOpCode {
@@ -203,6 +316,193 @@ pub enum PenExtensionOpCode {
     pen_changePenShadeBy,
     pen_setPenHueToNumber,
     pen_changePenHueBy,
+    pen_menu_colorParam,
+}
+}
+
+opcodes! {
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MusicOpCode {
+    music_playDrumForBeats,
+    music_midiPlayDrumForBeats,
+    music_restForBeats,
+    music_playNoteForBeats,
+    music_setInstrument,
+    music_midiSetInstrument,
+    music_setTempo,
+    music_changeTempo,
+    music_getTempo,
+    music_menu_DRUM,
+    music_menu_INSTRUMENT,
+    /// Note picker (piano keys) shadow block
+    note,
+}
+}
+
+opcodes! {
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text2SpeechOpCode {
+    text2speech_speakAndWait,
+    text2speech_setVoice,
+    text2speech_setLanguage,
+    text2speech_menu_voices,
+    text2speech_menu_languages,
+}
+}
+
+opcodes! {
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranslateOpCode {
+    translate_getTranslate,
+    translate_getViewerLanguage,
+    translate_menu_languages,
+}
+}
+
+opcodes! {
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoSensingOpCode {
+    videoSensing_whenMotionGreaterThan,
+    videoSensing_videoOn,
+    videoSensing_videoToggle,
+    videoSensing_setVideoTransparency,
+    videoSensing_menu_ATTRIBUTE,
+    videoSensing_menu_SUBJECT,
+    videoSensing_menu_VIDEO_STATE,
+}
+}
+
+opcodes! {
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MicrobitOpCode {
+    microbit_whenButtonPressed,
+    microbit_isButtonPressed,
+    microbit_whenGesture,
+    microbit_displaySymbol,
+    microbit_displayText,
+    microbit_displayClear,
+    microbit_whenTilted,
+    microbit_isTilted,
+    microbit_getTiltAngle,
+    microbit_whenPinConnected,
+    microbit_menu_buttons,
+    microbit_menu_gestures,
+    microbit_menu_tiltDirectionAny,
+    microbit_menu_tiltDirection,
+    microbit_menu_touchPins,
+    /// 5x5 LED matrix picker shadow block
+    matrix,
+}
+}
+
+opcodes! {
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wedo2OpCode {
+    wedo2_motorOnFor,
+    wedo2_motorOn,
+    wedo2_motorOff,
+    wedo2_startMotorPower,
+    wedo2_setMotorDirection,
+    wedo2_setLightHue,
+    wedo2_playNoteFor,
+    wedo2_whenDistance,
+    wedo2_whenTilted,
+    wedo2_getDistance,
+    wedo2_isTilted,
+    wedo2_getTiltAngle,
+    wedo2_menu_MOTOR_ID,
+    wedo2_menu_MOTOR_DIRECTION,
+    wedo2_menu_OP,
+    wedo2_menu_TILT_DIRECTION_ANY,
+    wedo2_menu_TILT_DIRECTION,
+}
+}
+
+opcodes! {
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ev3OpCode {
+    ev3_motorTurnClockwise,
+    ev3_motorTurnCounterClockwise,
+    ev3_motorSetPower,
+    ev3_getMotorPosition,
+    ev3_whenButtonPressed,
+    ev3_whenDistanceLessThan,
+    ev3_whenBrightnessLessThan,
+    ev3_buttonPressed,
+    ev3_getDistance,
+    ev3_getBrightness,
+    ev3_beep,
+    ev3_menu_motorPorts,
+    ev3_menu_sensorPorts,
+}
+}
+
+opcodes! {
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MakeyMakeyOpCode {
+    makeymakey_whenMakeyKeyPressed,
+    makeymakey_whenCodePressed,
+    makeymakey_menu_KEY,
+    makeymakey_menu_SEQUENCE,
+}
+}
+
+opcodes! {
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GdxforOpCode {
+    gdxfor_whenGesture,
+    gdxfor_whenForcePushedOrPulled,
+    gdxfor_getForce,
+    gdxfor_whenTilted,
+    gdxfor_isTilted,
+    gdxfor_getTilt,
+    gdxfor_isFreeFalling,
+    gdxfor_getSpinSpeed,
+    gdxfor_getAcceleration,
+    gdxfor_menu_gestureOptions,
+    gdxfor_menu_pushPullOptions,
+    gdxfor_menu_tiltAnyOptions,
+    gdxfor_menu_tiltOptions,
+    gdxfor_menu_axisOptions,
+}
+}
+
+/// Shape of any opcode that is known by this crate
+pub fn shape_of(opcode: &str) -> Option<BlockShape> {
+    schema::get(opcode).map(|schema| schema.shape)
+}
+
+/// Extension ids that Scratch knows how to load
+pub const EXTENSION_IDS: &[&str] = &[
+    "pen",
+    "music",
+    "text2speech",
+    "translate",
+    "videoSensing",
+    "microbit",
+    "wedo2",
+    "ev3",
+    "makeymakey",
+    "gdxfor",
+];
+
+/// Extension that this opcode belongs to.
+/// Opcodes that aren't in the schema are guessed from their prefix, ex. `pen_clear` belongs to `"pen"`.
+pub fn extension_of(opcode: &str) -> Option<&'static str> {
+    if let Some(schema) = schema::get(opcode) {
+        return schema.extension.as_deref();
+    }
+    let (prefix, _) = opcode.split_once('_')?;
+    EXTENSION_IDS.iter().copied().find(|id| *id == prefix)
 }
 
 macro_rules! impl_things {
@@ -214,6 +514,12 @@ macro_rules! impl_things {
                 }
             }
 
+            impl $ty {
+                pub fn shape(&self) -> BlockShape {
+                    shape_of(&self.to_string()).expect("every opcode is in the schema")
+                }
+            }
+
             impl std::fmt::Display for $ty {
                 fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                     write!(f, "{:?}", self)
@@ -223,4 +529,8 @@ macro_rules! impl_things {
     };
 }
 
-impl_things! { StandardOpCode PenExtensionOpCode }
+impl_things! {
+    StandardOpCode PenExtensionOpCode MusicOpCode
+    Text2SpeechOpCode TranslateOpCode VideoSensingOpCode
+    MicrobitOpCode Wedo2OpCode Ev3OpCode MakeyMakeyOpCode GdxforOpCode
+}
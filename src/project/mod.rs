@@ -1,11 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use crate::{
+    monitor::MonitorBuilder,
+    opcode,
     resource::Resource,
     target::{SpriteBuilder, StageBuilder},
-    uid::Uid,
+    uid::{Uid, UidGenerator, UidMode},
+    validate::{self, Diagnostic},
 };
 use sb_sbity::{
+    block::Block,
     monitor::Monitor,
     project::{Meta, Project},
     target::SpriteOrStage,
@@ -16,8 +20,13 @@ use sb_sbity::{
 pub struct ProjectBuilder {
     pub stage_builder:   StageBuilder,
     pub sprite_builders: Vec<SpriteBuilder>,
-    pub monitors:        Vec<Monitor>,
+    pub monitors:        Vec<MonitorBuilder>,
+    /// Broadcasts of the whole project. See [`ProjectBuilder::all_broadcasts`].
+    pub broadcasts:      BTreeSet<String>,
     pub meta:            Meta,
+    pub uid_mode:        UidMode,
+    /// Keep track of every generated uid to guarantee that none of them collide
+    pub unique_uid:      bool,
 }
 
 impl ProjectBuilder {
@@ -30,44 +39,133 @@ impl ProjectBuilder {
         self.sprite_builders.push(sprite_builder);
         self
     }
+
+    pub fn add_monitor(&mut self, monitor_builder: MonitorBuilder) -> &mut Self {
+        self.monitors.push(monitor_builder);
+        self
+    }
+
+    pub fn add_broadcast<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.broadcasts.insert(name.into());
+        self
+    }
+
+    /// Broadcasts added to the project, declared in any target
+    /// and used by any block (ex. `broadcast` or `when I receive`), each name once.
+    /// They're all put on the stage when building, where Scratch expects them.
+    pub fn all_broadcasts(&self) -> BTreeSet<String> {
+        let targets = std::iter::once(&self.stage_builder.target)
+            .chain(self.sprite_builders.iter().map(|sprite| &sprite.target));
+        let mut all_broadcasts = self.broadcasts.clone();
+        for target in targets {
+            all_broadcasts.extend(target.broadcasts.iter().cloned());
+            all_broadcasts.extend(target.used_broadcasts());
+        }
+        all_broadcasts
+    }
+
+    pub fn set_uid_mode(&mut self, uid_mode: UidMode) -> &mut Self {
+        self.uid_mode = uid_mode;
+        self
+    }
+
+    pub fn set_unique_uid(&mut self, unique_uid: bool) -> &mut Self {
+        self.unique_uid = unique_uid;
+        self
+    }
+
+    /// Look for mistakes that would otherwise only show up as a blank project in the Scratch editor.
+    /// See [`validate`] module.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        validate::validate(self)
+    }
 }
 
 impl ProjectBuilder {
+    /// Build with the [`UidGenerator`] made from [`ProjectBuilder::uid_mode`] and [`ProjectBuilder::unique_uid`]
     pub fn build(self, res_buf: &mut Vec<Resource>) -> Project {
+        let mut uid_gen = match self.unique_uid {
+            true => UidGenerator::new_unique(self.uid_mode),
+            false => UidGenerator::new(self.uid_mode),
+        };
+        self.build_with_uid_generator(res_buf, &mut uid_gen)
+    }
+
+    /// Build with your own [`UidGenerator`]. [`ProjectBuilder::uid_mode`] and [`ProjectBuilder::unique_uid`] are ignored.
+    pub fn build_with_uid_generator(
+        self,
+        res_buf: &mut Vec<Resource>,
+        uid_gen: &mut UidGenerator,
+    ) -> Project {
+        let broadcast_names = self.all_broadcasts();
         let ProjectBuilder {
-            stage_builder,
-            sprite_builders,
+            mut stage_builder,
+            mut sprite_builders,
             monitors,
+            broadcasts: _,
             meta,
+            uid_mode: _,
+            unique_uid: _,
         } = self;
 
-        let all_broadcasts: HashMap<String, Uid> = stage_builder
-            .target
-            .broadcasts
+        // Every broadcast goes on the stage only
+        for sprite_builder in &mut sprite_builders {
+            sprite_builder.target.broadcasts.clear();
+        }
+        let all_broadcasts: HashMap<String, Uid> = broadcast_names
             .iter()
-            .chain(sprite_builders.iter().flat_map(|sb| &sb.target.broadcasts))
-            .map(|(name, uid)| (name.clone(), uid.clone()))
-            .collect::<HashMap<_, _>>();
+            .map(|name| (name.clone(), uid_gen.generate()))
+            .collect();
+        stage_builder.target.broadcasts = broadcast_names;
 
         let mut targets = Vec::with_capacity(1 + sprite_builders.len());
-        let (stage, global_varlist_buf) = stage_builder.build(res_buf, &all_broadcasts);
+        let (stage, global_varlist_buf) = stage_builder.build(res_buf, &all_broadcasts, uid_gen);
         targets.push(SpriteOrStage::Stage(stage));
         targets.extend(sprite_builders.into_iter().map(|sprite_builder| {
             SpriteOrStage::Sprite(sprite_builder.build(
                 res_buf,
                 &global_varlist_buf,
                 &all_broadcasts,
+                uid_gen,
             ))
         }));
+        let monitors: Vec<Monitor> = monitors
+            .into_iter()
+            .map(|monitor_builder| monitor_builder.build(&targets, uid_gen))
+            .collect();
+        let extensions = used_extensions(&targets, &monitors)
+            .into_iter()
+            .map(|id| serde_json::value::Value::String(id.to_owned()))
+            .collect();
         Project {
             meta,
-            extensions: serde_json::value::Value::Array(vec![]),
+            extensions: serde_json::value::Value::Array(extensions),
             monitors,
             targets,
         }
     }
 }
 
+/// Every extension that the blocks in `targets` and the `monitors` are from
+fn used_extensions(targets: &[SpriteOrStage], monitors: &[Monitor]) -> BTreeSet<&'static str> {
+    targets
+        .iter()
+        .flat_map(|target| match target {
+            SpriteOrStage::Stage(stage) => stage.target.blocks.0.values(),
+            SpriteOrStage::Sprite(sprite) => sprite.target.blocks.0.values(),
+        })
+        .filter_map(|block| match block {
+            Block::Normal(block) => opcode::extension_of(&block.opcode),
+            Block::VarList(_) => None,
+        })
+        .chain(
+            monitors
+                .iter()
+                .filter_map(|monitor| opcode::extension_of(&monitor.opcode)),
+        )
+        .collect()
+}
+
 impl Default for ProjectBuilder {
     #[rustfmt::skip]
     fn default() -> Self {
@@ -75,11 +173,27 @@ impl Default for ProjectBuilder {
             stage_builder:   StageBuilder::default(),
             sprite_builders: Vec::default(),
             monitors:        Vec::default(),
+            broadcasts:      BTreeSet::default(),
             meta: Meta {
                 semver: "3.0.0".to_owned(),
                 vm:     "0.2.0-prerelease.20220222132735".to_owned(),
                 agent:  "sb-itchy/0.1.0".to_owned(),
             },
+            uid_mode:        UidMode::default(),
+            unique_uid:      true,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monitors_add_their_extension() {
+        let mut project = ProjectBuilder::default();
+        project.add_monitor(MonitorBuilder::reporter("music_getTempo"));
+        let project = project.build(&mut vec![]);
+        assert_eq!(project.extensions, serde_json::json!(["music"]));
+    }
+}
@@ -1,5 +1,5 @@
 use std::fs::File as FsFile;
-use std::io::{Error as IoError, Read};
+use std::io::{BufReader, Error as IoError, Read};
 use std::path::{Path, PathBuf};
 
 #[derive(Debug)]
@@ -26,11 +26,16 @@ impl std::fmt::Display for ResourceError {
 
 /// Preloads the file
 /// This might cost some additional memmory but will make the building part uses no result.
+///
+/// Large files can be streamed from the disk instead with [`Resource::open`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Resource {
     pub extension: String,
+    /// Empty when the resource is streamed from a file
     pub content: Vec<u8>,
     md5_hash: Option<String>,
+    /// File that the content is read from when it's needed
+    path: Option<PathBuf>,
 }
 
 impl Resource {
@@ -43,6 +48,7 @@ impl Resource {
             extension,
             content,
             md5_hash: None,
+            path: None,
         })
     }
 
@@ -59,16 +65,58 @@ impl Resource {
             extension: extension.to_string(),
             content: buf,
             md5_hash: None,
+            path: None,
         };
         Ok(file)
     }
 
+    /// Doesn't load the file, it's read from the disk every time the content is needed.
+    /// The md5 hash is computed here while reading through the file once.
+    ///
+    /// The file shouldn't change until the project is exported.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Resource, ResourceError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .ok_or(ResourceError::InvalidFileExtension)?
+            .to_string_lossy()
+            .to_string();
+        let mut file = BufReader::new(FsFile::options().read(true).open(path)?);
+        let mut context = md5::Context::new();
+        let mut buf = [0; 8192];
+        loop {
+            let read = file.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            context.consume(&buf[..read]);
+        }
+        Ok(Resource {
+            extension,
+            content: vec![],
+            md5_hash: Some(hex(&context.compute().0)),
+            path: Some(path.to_owned()),
+        })
+    }
+
+    /// True when made by [`Resource::open`]
+    pub fn is_streamed(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Read the content, from the file when it's streamed
+    pub fn reader(&self) -> Result<Box<dyn Read + '_>, IoError> {
+        match &self.path {
+            Some(path) => Ok(Box::new(BufReader::new(
+                FsFile::options().read(true).open(path)?,
+            ))),
+            None => Ok(Box::new(self.content.as_slice())),
+        }
+    }
+
     /// PathBuf is always valid utf8
     pub fn generate_file_name(&mut self) -> PathBuf {
-        let mut path = PathBuf::from(self.md5_hash().unwrap_or({
-            self.compute_md5_hash();
-            self.md5_hash().unwrap()
-        }));
+        let mut path = PathBuf::from(self.get_or_compute_md5_hash());
         path.set_extension(&self.extension);
         path
     }
@@ -77,14 +125,25 @@ impl Resource {
         self.md5_hash.as_ref()
     }
 
+    /// Streamed resource already has its hash so it's kept
     pub fn compute_md5_hash(&mut self) {
-        self.md5_hash = Some(
-            md5::compute(&self.content)
-                .0
-                .iter()
-                .map(|byte| format!("{byte:02x}"))
-                .collect(),
-        )
+        if self.is_streamed() {
+            return;
+        }
+        self.md5_hash = Some(hex(&md5::compute(&self.content).0))
+    }
+
+    pub fn get_or_compute_md5_hash(&mut self) -> &str {
+        if self.md5_hash.is_none() {
+            self.compute_md5_hash();
+        }
+        self.md5_hash.as_ref().unwrap()
+    }
+
+    /// `"<md5 hash>.<extension>"` which is how assets refer to it
+    pub fn md5ext(&mut self) -> String {
+        let md5_hash = self.get_or_compute_md5_hash().to_owned();
+        format!("{md5_hash}.{}", self.extension)
     }
 
     pub fn extension(&self) -> &str {
@@ -100,11 +159,19 @@ impl Resource {
         true
     }
 
+    /// Empty when the resource is streamed, use [`Resource::reader`] for that.
     pub fn content(&self) -> &[u8] {
         &self.content
     }
 
+    /// The resource isn't streamed anymore after this
     pub fn set_content(&mut self, content: Vec<u8>) {
-        self.content = content
+        self.content = content;
+        self.path = None;
+        self.md5_hash = None;
     }
 }
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,327 @@
+//! Schema of every opcode known by this crate
+//!
+//! It's loaded from `schema.json` that is bundled with the crate.
+//! Each opcode has its shape, the extension it's from, the inputs it can have
+//! with the kind of shadow Scratch's editor would put there, and the fields it must have
+//! with the values the menu allows.
+//! ```json
+//! "looks_seteffectto": {"shape": "stack", "inputs": {"VALUE": "number"}, "fields": {"EFFECT": ["COLOR", ...]}}
+//! ```
+//! Helpers in [`crate::blocks`] are checked against it in debug build and
+//! [`crate::validate`] checks every block in the project against it.
+
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use sb_sbity::block::BlockInputValue;
+use serde_json::Value as Json;
+
+use crate::{
+    block::{BlockBuilder, BlockNormalBuilder, StackOrValue},
+    opcode::BlockShape,
+};
+
+lazy_static! {
+    static ref SCHEMA: HashMap<String, OpCodeSchema> = parse(include_str!("schema.json"));
+}
+
+/// What an input expects to be in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputKind {
+    Number,
+    PositiveNumber,
+    WholeNumber,
+    Integer,
+    Angle,
+    Color,
+    Text,
+    Broadcast,
+    /// Hexagon slot, it has no shadow
+    Boolean,
+    /// Blocks inside a C block
+    Substack,
+    /// Shadow block of this opcode that has a field to pick from, ex. `looks_costume`
+    Menu(String),
+}
+
+impl std::fmt::Display for InputKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputKind::Number => write!(f, "a number"),
+            InputKind::PositiveNumber => write!(f, "a positive number"),
+            InputKind::WholeNumber => write!(f, "a whole number"),
+            InputKind::Integer => write!(f, "an integer"),
+            InputKind::Angle => write!(f, "an angle"),
+            InputKind::Color => write!(f, "a color"),
+            InputKind::Text => write!(f, "a text"),
+            InputKind::Broadcast => write!(f, "a broadcast"),
+            InputKind::Boolean => write!(f, "a boolean"),
+            InputKind::Substack => write!(f, "a substack"),
+            InputKind::Menu(opcode) => write!(f, "a `{opcode}` menu"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpCodeSchema {
+    pub shape: BlockShape,
+    /// None when it's not from an extension
+    pub extension: Option<String>,
+    /// None when the inputs aren't fixed (custom blocks)
+    pub inputs: Option<HashMap<String, InputKind>>,
+    /// Allowed values of each field. None when it's any name, ex. variable or sprite name.
+    pub fields: HashMap<String, Option<Vec<String>>>,
+}
+
+/// Schema of the opcode, None if the opcode isn't known by this crate
+pub fn get(opcode: &str) -> Option<&'static OpCodeSchema> {
+    SCHEMA.get(opcode)
+}
+
+/// Something about a block that doesn't match the schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaViolation {
+    UnknownOpCode,
+    UnknownInput(String),
+    InputKindMismatch { input: String, expected: InputKind },
+    MissingField(String),
+    UnknownField(String),
+    InvalidFieldValue { field: String, value: String },
+}
+
+impl SchemaViolation {
+    /// Wrong opcode or key, which is always a mistake in the code rather than in the values
+    pub fn is_key_mismatch(&self) -> bool {
+        matches!(
+            self,
+            SchemaViolation::UnknownOpCode
+                | SchemaViolation::UnknownInput(_)
+                | SchemaViolation::MissingField(_)
+                | SchemaViolation::UnknownField(_)
+        )
+    }
+}
+
+impl std::error::Error for SchemaViolation {}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaViolation::UnknownOpCode => write!(f, "is an unknown opcode"),
+            SchemaViolation::UnknownInput(input) => write!(f, "doesn't have input `{input}`"),
+            SchemaViolation::InputKindMismatch { input, expected } => {
+                write!(f, "expects {expected} in input `{input}`")
+            }
+            SchemaViolation::MissingField(field) => write!(f, "is missing field `{field}`"),
+            SchemaViolation::UnknownField(field) => write!(f, "doesn't have field `{field}`"),
+            SchemaViolation::InvalidFieldValue { field, value } => {
+                write!(f, "doesn't allow `{value}` in field `{field}`")
+            }
+        }
+    }
+}
+
+/// Check the block itself against the schema, blocks in its inputs aren't checked.
+pub fn check_block(block: &BlockNormalBuilder) -> Vec<SchemaViolation> {
+    let Some(schema) = get(block.opcode()) else {
+        return vec![SchemaViolation::UnknownOpCode];
+    };
+    let mut violations = vec![];
+    if let Some(input_kinds) = &schema.inputs {
+        for (key, input) in block.inputs() {
+            let Some(expected) = input_kinds.get(key) else {
+                violations.push(SchemaViolation::UnknownInput(key.clone()));
+                continue;
+            };
+            if !input
+                .values
+                .iter()
+                .flatten()
+                .all(|value| is_kind_of(value, expected))
+            {
+                violations.push(SchemaViolation::InputKindMismatch {
+                    input: key.clone(),
+                    expected: expected.clone(),
+                });
+            }
+        }
+    }
+    for (key, allowed) in &schema.fields {
+        match block.fields().get(key) {
+            None => violations.push(SchemaViolation::MissingField(key.clone())),
+            Some(field) => {
+                if let Some(allowed) = allowed {
+                    if !allowed.contains(&field.value) {
+                        violations.push(SchemaViolation::InvalidFieldValue {
+                            field: key.clone(),
+                            value: field.value.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    for key in block.fields().keys() {
+        if !schema.fields.contains_key(key) {
+            violations.push(SchemaViolation::UnknownField(key.clone()));
+        }
+    }
+    violations
+}
+
+/// Reporters fit anywhere except that only boolean reporters fit in boolean slot.
+/// Shadows are only checked when the schema knows what they should be.
+fn is_kind_of(value: &StackOrValue, expected: &InputKind) -> bool {
+    match value {
+        StackOrValue::Value(BlockInputValue::Variable { .. } | BlockInputValue::List { .. }) => {
+            !matches!(expected, InputKind::Boolean | InputKind::Substack)
+        }
+        StackOrValue::Value(value) => {
+            let kind = match value {
+                BlockInputValue::Number { .. } => InputKind::Number,
+                BlockInputValue::PositiveNumber { .. } => InputKind::PositiveNumber,
+                BlockInputValue::PositiveInteger { .. } => InputKind::WholeNumber,
+                BlockInputValue::Integer { .. } => InputKind::Integer,
+                BlockInputValue::Angle { .. } => InputKind::Angle,
+                BlockInputValue::Color { .. } => InputKind::Color,
+                BlockInputValue::String { .. } => InputKind::Text,
+                BlockInputValue::Broadcast { .. } => InputKind::Broadcast,
+                BlockInputValue::Variable { .. } | BlockInputValue::List { .. } => unreachable!(),
+            };
+            kind == *expected
+        }
+        StackOrValue::Stack(stack) => {
+            let Some(BlockBuilder::Normal(first)) = stack.stack.first() else {
+                return !matches!(expected, InputKind::Boolean | InputKind::Substack);
+            };
+            let shape = get(first.opcode()).map(|schema| schema.shape);
+            match expected {
+                InputKind::Menu(menu) if first.is_shadow() => first.opcode() == menu,
+                InputKind::Boolean => shape.is_none_or(|shape| shape == BlockShape::Boolean),
+                InputKind::Substack => shape.is_none_or(|shape| !shape.is_reporter()),
+                _ => shape.is_none_or(|shape| shape.is_reporter()),
+            }
+        }
+    }
+}
+
+fn parse(json: &str) -> HashMap<String, OpCodeSchema> {
+    let json: HashMap<String, Json> = serde_json::from_str(json).expect("schema.json is valid");
+    json.into_iter()
+        .map(|(opcode, schema)| {
+            let schema = OpCodeSchema {
+                shape: parse_shape(schema["shape"].as_str().unwrap()),
+                extension: schema["extension"].as_str().map(str::to_owned),
+                inputs: match schema.get("inputs") {
+                    Some(Json::Null) => None,
+                    Some(inputs) => Some(
+                        inputs
+                            .as_object()
+                            .unwrap()
+                            .iter()
+                            .map(|(key, kind)| (key.clone(), parse_input_kind(kind)))
+                            .collect(),
+                    ),
+                    None => Some(HashMap::new()),
+                },
+                fields: match schema.get("fields") {
+                    Some(fields) => fields
+                        .as_object()
+                        .unwrap()
+                        .iter()
+                        .map(|(key, allowed)| {
+                            let allowed = allowed.as_array().map(|allowed| {
+                                allowed
+                                    .iter()
+                                    .map(|value| value.as_str().unwrap().to_owned())
+                                    .collect()
+                            });
+                            (key.clone(), allowed)
+                        })
+                        .collect(),
+                    None => HashMap::new(),
+                },
+            };
+            (opcode, schema)
+        })
+        .collect()
+}
+
+fn parse_shape(shape: &str) -> BlockShape {
+    match shape {
+        "hat" => BlockShape::Hat,
+        "stack" => BlockShape::Stack,
+        "cap" => BlockShape::Cap,
+        "c" => BlockShape::C,
+        "c_cap" => BlockShape::CCap,
+        "boolean" => BlockShape::Boolean,
+        "reporter" => BlockShape::Reporter,
+        other => panic!("unknown shape `{other}` in schema.json"),
+    }
+}
+
+fn parse_input_kind(kind: &Json) -> InputKind {
+    if let Some(menu) = kind["menu"].as_str() {
+        return InputKind::Menu(menu.to_owned());
+    }
+    match kind.as_str().unwrap() {
+        "number" => InputKind::Number,
+        "positive_number" => InputKind::PositiveNumber,
+        "whole_number" => InputKind::WholeNumber,
+        "integer" => InputKind::Integer,
+        "angle" => InputKind::Angle,
+        "color" => InputKind::Color,
+        "text" => InputKind::Text,
+        "broadcast" => InputKind::Broadcast,
+        "boolean" => InputKind::Boolean,
+        "substack" => InputKind::Substack,
+        other => panic!("unknown input kind `{other}` in schema.json"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::{
+        Ev3OpCode, GdxforOpCode, MakeyMakeyOpCode, MicrobitOpCode, MusicOpCode, PenExtensionOpCode,
+        StandardOpCode, Text2SpeechOpCode, TranslateOpCode, VideoSensingOpCode, Wedo2OpCode,
+    };
+
+    fn names<T: ToString>(all: &[T]) -> Vec<String> {
+        all.iter().map(T::to_string).collect()
+    }
+
+    #[test]
+    fn opcode_enums_match_schema() {
+        let enums = [
+            (None, names(StandardOpCode::ALL)),
+            (Some("pen"), names(PenExtensionOpCode::ALL)),
+            (Some("music"), names(MusicOpCode::ALL)),
+            (Some("text2speech"), names(Text2SpeechOpCode::ALL)),
+            (Some("translate"), names(TranslateOpCode::ALL)),
+            (Some("videoSensing"), names(VideoSensingOpCode::ALL)),
+            (Some("microbit"), names(MicrobitOpCode::ALL)),
+            (Some("wedo2"), names(Wedo2OpCode::ALL)),
+            (Some("ev3"), names(Ev3OpCode::ALL)),
+            (Some("makeymakey"), names(MakeyMakeyOpCode::ALL)),
+            (Some("gdxfor"), names(GdxforOpCode::ALL)),
+        ];
+        for (extension, opcodes) in &enums {
+            for opcode in opcodes {
+                let schema =
+                    get(opcode).unwrap_or_else(|| panic!("`{opcode}` isn't in the schema"));
+                assert_eq!(schema.extension.as_deref(), *extension, "{opcode}");
+            }
+        }
+        for (opcode, schema) in SCHEMA.iter() {
+            let (_, opcodes) = enums
+                .iter()
+                .find(|(extension, _)| *extension == schema.extension.as_deref())
+                .unwrap_or_else(|| panic!("`{opcode}` is from an unknown extension"));
+            assert!(
+                opcodes.contains(opcode),
+                "`{opcode}` isn't in its opcode enum"
+            );
+        }
+    }
+}
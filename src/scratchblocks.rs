@@ -0,0 +1,1649 @@
+//! Compile scripts written in [scratchblocks](https://en.scratch-wiki.info/wiki/Block_Plugin/Syntax),
+//! the text syntax used on the Scratch wiki and forums.
+//! ```ignore
+//! scratchblocks::compile(
+//!     r#"
+//! when flag clicked
+//! set [score v] to (0)
+//! forever
+//!     if <touching (mouse-pointer v)?> then
+//!         change [score v] by (1)
+//!         broadcast [scored v]
+//!     end
+//! end
+//! "#,
+//!     &mut sprite.target,
+//!     Some(&stage.target),
+//! )?;
+//! ```
+//! Scripts are separated by a blank line and C blocks are closed with `end`.
+//! Variables and lists are looked up by name in the sprite and then in the stage.
+//! Broadcasts that don't exist yet are added to the target.
+//! Custom blocks are the ones with a `define` script in the same source, they can be used
+//! before they're defined.
+//!
+//! Comments (`// ...`) and category annotations (`:: pen`) are ignored, except `(name :: list)`
+//! which is always the list, ex. when a variable has the same name.
+//!
+//! [`decompile`] goes the other way and renders scripts back to text, ex. for reviewing or
+//! diffing generated projects. Comments on blocks are put at the end of their line.
+
+use std::collections::{BTreeSet, HashMap};
+
+use lazy_static::lazy_static;
+use sb_sbity::{
+    block::{BlockInputValue, BlockMutation, BlockMutationEnum, ListOrVariable, ShadowInputType},
+    project::Project,
+    target::{SpriteOrStage, Target},
+    value::Value,
+};
+
+use crate::{
+    block::{
+        BlockBuilder, BlockFieldBuilder, BlockInputBuilder, BlockNormalBuilder,
+        BlockVarListBuilder, FieldKind, StackOrValue, VarListFrom,
+    },
+    block_definer::{ArgumentKind, ProcedureBuilder, ProcedureError},
+    build_context::TargetContext,
+    comment::CommentBuilder,
+    import::varlist_context,
+    input,
+    opcode::{self, BlockShape},
+    project::ProjectBuilder,
+    schema::{self, InputKind},
+    stack::{StackBuilder, StackError},
+    target::TargetBuilder,
+    value::value_to_string,
+};
+
+type Bfb = BlockFieldBuilder;
+type Bib = BlockInputBuilder;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScratchblocksError {
+    /// Starts from 1
+    pub line: usize,
+    pub kind: ScratchblocksErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScratchblocksErrorKind {
+    /// Bracket that is never closed or that closes nothing
+    Bracket(char),
+    /// Not a block, a custom block, a variable, a list or an argument
+    UnknownBlock(String),
+    UnknownVariable(String),
+    UnknownList(String),
+    /// `else` or `end` that has no C block to go with
+    UnexpectedKeyword(String),
+    /// Custom block with the same label as one that's already defined
+    DuplicateDefine(String),
+    /// Something other than `[thing v]` in a dropdown
+    ExpectedDropdown(String),
+    /// Something other than `<...>` in a boolean slot
+    ExpectedBoolean(String),
+    Procedure(ProcedureError),
+    Stack(StackError),
+}
+
+impl std::error::Error for ScratchblocksError {}
+
+impl std::fmt::Display for ScratchblocksError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl std::fmt::Display for ScratchblocksErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScratchblocksErrorKind::Bracket(c) => write!(f, "unbalanced `{c}`"),
+            ScratchblocksErrorKind::UnknownBlock(block) => {
+                write!(f, "`{block}` isn't a block, variable, list or argument")
+            }
+            ScratchblocksErrorKind::UnknownVariable(name) => write!(f, "unknown variable `{name}`"),
+            ScratchblocksErrorKind::UnknownList(name) => write!(f, "unknown list `{name}`"),
+            ScratchblocksErrorKind::UnexpectedKeyword(keyword) => {
+                write!(f, "`{keyword}` without a C block")
+            }
+            ScratchblocksErrorKind::DuplicateDefine(label) => {
+                write!(f, "custom block `{label}` is already defined")
+            }
+            ScratchblocksErrorKind::ExpectedDropdown(found) => {
+                write!(f, "expected a dropdown but found `{found}`")
+            }
+            ScratchblocksErrorKind::ExpectedBoolean(found) => {
+                write!(f, "expected a boolean but found `{found}`")
+            }
+            ScratchblocksErrorKind::Procedure(e) => write!(f, "{e}"),
+            ScratchblocksErrorKind::Stack(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<ProcedureError> for ScratchblocksErrorKind {
+    fn from(value: ProcedureError) -> Self {
+        ScratchblocksErrorKind::Procedure(value)
+    }
+}
+
+impl From<StackError> for ScratchblocksErrorKind {
+    fn from(value: StackError) -> Self {
+        ScratchblocksErrorKind::Stack(value)
+    }
+}
+
+type Kind = ScratchblocksErrorKind;
+
+/// Compile `source` and add its scripts to `target`.
+///
+/// `stage` is where global variables, lists and broadcasts are, None when `target` is the stage.
+/// Nothing is added to `target` when there's an error.
+pub fn compile(
+    source: &str,
+    target: &mut TargetBuilder,
+    stage: Option<&TargetBuilder>,
+) -> Result<(), ScratchblocksError> {
+    let (scripts, broadcasts) = {
+        let mut compiler = Compiler {
+            target,
+            stage,
+            procedures: HashMap::new(),
+            procedure: None,
+            broadcasts: BTreeSet::new(),
+        };
+        let scripts = compiler.scripts(source)?;
+        (scripts, compiler.broadcasts)
+    };
+    for broadcast in broadcasts {
+        target.add_broadcast(broadcast);
+    }
+    target.add_block_stacks(scripts);
+    Ok(())
+}
+
+/// Label of each block where `{KEY}` is its input or field.
+/// Blocks with the same label are told apart by what's in their fields so the pickier goes first.
+#[rustfmt::skip]
+const BLOCKS: &[(&str, &str)] = &[
+    ("move {STEPS} steps",                         "motion_movesteps"),
+    ("turn right {DEGREES} degrees",               "motion_turnright"),
+    ("turn cw {DEGREES} degrees",                  "motion_turnright"),
+    ("turn ↻ {DEGREES} degrees",                   "motion_turnright"),
+    ("turn @turnright {DEGREES} degrees",          "motion_turnright"),
+    ("turn left {DEGREES} degrees",                "motion_turnleft"),
+    ("turn ccw {DEGREES} degrees",                 "motion_turnleft"),
+    ("turn ↺ {DEGREES} degrees",                   "motion_turnleft"),
+    ("turn @turnleft {DEGREES} degrees",           "motion_turnleft"),
+    ("go to {TO}",                                 "motion_goto"),
+    ("go to x: {X} y: {Y}",                        "motion_gotoxy"),
+    ("glide {SECS} secs to {TO}",                  "motion_glideto"),
+    ("glide {SECS} secs to x: {X} y: {Y}",         "motion_glidesecstoxy"),
+    ("point in direction {DIRECTION}",             "motion_pointindirection"),
+    ("point towards {TOWARDS}",                    "motion_pointtowards"),
+    ("change x by {DX}",                           "motion_changexby"),
+    ("set x to {X}",                               "motion_setx"),
+    ("change y by {DY}",                           "motion_changeyby"),
+    ("set y to {Y}",                               "motion_sety"),
+    ("if on edge, bounce",                         "motion_ifonedgebounce"),
+    ("set rotation style {STYLE}",                 "motion_setrotationstyle"),
+    ("x position",                                 "motion_xposition"),
+    ("y position",                                 "motion_yposition"),
+    ("direction",                                  "motion_direction"),
+
+    ("say {MESSAGE} for {SECS} seconds",           "looks_sayforsecs"),
+    ("say {MESSAGE}",                              "looks_say"),
+    ("think {MESSAGE} for {SECS} seconds",         "looks_thinkforsecs"),
+    ("think {MESSAGE}",                            "looks_think"),
+    ("switch costume to {COSTUME}",                "looks_switchcostumeto"),
+    ("next costume",                               "looks_nextcostume"),
+    ("switch backdrop to {BACKDROP}",              "looks_switchbackdropto"),
+    ("switch backdrop to {BACKDROP} and wait",     "looks_switchbackdroptoandwait"),
+    ("next backdrop",                              "looks_nextbackdrop"),
+    ("change size by {CHANGE}",                    "looks_changesizeby"),
+    ("set size to {SIZE} %",                       "looks_setsizeto"),
+    ("change {EFFECT} effect by {CHANGE}",         "looks_changeeffectby"),
+    ("set {EFFECT} effect to {VALUE}",             "looks_seteffectto"),
+    ("clear graphic effects",                      "looks_cleargraphiceffects"),
+    ("show",                                       "looks_show"),
+    ("hide",                                       "looks_hide"),
+    ("go to {FRONT_BACK} layer",                   "looks_gotofrontback"),
+    ("go {FORWARD_BACKWARD} {NUM} layers",         "looks_goforwardbackwardlayers"),
+    ("costume {NUMBER_NAME}",                      "looks_costumenumbername"),
+    ("backdrop {NUMBER_NAME}",                     "looks_backdropnumbername"),
+    ("size",                                       "looks_size"),
+
+    ("play sound {SOUND_MENU} until done",         "sound_playuntildone"),
+    ("start sound {SOUND_MENU}",                   "sound_play"),
+    ("stop all sounds",                            "sound_stopallsounds"),
+    ("change {EFFECT} effect by {VALUE}",          "sound_changeeffectby"),
+    ("set {EFFECT} effect to {VALUE}",             "sound_seteffectto"),
+    ("clear sound effects",                        "sound_cleareffects"),
+    ("change volume by {VOLUME}",                  "sound_changevolumeby"),
+    ("set volume to {VOLUME} %",                   "sound_setvolumeto"),
+    ("volume",                                     "sound_volume"),
+
+    ("when flag clicked",                          "event_whenflagclicked"),
+    ("when green flag clicked",                    "event_whenflagclicked"),
+    ("when @greenflag clicked",                    "event_whenflagclicked"),
+    ("when {KEY_OPTION} key pressed",              "event_whenkeypressed"),
+    ("when this sprite clicked",                   "event_whenthisspriteclicked"),
+    ("when stage clicked",                         "event_whenstageclicked"),
+    ("when backdrop switches to {BACKDROP}",       "event_whenbackdropswitchesto"),
+    ("when {WHENGREATERTHANMENU} > {VALUE}",       "event_whengreaterthan"),
+    ("when i receive {BROADCAST_OPTION}",          "event_whenbroadcastreceived"),
+    ("broadcast {BROADCAST_INPUT}",                "event_broadcast"),
+    ("broadcast {BROADCAST_INPUT} and wait",       "event_broadcastandwait"),
+
+    ("wait {DURATION} seconds",                    "control_wait"),
+    ("repeat {TIMES}",                             "control_repeat"),
+    ("forever",                                    "control_forever"),
+    ("if {CONDITION} then",                        "control_if"),
+    ("wait until {CONDITION}",                     "control_wait_until"),
+    ("repeat until {CONDITION}",                   "control_repeat_until"),
+    ("stop {STOP_OPTION}",                         "control_stop"),
+    ("when i start as a clone",                    "control_start_as_clone"),
+    ("create clone of {CLONE_OPTION}",             "control_create_clone_of"),
+    ("delete this clone",                          "control_delete_this_clone"),
+
+    ("touching {TOUCHINGOBJECTMENU}",              "sensing_touchingobject"),
+    ("touching color {COLOR}",                     "sensing_touchingcolor"),
+    ("color {COLOR} is touching {COLOR2}",         "sensing_coloristouchingcolor"),
+    ("distance to {DISTANCETOMENU}",               "sensing_distanceto"),
+    ("ask {QUESTION} and wait",                    "sensing_askandwait"),
+    ("answer",                                     "sensing_answer"),
+    ("key {KEY_OPTION} pressed",                   "sensing_keypressed"),
+    ("mouse down",                                 "sensing_mousedown"),
+    ("mouse x",                                    "sensing_mousex"),
+    ("mouse y",                                    "sensing_mousey"),
+    ("set drag mode {DRAG_MODE}",                  "sensing_setdragmode"),
+    ("loudness",                                   "sensing_loudness"),
+    ("timer",                                      "sensing_timer"),
+    ("reset timer",                                "sensing_resettimer"),
+    ("current {CURRENTMENU}",                      "sensing_current"),
+    ("days since 2000",                            "sensing_dayssince2000"),
+    ("username",                                   "sensing_username"),
+
+    ("{NUM1} + {NUM2}",                            "operator_add"),
+    ("{NUM1} - {NUM2}",                            "operator_subtract"),
+    ("{NUM1} * {NUM2}",                            "operator_multiply"),
+    ("{NUM1} / {NUM2}",                            "operator_divide"),
+    ("pick random {FROM} to {TO}",                 "operator_random"),
+    ("{OPERAND1} > {OPERAND2}",                    "operator_gt"),
+    ("{OPERAND1} < {OPERAND2}",                    "operator_lt"),
+    ("{OPERAND1} = {OPERAND2}",                    "operator_equals"),
+    ("{OPERAND1} and {OPERAND2}",                  "operator_and"),
+    ("{OPERAND1} or {OPERAND2}",                   "operator_or"),
+    ("not {OPERAND}",                              "operator_not"),
+    ("join {STRING1} {STRING2}",                   "operator_join"),
+    ("letter {LETTER} of {STRING}",                "operator_letter_of"),
+    ("{NUM1} mod {NUM2}",                          "operator_mod"),
+    ("round {NUM}",                                "operator_round"),
+    ("{OPERATOR} of {NUM}",                        "operator_mathop"),
+    ("{PROPERTY} of {OBJECT}",                     "sensing_of"),
+
+    ("set {VARIABLE} to {VALUE}",                  "data_setvariableto"),
+    ("change {VARIABLE} by {VALUE}",               "data_changevariableby"),
+    ("show variable {VARIABLE}",                   "data_showvariable"),
+    ("hide variable {VARIABLE}",                   "data_hidevariable"),
+    ("add {ITEM} to {LIST}",                       "data_addtolist"),
+    ("delete {INDEX} of {LIST}",                   "data_deleteoflist"),
+    ("delete all of {LIST}",                       "data_deletealloflist"),
+    ("insert {ITEM} at {INDEX} of {LIST}",         "data_insertatlist"),
+    ("replace item {INDEX} of {LIST} with {ITEM}", "data_replaceitemoflist"),
+    ("item {INDEX} of {LIST}",                     "data_itemoflist"),
+    ("item # of {ITEM} in {LIST}",                 "data_itemnumoflist"),
+    ("length of {LIST}",                           "data_lengthoflist"),
+    ("length of {STRING}",                         "operator_length"),
+    ("{LIST} contains {ITEM}",                     "data_listcontainsitem"),
+    ("{STRING1} contains {STRING2}",               "operator_contains"),
+    ("show list {LIST}",                           "data_showlist"),
+    ("hide list {LIST}",                           "data_hidelist"),
+
+    ("erase all",                                  "pen_clear"),
+    ("stamp",                                      "pen_stamp"),
+    ("pen down",                                   "pen_penDown"),
+    ("pen up",                                     "pen_penUp"),
+    ("set pen color to {COLOR}",                   "pen_setPenColorToColor"),
+    ("change pen {COLOR_PARAM} by {VALUE}",        "pen_changePenColorParamBy"),
+    ("set pen {COLOR_PARAM} to {VALUE}",           "pen_setPenColorParamTo"),
+    ("change pen size by {SIZE}",                  "pen_changePenSizeBy"),
+    ("set pen size to {SIZE}",                     "pen_setPenSizeTo"),
+];
+
+/// Menus that have these special values in place of a sprite name
+#[rustfmt::skip]
+const SPECIAL_MENU_VALUES: &[(&str, &str)] = &[
+    ("mouse-pointer",   "_mouse_"),
+    ("random position", "_random_"),
+    ("edge",            "_edge_"),
+    ("myself",          "_myself_"),
+    ("stage",           "_stage_"),
+];
+const SPRITE_MENUS: &[&str] = &[
+    "TO",
+    "TOWARDS",
+    "TOUCHINGOBJECTMENU",
+    "DISTANCETOMENU",
+    "CLONE_OPTION",
+    "OBJECT",
+];
+
+lazy_static! {
+    /// Label with `_` in place of the inputs and fields -> opcode and keys of the inputs and fields in order
+    static ref TEMPLATES: HashMap<String, Vec<(&'static str, Vec<&'static str>)>> = {
+        let mut templates: HashMap<String, Vec<_>> = HashMap::new();
+        for (template, opcode) in BLOCKS {
+            let mut words = vec![];
+            let mut keys = vec![];
+            for word in template.split(' ') {
+                match word.strip_prefix('{').and_then(|key| key.strip_suffix('}')) {
+                    Some(key) => {
+                        words.push("_");
+                        keys.push(key);
+                    }
+                    None => words.push(word),
+                }
+            }
+            templates
+                .entry(words.join(" "))
+                .or_default()
+                .push((*opcode, keys));
+        }
+        templates
+    };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Word(String),
+    Arg(Arg),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Arg {
+    /// `(10)`
+    Number(String),
+    /// `[hello]`
+    Text(String),
+    /// `[#ff0000]`
+    Color(String),
+    /// `[thing v]` or `(thing v)`
+    Dropdown(String),
+    /// `(x position)`
+    Reporter(Vec<Part>),
+    /// `<mouse down?>`, it's empty for `<>`
+    Boolean(Vec<Part>),
+    /// `(things :: list)`
+    List(String),
+}
+
+impl std::fmt::Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::Word(word) => write!(f, "{word}"),
+            Part::Arg(Arg::Number(n)) => write!(f, "({n})"),
+            Part::Arg(Arg::Text(text) | Arg::Color(text)) => write!(f, "[{text}]"),
+            Part::Arg(Arg::Dropdown(value)) => write!(f, "[{value} v]"),
+            Part::Arg(Arg::Reporter(parts)) => write!(f, "({})", show(parts)),
+            Part::Arg(Arg::Boolean(parts)) => write!(f, "<{}>", show(parts)),
+            Part::Arg(Arg::List(name)) => write!(f, "({name} :: list)"),
+        }
+    }
+}
+
+fn show(parts: &[Part]) -> String {
+    parts
+        .iter()
+        .map(Part::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Lowercase label with `_` in place of the args, `?` is left out.
+/// Ex. `<touching (mouse-pointer v)?>` is `touching _`
+fn label(parts: &[Part]) -> String {
+    parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::Word(word) => {
+                let word = word.replace('?', "").to_lowercase();
+                (!word.is_empty()).then_some(word)
+            }
+            Part::Arg(_) => Some("_".to_owned()),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn args(parts: &[Part]) -> Vec<&Arg> {
+    parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::Arg(arg) => Some(arg),
+            Part::Word(_) => None,
+        })
+        .collect()
+}
+
+/// The words joined by space, None when there's an arg
+fn words(parts: &[Part]) -> Option<String> {
+    let words = parts
+        .iter()
+        .map(|part| match part {
+            Part::Word(word) => Some(word.as_str()),
+            Part::Arg(_) => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+fn is_color(text: &str) -> bool {
+    text.len() == 7 && text.starts_with('#') && text[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Lexer {
+    fn parse(line: &str) -> Result<Vec<Part>, Kind> {
+        let mut lexer = Lexer {
+            chars: line.chars().collect(),
+            pos: 0,
+        };
+        Ok(lexer.parts(None)?.0)
+    }
+
+    fn peek_at(&self, offset: isize) -> Option<char> {
+        let pos = self.pos.checked_add_signed(offset)?;
+        self.chars.get(pos).copied()
+    }
+
+    /// `<` and `>` with space on both sides are the operators, not brackets
+    fn is_operator(&self) -> bool {
+        self.peek_at(-1).is_some_and(char::is_whitespace)
+            && self.peek_at(1).is_some_and(char::is_whitespace)
+    }
+
+    /// Parts until `close`, and the words of the `:: category` annotation
+    fn parts(&mut self, close: Option<char>) -> Result<(Vec<Part>, String), Kind> {
+        let mut parts = vec![];
+        let mut annotation = None;
+        loop {
+            while self.peek_at(0).is_some_and(char::is_whitespace) {
+                self.pos += 1;
+            }
+            let Some(c) = self.peek_at(0) else {
+                return match close {
+                    Some(close) => Err(Kind::Bracket(close)),
+                    None => Ok((parts, annotation.unwrap_or_default())),
+                };
+            };
+            let part = match c {
+                _ if Some(c) == close && !(c == '>' && self.is_operator()) => {
+                    self.pos += 1;
+                    return Ok((parts, annotation.unwrap_or_default()));
+                }
+                ')' | ']' => return Err(Kind::Bracket(c)),
+                '/' if close.is_none() && self.peek_at(1) == Some('/') => {
+                    return Ok((parts, annotation.unwrap_or_default()))
+                }
+                '(' => {
+                    self.pos += 1;
+                    let (parts, annotation) = self.parts(Some(')'))?;
+                    Part::Arg(round(parts, &annotation))
+                }
+                '[' => {
+                    self.pos += 1;
+                    Part::Arg(square(self.text()?))
+                }
+                '<' if !self.is_operator() => {
+                    self.pos += 1;
+                    Part::Arg(Arg::Boolean(self.parts(Some('>'))?.0))
+                }
+                _ => Part::Word(self.word(close)),
+            };
+            // `:: category` is only for how the block looks, except `:: list` on a reporter
+            match (&mut annotation, part) {
+                (None, Part::Word(word)) if word == "::" => annotation = Some(String::new()),
+                (None, part) => parts.push(part),
+                (Some(annotation), part) => {
+                    if !annotation.is_empty() {
+                        annotation.push(' ');
+                    }
+                    annotation.push_str(&part.to_string());
+                }
+            }
+        }
+    }
+
+    fn word(&mut self, close: Option<char>) -> String {
+        let start = self.pos;
+        while let Some(c) = self.peek_at(0) {
+            let ends = c.is_whitespace()
+                || matches!(c, '(' | ')' | '[' | ']' | '<')
+                || (c == '>' && close == Some('>'));
+            if ends && self.pos > start {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// Inside `[]` is taken as is, `\]` is a `]`
+    fn text(&mut self) -> Result<String, Kind> {
+        let mut text = String::new();
+        loop {
+            match self.peek_at(0) {
+                None => return Err(Kind::Bracket('[')),
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(text);
+                }
+                Some('\\') if self.peek_at(1) == Some(']') => {
+                    text.push(']');
+                    self.pos += 2;
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+fn round(parts: Vec<Part>, annotation: &str) -> Arg {
+    if annotation == "list" {
+        if let Some(name) = words(&parts) {
+            return Arg::List(name);
+        }
+    }
+    match parts.as_slice() {
+        [] => Arg::Number(String::new()),
+        [Part::Word(word)]
+            if word.parse::<f64>().is_ok() && !word.starts_with(char::is_alphabetic) =>
+        {
+            Arg::Number(word.clone())
+        }
+        [Part::Word(word)] if is_color(word) => Arg::Color(word.clone()),
+        [rest @ .., Part::Word(v)] if v == "v" => match words(rest) {
+            Some(value) => Arg::Dropdown(value),
+            None => Arg::Reporter(parts),
+        },
+        _ => Arg::Reporter(parts),
+    }
+}
+
+fn square(text: String) -> Arg {
+    let trimmed = text.trim();
+    if let Some(value) = trimmed.strip_suffix(" v") {
+        Arg::Dropdown(value.trim_end().to_owned())
+    } else if is_color(trimmed) {
+        Arg::Color(trimmed.to_owned())
+    } else {
+        Arg::Text(text)
+    }
+}
+
+/// C block that's waiting for its `end`, or the script itself
+#[derive(Default)]
+struct Frame {
+    block: Option<BlockNormalBuilder>,
+    /// Bodies before `else`
+    substacks: Vec<Option<StackBuilder>>,
+    body: Option<StackBuilder>,
+}
+
+impl Frame {
+    fn push(&mut self, stack: StackBuilder) -> Result<(), Kind> {
+        self.body = Some(match self.body.take() {
+            Some(body) => body.try_next(stack)?,
+            None => stack,
+        });
+        Ok(())
+    }
+
+    fn close(self) -> StackBuilder {
+        let Frame {
+            block,
+            mut substacks,
+            body,
+        } = self;
+        let mut block = block.expect("the script itself isn't closed");
+        substacks.push(body);
+        if substacks.len() == 2 {
+            block.set_opcode("control_if_else".to_owned());
+        }
+        for (key, substack) in ["SUBSTACK", "SUBSTACK2"].into_iter().zip(substacks) {
+            if let Some(substack) = substack {
+                block.add_input(key, Bib::stack(substack));
+            }
+        }
+        StackBuilder::start(block)
+    }
+}
+
+struct Compiler<'a> {
+    target: &'a TargetBuilder,
+    stage: Option<&'a TargetBuilder>,
+    /// Custom blocks by their label
+    procedures: HashMap<String, ProcedureBuilder>,
+    /// Label of the custom block whose script is being compiled
+    procedure: Option<String>,
+    /// Broadcasts that aren't in the target or the stage yet
+    broadcasts: BTreeSet<String>,
+}
+
+impl Compiler<'_> {
+    fn scripts(&mut self, source: &str) -> Result<Vec<StackBuilder>, ScratchblocksError> {
+        let mut scripts: Vec<Vec<(usize, Vec<Part>)>> = vec![vec![]];
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            if line.trim().is_empty() {
+                scripts.push(vec![]);
+                continue;
+            }
+            let parts = Lexer::parse(line).map_err(|kind| ScratchblocksError {
+                line: line_number,
+                kind,
+            })?;
+            if !parts.is_empty() {
+                scripts.last_mut().unwrap().push((line_number, parts));
+            }
+        }
+        scripts.retain(|script| !script.is_empty());
+
+        for script in &scripts {
+            let (line, parts) = &script[0];
+            if is_define(parts) {
+                let error = |kind| ScratchblocksError { line: *line, kind };
+                let (label, procedure) = define(parts).map_err(error)?;
+                if self.procedures.contains_key(&label) {
+                    return Err(error(Kind::DuplicateDefine(show(&parts[1..]))));
+                }
+                self.procedures.insert(label, procedure);
+            }
+        }
+
+        scripts.iter().map(|script| self.script(script)).collect()
+    }
+
+    fn script(&mut self, lines: &[(usize, Vec<Part>)]) -> Result<StackBuilder, ScratchblocksError> {
+        self.procedure = None;
+        let mut frames = vec![Frame::default()];
+        for (line, parts) in lines {
+            self.line(&mut frames, parts)
+                .map_err(|kind| ScratchblocksError { line: *line, kind })?;
+        }
+        let last_line = lines.last().map_or(0, |(line, _)| *line);
+        // C blocks that aren't closed with `end` are closed at the end of the script
+        while frames.len() > 1 {
+            let stack = frames.pop().unwrap().close();
+            frames
+                .last_mut()
+                .unwrap()
+                .push(stack)
+                .map_err(|kind| ScratchblocksError {
+                    line: last_line,
+                    kind,
+                })?;
+        }
+        Ok(frames.pop().unwrap().body.unwrap_or_default())
+    }
+
+    fn line(&mut self, frames: &mut Vec<Frame>, parts: &[Part]) -> Result<(), Kind> {
+        match label(parts).as_str() {
+            "end" => {
+                if frames.len() == 1 {
+                    return Err(Kind::UnexpectedKeyword("end".to_owned()));
+                }
+                let stack = frames.pop().unwrap().close();
+                frames.last_mut().unwrap().push(stack)
+            }
+            "else" => {
+                let frame = frames.last_mut().unwrap();
+                let is_if = frame
+                    .block
+                    .as_ref()
+                    .is_some_and(|block| block.opcode() == "control_if");
+                if !is_if || !frame.substacks.is_empty() {
+                    return Err(Kind::UnexpectedKeyword("else".to_owned()));
+                }
+                let body = frame.body.take();
+                frame.substacks.push(body);
+                Ok(())
+            }
+            _ if is_define(parts) => {
+                let label = label(&parts[1..]);
+                let definition = self
+                    .procedures
+                    .get(&label)
+                    .ok_or_else(|| Kind::UnknownBlock(show(parts)))?
+                    .definition();
+                self.procedure = Some(label);
+                frames.last_mut().unwrap().push(definition)
+            }
+            _ => {
+                let mut stack = self.block(parts)?;
+                let is_c = match stack.stack.as_slice() {
+                    [BlockBuilder::Normal(block)] => schema::get(block.opcode())
+                        .and_then(|schema| schema.inputs.as_ref())
+                        .is_some_and(|inputs| inputs.contains_key("SUBSTACK")),
+                    _ => false,
+                };
+                if is_c {
+                    let Some(BlockBuilder::Normal(block)) = stack.stack.pop() else {
+                        unreachable!()
+                    };
+                    frames.push(Frame {
+                        block: Some(block),
+                        ..Default::default()
+                    });
+                    Ok(())
+                } else {
+                    frames.last_mut().unwrap().push(stack)
+                }
+            }
+        }
+    }
+
+    fn block(&mut self, parts: &[Part]) -> Result<StackBuilder, Kind> {
+        // Reporter on its own line, ex. `(x position)`
+        match parts {
+            [Part::Arg(Arg::Reporter(parts) | Arg::Boolean(parts))] => return self.block(parts),
+            [Part::Arg(Arg::List(name))] => return self.list(name),
+            _ => {}
+        }
+        let label = label(parts);
+        let args = args(parts);
+        if let Some(procedure) = self.procedures.get(&label) {
+            let procedure = procedure.clone();
+            return self.call(&procedure, &args);
+        }
+        if let Some(name) = words(parts) {
+            if let Some(argument) = self.argument(&name) {
+                return Ok(argument);
+            }
+        }
+        if let Some(candidates) = TEMPLATES.get(&label) {
+            let (opcode, keys) = candidates
+                .iter()
+                .find(|(opcode, keys)| fits(opcode, keys, &args))
+                .unwrap_or_else(|| candidates.last().unwrap());
+            return self.normal(opcode, keys, &args);
+        }
+        match words(parts) {
+            Some(name) => self
+                .varlist(&name, ListOrVariable::Variable)
+                .or_else(|| self.varlist(&name, ListOrVariable::List))
+                .map(StackBuilder::start_varlist)
+                .ok_or(Kind::UnknownBlock(name)),
+            None => Err(Kind::UnknownBlock(show(parts))),
+        }
+    }
+
+    fn normal(&mut self, opcode: &str, keys: &[&str], args: &[&Arg]) -> Result<StackBuilder, Kind> {
+        let schema = schema::get(opcode).expect("every block in BLOCKS is in the schema");
+        let mut b = BlockNormalBuilder::new(opcode);
+        for (key, arg) in keys.iter().zip(args) {
+            if let Some(allowed) = schema.fields.get(*key) {
+                let field = self.field(key, allowed.as_deref(), arg)?;
+                if opcode == "control_stop" {
+                    b.set_mutation(BlockMutation {
+                        tag_name: "mutation".to_owned(),
+                        children: vec![],
+                        mutation_enum: BlockMutationEnum::ControlStop {
+                            hasnext: field.value.starts_with("other scripts"),
+                        },
+                    });
+                }
+                b.add_field(*key, field);
+            } else if let Some(kind) = schema.inputs.as_ref().and_then(|inputs| inputs.get(*key)) {
+                if let Some(input) = self.input(kind, arg)? {
+                    b.add_input(*key, input);
+                }
+            }
+        }
+        Ok(StackBuilder::start(b))
+    }
+
+    fn call(&mut self, procedure: &ProcedureBuilder, args: &[&Arg]) -> Result<StackBuilder, Kind> {
+        let mut inputs = vec![];
+        for (argument, arg) in procedure.arguments().iter().zip(args) {
+            let kind = match argument.kind {
+                ArgumentKind::StringNumber => InputKind::Text,
+                ArgumentKind::Boolean => InputKind::Boolean,
+            };
+            inputs.push(self.input(&kind, arg)?.unwrap_or_default());
+        }
+        let mut call = procedure.call(inputs)?;
+        // Empty boolean slot has no input at all
+        if let Some(BlockBuilder::Normal(b)) = call.stack.first_mut() {
+            let mut inputs = b.inputs().clone();
+            inputs.retain(|_, input| !input.values.is_empty());
+            b.set_inputs(inputs);
+        }
+        Ok(call)
+    }
+
+    fn argument(&self, name: &str) -> Option<StackBuilder> {
+        let procedure = self.procedures.get(self.procedure.as_ref()?)?;
+        procedure.argument(name).ok()
+    }
+
+    fn list(&self, name: &str) -> Result<StackBuilder, Kind> {
+        self.varlist(name, ListOrVariable::List)
+            .map(StackBuilder::start_varlist)
+            .ok_or_else(|| Kind::UnknownList(name.to_owned()))
+    }
+
+    /// Variable or list by its name, the sprite's one goes before the stage's
+    fn varlist(&self, name: &str, kind: ListOrVariable) -> Option<BlockVarListBuilder> {
+        let has = |target: &TargetBuilder| match kind {
+            ListOrVariable::Variable => target.variables.contains_key(name),
+            ListOrVariable::List => target.lists.contains_key(name),
+        };
+        let from = match self.stage {
+            Some(_) if has(self.target) => VarListFrom::Sprite,
+            Some(stage) if has(stage) => VarListFrom::Global,
+            None if has(self.target) => VarListFrom::Global,
+            _ => return None,
+        };
+        let mut b = BlockVarListBuilder::global_var(name);
+        b.set_kind(kind).set_from(from);
+        Some(b)
+    }
+
+    fn field(&mut self, key: &str, allowed: Option<&[String]>, arg: &Arg) -> Result<Bfb, Kind> {
+        let (Arg::Dropdown(value) | Arg::Text(value)) = arg else {
+            return Err(Kind::ExpectedDropdown(Part::Arg(arg.clone()).to_string()));
+        };
+        let varlist_field = |kind, from| match (kind, from) {
+            (ListOrVariable::Variable, VarListFrom::Sprite) => FieldKind::SpriteVariable,
+            (ListOrVariable::Variable, VarListFrom::Global) => FieldKind::GlobalVariable,
+            (ListOrVariable::List, VarListFrom::Sprite) => FieldKind::SpriteList,
+            (ListOrVariable::List, VarListFrom::Global) => FieldKind::GlobalList,
+            (ListOrVariable::Variable, VarListFrom::Scope) => FieldKind::Variable,
+            (ListOrVariable::List, VarListFrom::Scope) => FieldKind::List,
+        };
+        Ok(match key {
+            "VARIABLE" => {
+                let variable = self
+                    .varlist(value, ListOrVariable::Variable)
+                    .ok_or_else(|| Kind::UnknownVariable(value.clone()))?;
+                Bfb::new_with_kind(value.clone(), varlist_field(variable.kind, variable.from))
+            }
+            "LIST" => {
+                let list = self
+                    .varlist(value, ListOrVariable::List)
+                    .ok_or_else(|| Kind::UnknownList(value.clone()))?;
+                Bfb::new_with_kind(value.clone(), varlist_field(list.kind, list.from))
+            }
+            "BROADCAST_OPTION" => {
+                self.broadcast(value);
+                Bfb::new_with_kind(value.clone(), FieldKind::Broadcast)
+            }
+            _ => Bfb::new(menu_value(key, allowed, value)),
+        })
+    }
+
+    /// None when the input is left empty
+    fn input(&mut self, kind: &InputKind, arg: &Arg) -> Result<Option<Bib>, Kind> {
+        let value = match (kind, arg) {
+            (_, Arg::Boolean(parts)) if parts.is_empty() => return Ok(None),
+            (InputKind::Boolean, Arg::Boolean(parts) | Arg::Reporter(parts)) => {
+                return Ok(Some(Bib::stack(self.block(parts)?)))
+            }
+            (InputKind::Boolean, _) => {
+                return Err(Kind::ExpectedBoolean(Part::Arg(arg.clone()).to_string()))
+            }
+            (_, Arg::Reporter(_) | Arg::Boolean(_) | Arg::List(_)) => {
+                let reporter = match arg {
+                    Arg::List(name) => self.list(name)?,
+                    Arg::Reporter(parts) | Arg::Boolean(parts) => self.block(parts)?,
+                    _ => unreachable!(),
+                };
+                return Ok(Some(match kind {
+                    InputKind::Menu(menu) => {
+                        let mut b = Bib::new();
+                        b.set_shadow(ShadowInputType::ShadowObscured)
+                            .add_input(Some(StackOrValue::Stack(reporter)))
+                            .add_input(Some(StackOrValue::Stack(menu_block(menu, ""))));
+                        b
+                    }
+                    _ => match shadow(kind) {
+                        Some(shadow) => Bib::stack_with_value_obscured(reporter, shadow),
+                        None => Bib::stack(reporter),
+                    },
+                }));
+            }
+            (
+                _,
+                Arg::Number(value) | Arg::Text(value) | Arg::Color(value) | Arg::Dropdown(value),
+            ) => value,
+        };
+        Ok(Some(match kind {
+            InputKind::Menu(menu) => {
+                let mut b = Bib::new();
+                b.set_shadow(ShadowInputType::Shadow)
+                    .add_input(Some(StackOrValue::Stack(menu_block(menu, value))));
+                b
+            }
+            InputKind::Broadcast => {
+                self.broadcast(value);
+                Bib::value(BlockInputValue::Broadcast {
+                    name: value.clone(),
+                    id: "".to_owned(),
+                })
+            }
+            kind => Bib::value(literal(kind, value)),
+        }))
+    }
+
+    fn broadcast(&mut self, name: &str) {
+        let exists = |target: &TargetBuilder| target.broadcasts.contains(name);
+        if !exists(self.target) && !self.stage.is_some_and(exists) {
+            self.broadcasts.insert(name.to_owned());
+        }
+    }
+}
+
+fn is_define(parts: &[Part]) -> bool {
+    matches!(parts.first(), Some(Part::Word(word)) if word.eq_ignore_ascii_case("define"))
+}
+
+/// `define jump (height) <fast>` is a custom block with proccode `jump %s %b`
+fn define(parts: &[Part]) -> Result<(String, ProcedureBuilder), Kind> {
+    let mut proccode = vec![];
+    let mut names = vec![];
+    for part in &parts[1..] {
+        let (placeholder, name) = match part {
+            Part::Word(word) => {
+                proccode.push(word.clone());
+                continue;
+            }
+            Part::Arg(Arg::Reporter(parts)) => ("%s", words(parts)),
+            Part::Arg(Arg::Boolean(parts)) => ("%b", words(parts)),
+            Part::Arg(
+                Arg::Number(name) | Arg::Text(name) | Arg::Dropdown(name) | Arg::List(name),
+            ) => ("%s", Some(name.clone())),
+            Part::Arg(Arg::Color(_)) => ("%s", None),
+        };
+        let name = name.ok_or_else(|| Kind::UnknownBlock(part.to_string()))?;
+        proccode.push(placeholder.to_owned());
+        names.push(name);
+    }
+    let procedure = ProcedureBuilder::new(proccode.join(" "), names)?;
+    Ok((label(&parts[1..]), procedure))
+}
+
+/// Whether `args` fits in the fields of the block, inputs take anything
+fn fits(opcode: &str, keys: &[&str], args: &[&Arg]) -> bool {
+    let schema = schema::get(opcode).expect("every block in BLOCKS is in the schema");
+    keys.iter()
+        .zip(args)
+        .all(|(key, arg)| match (schema.fields.get(*key), arg) {
+            (None, _) => true,
+            (Some(Some(allowed)), Arg::Dropdown(value) | Arg::Text(value)) => allowed
+                .iter()
+                .any(|allowed| normalize(allowed) == normalize(value)),
+            (Some(None), Arg::Dropdown(_)) => true,
+            (Some(_), _) => false,
+        })
+}
+
+/// Dropdown's text is compared without case and space, ex. `day of week` is `DAYOFWEEK`
+fn normalize(value: &str) -> String {
+    value.replace(' ', "").to_lowercase()
+}
+
+/// Value that is put in the field when `shown` is picked in the dropdown
+fn menu_value(key: &str, allowed: Option<&[String]>, shown: &str) -> String {
+    let value = match allowed {
+        Some(allowed) => allowed
+            .iter()
+            .find(|allowed| normalize(allowed) == normalize(shown))
+            .map(String::as_str),
+        None if SPRITE_MENUS.contains(&key) => SPECIAL_MENU_VALUES
+            .iter()
+            .find(|(special, _)| special.eq_ignore_ascii_case(shown))
+            .map(|(_, value)| *value),
+        None => None,
+    };
+    value.unwrap_or(shown).to_owned()
+}
+
+fn menu_block(menu: &str, shown: &str) -> StackBuilder {
+    let schema = schema::get(menu).expect("menu of an input is in the schema");
+    let mut b = BlockNormalBuilder::new(menu);
+    if let Some((key, allowed)) = schema.fields.iter().next() {
+        let value = match shown {
+            "" => allowed
+                .as_ref()
+                .and_then(|allowed| allowed.first().cloned())
+                .unwrap_or_default(),
+            shown => menu_value(key, allowed.as_deref(), shown),
+        };
+        b.add_field(key.clone(), Bfb::new(value));
+    }
+    b.set_shadow(true);
+    StackBuilder::start(b)
+}
+
+fn shadow(kind: &InputKind) -> Option<BlockInputValue> {
+    match kind {
+        InputKind::Number => input::Number::shadow(),
+        InputKind::PositiveNumber => input::PositiveNumber::shadow(),
+        InputKind::WholeNumber => input::WholeNumber::shadow(),
+        InputKind::Integer => input::Integer::shadow(),
+        InputKind::Angle => input::Angle::shadow(),
+        InputKind::Color => input::Color::shadow(),
+        InputKind::Text => input::Text::shadow(),
+        InputKind::Broadcast => input::Broadcast::shadow(),
+        InputKind::Boolean | InputKind::Substack | InputKind::Menu(_) => None,
+    }
+}
+
+/// Literal typed in a slot, it's kept as text when it isn't a number
+fn literal(kind: &InputKind, text: &str) -> BlockInputValue {
+    let number = match (text.parse::<i64>(), text.parse::<f64>()) {
+        (Ok(n), _) => Value::Number(n.into()),
+        (_, Ok(n)) => Value::Number(n.into()),
+        _ => Value::Text(text.to_owned()),
+    };
+    match kind {
+        InputKind::Number => input::Number::literal(number),
+        InputKind::PositiveNumber => input::PositiveNumber::literal(number),
+        InputKind::WholeNumber => input::WholeNumber::literal(number),
+        InputKind::Integer => input::Integer::literal(number),
+        InputKind::Angle => input::Angle::literal(number),
+        InputKind::Color => BlockInputValue::Color {
+            value: Value::Text(text.to_owned()),
+        },
+        _ => BlockInputValue::String {
+            value: Value::Text(text.to_owned()),
+        },
+    }
+}
+
+/// Render scripts as scratchblocks, the reverse of [`compile`].
+/// Scripts are separated by a blank line.
+pub fn decompile(scripts: &[StackBuilder]) -> String {
+    let mut out = String::new();
+    for script in scripts {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        render_script(script, 0, &mut out);
+    }
+    out
+}
+
+/// Scripts of `target` and its comments that aren't attached to any block
+pub fn decompile_target(target: &TargetBuilder) -> String {
+    render_target(&target.block_stackes, &target.comments)
+}
+
+/// Every target under a `// --- name ---` header, the stage goes first.
+pub fn decompile_project(project: &ProjectBuilder) -> String {
+    let targets = std::iter::once(&project.stage_builder.target)
+        .chain(project.sprite_builders.iter().map(|sprite| &sprite.target))
+        .map(|target| (target.name.as_str(), decompile_target(target)));
+    with_headers(targets)
+}
+
+/// Same as [`decompile_project`] but straight from `project.json`, so assets aren't needed.
+pub fn decompile_sb3(project: &Project) -> String {
+    let targets: Vec<&Target> = project
+        .targets
+        .iter()
+        .map(|target| match target {
+            SpriteOrStage::Stage(stage) => &stage.target,
+            SpriteOrStage::Sprite(sprite) => &sprite.target,
+        })
+        .collect();
+    let global_varlist_ctx = project.targets.iter().find_map(|target| match target {
+        SpriteOrStage::Stage(stage) => Some(varlist_context(&stage.target)),
+        SpriteOrStage::Sprite(_) => None,
+    });
+    // Broadcasts are rendered by their name, ids aren't needed
+    let all_broadcasts = HashMap::new();
+    let targets = targets.into_iter().map(|target| {
+        let this_varlist_ctx = varlist_context(target);
+        let global_varlist_ctx = global_varlist_ctx.as_ref().unwrap_or(&this_varlist_ctx);
+        let scripts = StackBuilder::from_target_blocks(
+            &target.blocks.0,
+            &target.comments.0,
+            &TargetContext {
+                global_vars: &global_varlist_ctx.vars,
+                global_lists: &global_varlist_ctx.lists,
+                this_sprite_vars: &this_varlist_ctx.vars,
+                this_sprite_lists: &this_varlist_ctx.lists,
+                all_broadcasts: &all_broadcasts,
+            },
+        );
+        let mut comments: Vec<CommentBuilder> = target
+            .comments
+            .0
+            .values()
+            .filter(|comment| comment.block_id.is_none())
+            .map(|comment| CommentBuilder::from_comment(comment.clone()))
+            .collect();
+        comments.sort_by(|a, b| a.content.cmp(&b.content));
+        (target.name.as_str(), render_target(&scripts, &comments))
+    });
+    with_headers(targets)
+}
+
+lazy_static! {
+    /// Opcode -> the first label in [`BLOCKS`] that it has
+    static ref LABELS: HashMap<&'static str, &'static str> = {
+        let mut labels = HashMap::new();
+        for (template, opcode) in BLOCKS {
+            labels.entry(*opcode).or_insert(*template);
+        }
+        labels
+    };
+}
+
+fn with_headers<'a>(targets: impl Iterator<Item = (&'a str, String)>) -> String {
+    targets
+        .map(|(name, scripts)| format!("// --- {name} ---\n\n{scripts}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_target(scripts: &[StackBuilder], comments: &[CommentBuilder]) -> String {
+    let mut out = decompile(scripts);
+    for comment in comments {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        for line in comment.content.lines() {
+            out.push_str(&format!("// {line}\n"));
+        }
+    }
+    out
+}
+
+fn push_line(out: &mut String, indent: usize, text: &str, comments: &[String]) {
+    out.push_str(&"    ".repeat(indent));
+    out.push_str(text);
+    if !comments.is_empty() {
+        let comments = comments
+            .iter()
+            .map(|comment| comment.replace('\n', " "))
+            .collect::<Vec<_>>()
+            .join("; ");
+        out.push_str(&format!(" // {comments}"));
+    }
+    out.push('\n');
+}
+
+fn render_script(stack: &StackBuilder, indent: usize, out: &mut String) {
+    for block in &stack.stack {
+        match block {
+            BlockBuilder::Normal(b) => render_statement(b, indent, out),
+            BlockBuilder::VarList(vl) => {
+                let comments: Vec<String> = vl.comment.iter().map(|c| c.content.clone()).collect();
+                push_line(out, indent, &render_varlist(&vl.kind, &vl.name), &comments);
+            }
+        }
+    }
+}
+
+fn render_statement(b: &BlockNormalBuilder, indent: usize, out: &mut String) {
+    let mut comments = vec![];
+    let text = render_block(b, &mut comments);
+    let text = match opcode::shape_of(b.opcode()) {
+        Some(BlockShape::Reporter) => format!("({text})"),
+        Some(BlockShape::Boolean) => format!("<{text}>"),
+        _ => text,
+    };
+    push_line(out, indent, &text, &comments);
+
+    let is_c = schema::get(b.opcode())
+        .and_then(|schema| schema.inputs.as_ref())
+        .is_some_and(|inputs| inputs.contains_key("SUBSTACK"));
+    if !is_c {
+        return;
+    }
+    let substack = |key: &str, out: &mut String| {
+        if let Some(StackOrValue::Stack(stack)) = b
+            .inputs()
+            .get(key)
+            .and_then(|input| input.values.iter().flatten().next())
+        {
+            render_script(stack, indent + 1, out);
+        }
+    };
+    substack("SUBSTACK", out);
+    if b.opcode() == "control_if_else" {
+        push_line(out, indent, "else", &[]);
+        substack("SUBSTACK2", out);
+    }
+    push_line(out, indent, "end", &[]);
+}
+
+/// Text of the block without its outer brackets.
+/// Comments of the block and the reporters in it are put in `comments`.
+fn render_block(b: &BlockNormalBuilder, comments: &mut Vec<String>) -> String {
+    if let Some(comment) = b.comment() {
+        comments.push(comment.content.clone());
+    }
+    let opcode = b.opcode().as_str();
+    let field = |key: &str| {
+        b.fields()
+            .get(key)
+            .map(|field| field.value.clone())
+            .unwrap_or_default()
+    };
+    match (opcode, b.mutation().map(|mutation| &mutation.mutation_enum)) {
+        ("procedures_definition", _) => {
+            let prototype = b
+                .inputs()
+                .get("custom_block")
+                .and_then(|input| input.values.iter().flatten().next());
+            let Some(StackOrValue::Stack(prototype)) = prototype else {
+                return "define".to_owned();
+            };
+            let Some(BlockBuilder::Normal(prototype)) = prototype.stack.first() else {
+                return "define".to_owned();
+            };
+            let Some(BlockMutationEnum::ProceduresPrototype {
+                proccode,
+                argumentnames,
+                ..
+            }) = prototype.mutation().map(|mutation| &mutation.mutation_enum)
+            else {
+                return "define".to_owned();
+            };
+            let proccode = fill_proccode(proccode, |i, kind| {
+                let name = argumentnames.get(i).map_or("", String::as_str);
+                match kind {
+                    ArgumentKind::StringNumber => format!("({name})"),
+                    ArgumentKind::Boolean => format!("<{name}>"),
+                }
+            });
+            format!("define {proccode}")
+        }
+        (
+            "procedures_call",
+            Some(BlockMutationEnum::ProceduresCall {
+                proccode,
+                argumentids,
+                ..
+            }),
+        ) => fill_proccode(proccode, |i, kind| {
+            let kind = match kind {
+                ArgumentKind::StringNumber => InputKind::Text,
+                ArgumentKind::Boolean => InputKind::Boolean,
+            };
+            match argumentids.get(i).and_then(|id| b.inputs().get(id)) {
+                Some(input) => render_input(input, Some(&kind), comments),
+                None => render_empty(Some(&kind)),
+            }
+        }),
+        ("argument_reporter_string_number" | "argument_reporter_boolean", _) => field("VALUE"),
+        ("data_variable", _) => field("VARIABLE"),
+        ("data_listcontents", _) => format!("{} :: list", field("LIST")),
+        _ => {
+            let label_opcode = match opcode {
+                "control_if_else" => "control_if",
+                opcode => opcode,
+            };
+            match LABELS.get(label_opcode) {
+                Some(label) => label
+                    .split(' ')
+                    .map(|word| {
+                        match word.strip_prefix('{').and_then(|key| key.strip_suffix('}')) {
+                            Some(key) => render_slot(b, key, comments),
+                            None => word.to_owned(),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                None => render_unknown(b, comments),
+            }
+        }
+    }
+}
+
+/// Text of the block without its outer brackets, ex. for estimating how wide it is
+pub(crate) fn block_text(b: &BlockNormalBuilder) -> String {
+    render_block(b, &mut vec![])
+}
+
+/// Block that scratchblocks doesn't have a label for, it's shown with its opcode and its slots
+/// ordered by key, ex. `music_playDrumForBeats (1 v) (0.25) :: music`
+fn render_unknown(b: &BlockNormalBuilder, comments: &mut Vec<String>) -> String {
+    let schema = schema::get(b.opcode());
+    let mut keys: Vec<&String> = b
+        .fields()
+        .keys()
+        .chain(b.inputs().keys().filter(|key| {
+            let kind = schema
+                .and_then(|schema| schema.inputs.as_ref())
+                .and_then(|inputs| inputs.get(*key));
+            kind != Some(&InputKind::Substack)
+        }))
+        .collect();
+    keys.sort();
+    let mut words = vec![b.opcode().clone()];
+    words.extend(keys.into_iter().map(|key| render_slot(b, key, comments)));
+    if let Some(extension) = opcode::extension_of(b.opcode()) {
+        words.push(format!(":: {extension}"));
+    }
+    words.join(" ")
+}
+
+fn render_slot(b: &BlockNormalBuilder, key: &str, comments: &mut Vec<String>) -> String {
+    let schema = schema::get(b.opcode());
+    if let Some(field) = b.fields().get(key) {
+        let has_allowed = schema
+            .and_then(|schema| schema.fields.get(key))
+            .is_some_and(Option::is_some);
+        return format!("[{} v]", escape(&shown(key, &field.value, has_allowed)));
+    }
+    let kind = schema
+        .and_then(|schema| schema.inputs.as_ref())
+        .and_then(|inputs| inputs.get(key));
+    match b.inputs().get(key) {
+        Some(input) => render_input(input, kind, comments),
+        None => render_empty(kind),
+    }
+}
+
+/// Input with its brackets. Reporter that obscures the shadow is shown in place of the shadow.
+fn render_input(input: &Bib, kind: Option<&InputKind>, comments: &mut Vec<String>) -> String {
+    match input.values.iter().flatten().next() {
+        None => render_empty(kind),
+        Some(StackOrValue::Value(value)) => render_value(value),
+        Some(StackOrValue::Stack(stack)) => match stack.stack.first() {
+            None => render_empty(kind),
+            Some(BlockBuilder::VarList(vl)) => {
+                comments.extend(vl.comment.iter().map(|c| c.content.clone()));
+                render_varlist(&vl.kind, &vl.name)
+            }
+            Some(BlockBuilder::Normal(b)) if b.is_shadow() && b.fields().len() == 1 => {
+                let (key, field) = b.fields().iter().next().unwrap();
+                let has_allowed = schema::get(b.opcode())
+                    .and_then(|schema| schema.fields.get(key))
+                    .is_some_and(Option::is_some);
+                format!("({} v)", escape(&shown(key, &field.value, has_allowed)))
+            }
+            Some(BlockBuilder::Normal(b)) => {
+                let text = render_block(b, comments);
+                match opcode::shape_of(b.opcode()) {
+                    Some(BlockShape::Boolean) => format!("<{text}>"),
+                    _ => format!("({text})"),
+                }
+            }
+        },
+    }
+}
+
+fn render_value(value: &BlockInputValue) -> String {
+    match value {
+        BlockInputValue::Number { value }
+        | BlockInputValue::PositiveNumber { value }
+        | BlockInputValue::PositiveInteger { value }
+        | BlockInputValue::Integer { value }
+        | BlockInputValue::Angle { value } => {
+            let text = value_to_string(value);
+            // Text in a number slot would read as a reporter in `()`
+            if text.is_empty() || text.parse::<f64>().is_ok() {
+                format!("({text})")
+            } else {
+                format!("[{}]", escape(&text))
+            }
+        }
+        BlockInputValue::String { value } | BlockInputValue::Color { value } => {
+            format!("[{}]", escape(&value_to_string(value)))
+        }
+        BlockInputValue::Broadcast { name, .. } => format!("({} v)", escape(name)),
+        BlockInputValue::Variable { name, .. } => render_varlist(&ListOrVariable::Variable, name),
+        BlockInputValue::List { name, .. } => render_varlist(&ListOrVariable::List, name),
+    }
+}
+
+fn render_varlist(kind: &ListOrVariable, name: &str) -> String {
+    match kind {
+        ListOrVariable::Variable => format!("({name})"),
+        ListOrVariable::List => format!("({name} :: list)"),
+    }
+}
+
+fn render_empty(kind: Option<&InputKind>) -> String {
+    match kind {
+        Some(InputKind::Boolean) => "<>".to_owned(),
+        Some(InputKind::Text) => "[]".to_owned(),
+        _ => "()".to_owned(),
+    }
+}
+
+/// What the dropdown shows for the field's value, the reverse of [`menu_value`]
+fn shown(key: &str, value: &str, has_allowed: bool) -> String {
+    if SPRITE_MENUS.contains(&key) {
+        if let Some((shown, _)) = SPECIAL_MENU_VALUES
+            .iter()
+            .find(|(_, special)| *special == value)
+        {
+            return (*shown).to_owned();
+        }
+    }
+    if has_allowed {
+        value.to_lowercase()
+    } else {
+        value.to_owned()
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace(']', "\\]")
+}
+
+/// Proccode with each `%s`, `%n` and `%b` replaced by `arg(index, kind)`
+fn fill_proccode(proccode: &str, mut arg: impl FnMut(usize, ArgumentKind) -> String) -> String {
+    let mut filled = String::new();
+    let mut index = 0;
+    let mut chars = proccode.chars().peekable();
+    while let Some(c) = chars.next() {
+        let kind = match (c, chars.peek()) {
+            ('%', Some('s' | 'n')) => ArgumentKind::StringNumber,
+            ('%', Some('b')) => ArgumentKind::Boolean,
+            _ => {
+                filled.push(c);
+                continue;
+            }
+        };
+        chars.next();
+        filled.push_str(&arg(index, kind));
+        index += 1;
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blocks::{
+            self, add_to_list, and, global_list, global_var, greater_than, hide, if_else, join,
+            length_of_list, less_than, mouse_down, repeat, say, sprite_list, sprite_var,
+            wait_until, x_position, y_position,
+        },
+        data::{ListBuilder, VariableBuilder},
+    };
+
+    fn compiled(source: &str) -> Result<Vec<StackBuilder>, ScratchblocksError> {
+        let mut sprite = TargetBuilder::default();
+        compile(source, &mut sprite, Some(&TargetBuilder::default()))?;
+        Ok(sprite.block_stackes)
+    }
+
+    fn error(source: &str) -> (usize, Kind) {
+        let e = compiled(source).unwrap_err();
+        (e.line, e.kind)
+    }
+
+    fn opcodes(stack: &StackBuilder) -> Vec<&str> {
+        stack
+            .stack
+            .iter()
+            .map(|block| match block {
+                BlockBuilder::Normal(b) => b.opcode().as_str(),
+                BlockBuilder::VarList(_) => "varlist",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nested_brackets() {
+        assert_eq!(
+            compiled("say (join [a] (join [b] (x position)))").unwrap(),
+            vec![say(join("a", join("b", x_position())))]
+        );
+    }
+
+    #[test]
+    fn operators_are_not_boolean_brackets() {
+        assert_eq!(
+            compiled("wait until <<(x position) < [10]> and <(x position) > (y position)>>")
+                .unwrap(),
+            vec![wait_until(and(
+                less_than(x_position(), "10"),
+                greater_than(x_position(), y_position()),
+            ))]
+        );
+    }
+
+    #[test]
+    fn escaped_square_bracket() {
+        assert_eq!(compiled(r"say [a\]b]").unwrap(), vec![say("a]b")]);
+        assert_eq!(error("say [a\\]"), (1, Kind::Bracket('[')));
+    }
+
+    #[test]
+    fn else_and_end_need_a_c_block() {
+        assert_eq!(
+            error("move (1) steps\nend"),
+            (2, Kind::UnexpectedKeyword("end".to_owned()))
+        );
+        assert_eq!(
+            error("forever\nmove (1) steps\nelse\nend"),
+            (3, Kind::UnexpectedKeyword("else".to_owned()))
+        );
+        assert_eq!(
+            error("if <mouse down?> then\nelse\nelse\nend"),
+            (3, Kind::UnexpectedKeyword("else".to_owned()))
+        );
+        assert_eq!(error("say (x position"), (1, Kind::Bracket(')')));
+    }
+
+    #[test]
+    fn c_block_is_closed_at_end_of_script() {
+        assert_eq!(
+            compiled("if <mouse down?> then\nhide\nelse\nshow").unwrap(),
+            vec![if_else(
+                mouse_down(),
+                Some(Bib::stack(hide())),
+                Some(Bib::stack(blocks::show())),
+            )]
+        );
+    }
+
+    #[test]
+    fn custom_block_used_before_define() {
+        let scripts =
+            compiled("when flag clicked\njump (10)\n\ndefine jump (height)\nchange y by (height)")
+                .unwrap();
+        assert_eq!(
+            opcodes(&scripts[0]),
+            ["event_whenflagclicked", "procedures_call"]
+        );
+        assert_eq!(
+            opcodes(&scripts[1]),
+            ["procedures_definition", "motion_changeyby"]
+        );
+    }
+
+    #[test]
+    fn duplicate_define_is_error() {
+        assert_eq!(
+            error("define jump (height)\n\ndefine jump (speed)"),
+            (3, Kind::DuplicateDefine("jump (speed)".to_owned()))
+        );
+    }
+
+    #[test]
+    fn broadcasts_are_declared() {
+        let mut stage = TargetBuilder::default();
+        stage.add_broadcast("old");
+        let mut sprite = TargetBuilder::default();
+        compile(
+            "when I receive [start v]\nbroadcast (old v)\nbroadcast [new]",
+            &mut sprite,
+            Some(&stage),
+        )
+        .unwrap();
+        assert_eq!(
+            sprite.broadcasts,
+            BTreeSet::from(["new".to_owned(), "start".to_owned()])
+        );
+    }
+
+    #[test]
+    fn list_annotation_picks_the_list() {
+        let mut sprite = TargetBuilder::default();
+        sprite
+            .add_variable("items", VariableBuilder::new(Value::Text(String::new())))
+            .add_list("items", ListBuilder::new(vec![]));
+        compile("say (items)\nsay (items :: list)", &mut sprite, None).unwrap();
+        assert_eq!(
+            sprite.block_stackes,
+            vec![say(global_var("items")).next(say(global_list("items")))]
+        );
+        assert_eq!(
+            error("say (items :: list)"),
+            (1, Kind::UnknownList("items".to_owned()))
+        );
+    }
+
+    #[test]
+    fn compile_decompile_round_trip() {
+        let mut stage = TargetBuilder::default();
+        stage
+            .add_variable("score", VariableBuilder::new(Value::Text(String::new())))
+            .add_list("names", ListBuilder::new(vec![]));
+        let mut sprite = TargetBuilder::default();
+        sprite
+            .add_variable("items", VariableBuilder::new(Value::Text(String::new())))
+            .add_list("items", ListBuilder::new(vec![]))
+            .add_list("7", ListBuilder::new(vec![]));
+        let items = || Bfb::new_with_kind("items".to_owned(), FieldKind::SpriteList);
+        let scripts = vec![
+            blocks::when_flag_clicked()
+                .next(say(join(global_list("names"), sprite_list("items"))))
+                .next(repeat(
+                    length_of_list(items()),
+                    Some(Bib::stack(add_to_list(items(), sprite_var("items")))),
+                ))
+                .next(if_else(
+                    mouse_down(),
+                    Some(Bib::stack(say(global_var("score")))),
+                    Some(Bib::stack(say(sprite_list("7")))),
+                )),
+            sprite_list("items"),
+            sprite_var("items"),
+        ];
+        let source = decompile(&scripts);
+        let mut compiled = sprite.clone();
+        compile(&source, &mut compiled, Some(&stage)).unwrap();
+        assert_eq!(compiled.block_stackes, scripts, "{source}");
+    }
+}
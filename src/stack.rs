@@ -1,13 +1,47 @@
 use std::collections::HashMap;
 
-use sb_sbity::{block::Block, comment::Comment};
+use sb_sbity::{
+    block::{Block, BlockMutationEnum, ListOrVariable},
+    comment::Comment,
+    value::OpCode,
+};
 
 use crate::{
     block::{BlockBuilder, BlockNormalBuilder, BlockVarListBuilder},
     build_context::TargetContext,
-    uid::Uid,
+    opcode::{self, BlockShape, StandardOpCode},
+    uid::{Uid, UidGenerator},
 };
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum StackError {
+    /// Nothing can go after this block. It's a cap block or a reporter.
+    NoNext { opcode: OpCode, shape: BlockShape },
+    /// This block can't go after other blocks. It's a hat block or a reporter.
+    NoPrevious { opcode: OpCode, shape: BlockShape },
+}
+
+impl std::error::Error for StackError {}
+
+impl std::fmt::Display for StackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StackError::NoNext { opcode, shape } => {
+                write!(
+                    f,
+                    "`{opcode}` is a {shape:?} block, nothing can go after it"
+                )
+            }
+            StackError::NoPrevious { opcode, shape } => {
+                write!(
+                    f,
+                    "`{opcode}` is a {shape:?} block, it can't go after other blocks"
+                )
+            }
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct StackBuilder {
     pub stack: Vec<BlockBuilder>,
@@ -38,11 +72,36 @@ impl StackBuilder {
         StackBuilder { stack }
     }
 
+    /// Append `next_stack` after this stack.
+    /// It isn't checked that the blocks can connect, see [`StackBuilder::try_next`] for that.
     pub fn next(mut self, mut next_stack: StackBuilder) -> StackBuilder {
         self.stack.append(&mut next_stack.stack);
         self
     }
 
+    /// Append `next_stack` after this stack.
+    /// Error when the last block of this stack is a cap block or a reporter,
+    /// or the first block of `next_stack` is a hat block or a reporter.
+    /// Blocks with unknown opcode are let through.
+    pub fn try_next(mut self, mut next_stack: StackBuilder) -> Result<StackBuilder, StackError> {
+        if let Some(last) = self.stack.last() {
+            if let Some((opcode, shape)) = block_shape(last) {
+                if !shape.has_next() && !is_stop_with_next(last) {
+                    return Err(StackError::NoNext { opcode, shape });
+                }
+            }
+        }
+        if let Some(first) = next_stack.stack.first() {
+            if let Some((opcode, shape)) = block_shape(first) {
+                if !shape.has_previous() {
+                    return Err(StackError::NoPrevious { opcode, shape });
+                }
+            }
+        }
+        self.stack.append(&mut next_stack.stack);
+        Ok(self)
+    }
+
     pub fn set_top_block_position(&mut self, x: f64, y: f64) -> &mut Self {
         match &mut self.stack[0] {
             BlockBuilder::Normal(n) => {
@@ -55,49 +114,249 @@ impl StackBuilder {
         self
     }
 
+    /// Variable or list reporter that is chained with other blocks is built as
+    /// a `data_variable` or `data_listcontents` block so it keeps its place in the stack.
+    /// [`crate::validate`] reports it as a reporter used as a stack block.
     pub fn build(
         self,
         first_block_uid: &Uid,
         comment_buff: &mut HashMap<Uid, Comment>,
         target_context: &TargetContext,
+        uid_gen: &mut UidGenerator,
     ) -> HashMap<Uid, Block> {
         let mut stack_b: HashMap<Uid, Block> = HashMap::default();
         let mut self_stack_iter = self.stack.into_iter();
-        let first_block = self_stack_iter.next().unwrap().build(
+        let first_block = self_stack_iter.next().unwrap();
+        let mut self_stack_iter = self_stack_iter
+            .map(BlockBuilder::into_block_normal)
+            .peekable();
+        let first_block = match first_block {
+            // Reporter on its own is kept in its short form
+            BlockBuilder::VarList(vl) if self_stack_iter.peek().is_none() => {
+                let vl = vl.build(first_block_uid, comment_buff, target_context, uid_gen);
+                stack_b.insert(first_block_uid.clone(), Block::VarList(vl));
+                return stack_b;
+            }
+            first_block => first_block.into_block_normal(),
+        };
+        let mut first_block = first_block.build(
             first_block_uid,
             comment_buff,
             &mut stack_b,
             target_context,
+            uid_gen,
         );
+        first_block.top_level = true;
+        // Position from `set_top_block_position` is kept
+        first_block.x.get_or_insert(0.into());
+        first_block.y.get_or_insert(0.into());
+        let mut previous_block = (first_block, first_block_uid.clone());
+        for block_builder2 in self_stack_iter {
+            let block2_uid = uid_gen.generate();
+            let mut block2 = block_builder2.build(
+                &block2_uid,
+                comment_buff,
+                &mut stack_b,
+                target_context,
+                uid_gen,
+            );
+            let (mut block1, block1_uid) = previous_block;
+
+            block1.next = Some(block2_uid.clone().into_inner());
+            block2.parent = Some(block1_uid.clone().into_inner());
 
-        match first_block {
-            Block::Normal(mut first_block) => {
-                first_block.top_level = true;
-                first_block.x = Some(0.into());
-                first_block.y = Some(0.into());
-                let mut previous_block = (first_block, first_block_uid.clone());
-                for block_builder2 in self_stack_iter {
-                    let (mut block1, block1_uid) = previous_block;
-                    let block2_uid = Uid::generate();
-                    let Block::Normal(mut block2) =
-                        block_builder2.build(&block2_uid, comment_buff, &mut stack_b, target_context) else {
-                        unreachable!("BlockVarList shouldn't exist here")
-                    };
-
-                    block1.next = Some(block2_uid.clone().into_inner());
-                    block2.parent = Some(block1_uid.clone().into_inner());
-
-                    previous_block = (block2, block2_uid);
-
-                    stack_b.insert(block1_uid, Block::Normal(block1));
+            previous_block = (block2, block2_uid);
+
+            stack_b.insert(block1_uid, Block::Normal(block1));
+        }
+        stack_b.insert(previous_block.1, Block::Normal(previous_block.0));
+        stack_b
+    }
+
+    /// Reverse of [`StackBuilder::build`].
+    /// Start from `first_block_uid` and follow the `next` of each block until the end of the stack.
+    pub fn from_blocks(
+        first_block_uid: &Uid,
+        blocks: &HashMap<String, Block>,
+        comments: &HashMap<String, Comment>,
+        target_context: &TargetContext,
+    ) -> StackBuilder {
+        let mut stack_b = StackBuilder::new();
+        let mut next_uid = Some(first_block_uid.clone());
+        while let Some(uid) = next_uid.take() {
+            // Guard against `next` that loop back into the stack
+            if stack_b.stack.len() >= blocks.len() {
+                break;
+            }
+            let Some(block) = blocks.get(uid.inner()) else {
+                break;
+            };
+            let block_b = match block {
+                Block::Normal(n) => {
+                    next_uid = n.next.as_ref().map(Uid::new);
+                    BlockBuilder::Normal(BlockNormalBuilder::from_block_normal(
+                        n,
+                        blocks,
+                        comments,
+                        target_context,
+                    ))
                 }
-                stack_b.insert(previous_block.1, Block::Normal(previous_block.0));
-                stack_b
+                Block::VarList(vl) => BlockBuilder::VarList(
+                    BlockVarListBuilder::from_block_varlist(&uid, vl, comments, target_context),
+                ),
+            };
+            stack_b.stack.push(block_b);
+        }
+        stack_b
+    }
+
+    /// Reconstruct every top level stack in `blocks`.
+    /// Stacks are ordered by the uid of their first block so the result doesn't depend on map order.
+    pub fn from_target_blocks(
+        blocks: &HashMap<String, Block>,
+        comments: &HashMap<String, Comment>,
+        target_context: &TargetContext,
+    ) -> Vec<StackBuilder> {
+        let mut top_level_uids: Vec<&String> = blocks
+            .iter()
+            .filter(|(_, block)| match block {
+                Block::Normal(n) => n.top_level,
+                Block::VarList(_) => true,
+            })
+            .map(|(uid, _)| uid)
+            .collect();
+        top_level_uids.sort();
+        top_level_uids
+            .into_iter()
+            .map(|uid| StackBuilder::from_blocks(&Uid::new(uid), blocks, comments, target_context))
+            .collect()
+    }
+}
+
+/// Opcode and shape of the block. None if the opcode is unknown.
+fn block_shape(block: &BlockBuilder) -> Option<(OpCode, BlockShape)> {
+    match block {
+        BlockBuilder::Normal(n) => {
+            opcode::shape_of(n.opcode()).map(|shape| (n.opcode().clone(), shape))
+        }
+        BlockBuilder::VarList(vl) => {
+            let opcode = match vl.kind {
+                ListOrVariable::Variable => StandardOpCode::data_variable,
+                ListOrVariable::List => StandardOpCode::data_listcontents,
+            };
+            Some((opcode.into(), BlockShape::Reporter))
+        }
+    }
+}
+
+/// "stop other scripts in sprite" is the only stop option that isn't a cap block
+fn is_stop_with_next(block: &BlockBuilder) -> bool {
+    match block {
+        BlockBuilder::Normal(n) => matches!(
+            n.mutation().map(|mutation| &mutation.mutation_enum),
+            Some(BlockMutationEnum::ControlStop { hasnext: true })
+        ),
+        BlockBuilder::VarList(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blocks::{global_var, join, say, turn_right, when_flag_clicked},
+        uid::UidMode,
+    };
+
+    struct Context {
+        global_vars: HashMap<String, Uid>,
+        empty: HashMap<String, Uid>,
+    }
+
+    impl Context {
+        fn new() -> Context {
+            Context {
+                global_vars: HashMap::from([("score".to_owned(), Uid::new("score id"))]),
+                empty: HashMap::new(),
             }
-            Block::VarList(vl) => {
-                stack_b.insert(first_block_uid.clone(), Block::VarList(vl));
-                stack_b
+        }
+
+        fn target_context(&self) -> TargetContext<'_> {
+            TargetContext {
+                global_vars: &self.global_vars,
+                global_lists: &self.empty,
+                this_sprite_vars: &self.empty,
+                this_sprite_lists: &self.empty,
+                all_broadcasts: &self.empty,
             }
         }
     }
+
+    fn script() -> StackBuilder {
+        let mut script = when_flag_clicked()
+            .next(turn_right(15))
+            .next(say(join("score: ", global_var("score"))));
+        script.set_top_block_position(20., 40.);
+        script
+    }
+
+    fn build(stack: StackBuilder, first_uid: &str) -> HashMap<String, Block> {
+        let context = Context::new();
+        stack
+            .build(
+                &Uid::new(first_uid),
+                &mut HashMap::new(),
+                &context.target_context(),
+                &mut UidGenerator::new(UidMode::Sequential),
+            )
+            .into_iter()
+            .map(|(uid, block)| (uid.into_inner(), block))
+            .collect()
+    }
+
+    #[test]
+    fn from_blocks_reverses_build() {
+        let blocks = build(script(), "top");
+        let context = Context::new();
+        let stack = StackBuilder::from_blocks(
+            &Uid::new("top"),
+            &blocks,
+            &HashMap::new(),
+            &context.target_context(),
+        );
+        assert_eq!(stack, script());
+    }
+
+    #[test]
+    fn from_blocks_stops_at_next_looping_back() {
+        let mut blocks = build(script(), "top");
+        let last = blocks
+            .values_mut()
+            .find_map(|block| match block {
+                Block::Normal(n) if n.opcode == OpCode::from(StandardOpCode::looks_say) => Some(n),
+                _ => None,
+            })
+            .unwrap();
+        last.next = Some("top".to_owned());
+        let context = Context::new();
+        let stack = StackBuilder::from_blocks(
+            &Uid::new("top"),
+            &blocks,
+            &HashMap::new(),
+            &context.target_context(),
+        );
+        assert_eq!(stack.stack.len(), blocks.len());
+    }
+
+    #[test]
+    fn from_target_blocks_is_sorted_by_uid() {
+        let mut blocks = build(say("second"), "b");
+        blocks.extend(build(script(), "a"));
+        let context = Context::new();
+        let stacks =
+            StackBuilder::from_target_blocks(&blocks, &HashMap::new(), &context.target_context());
+        assert_eq!(stacks.len(), 2);
+        assert_eq!(stacks[0], script());
+        assert_eq!(stacks[1].stack.len(), 1);
+    }
 }
@@ -1,25 +1,29 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use sb_sbity::{
     asset::{Costume, Sound},
-    block::Block,
+    block::{Block, BlockInputValue, ListOrVariable},
     broadcast::Broadcast,
     comment::Comment,
     list::List,
     string_hashmap::StringHashMap,
-    target::{RotationStyle, Sprite, Stage, Target, VideoState},
+    target::{RotationStyle, Sprite, Stage, Target, TextToSpeechLanguage, VideoState},
+    value::Value,
     variable::Variable,
 };
 
 use crate::build_context::GlobalVarListContext;
 use crate::{
     asset::{CostumeBuilder, SoundBuilder},
+    block::{BlockBuilder, FieldKind, StackOrValue, VarListFrom},
     build_context::TargetContext,
+    cloud,
     comment::CommentBuilder,
     data::{ListBuilder, VariableBuilder},
+    layout,
     resource::Resource,
     stack::StackBuilder,
-    uid::Uid,
+    uid::{Uid, UidGenerator},
 };
 
 #[rustfmt::skip]
@@ -28,14 +32,18 @@ pub struct TargetBuilder {
     pub name:            String,
     pub variables:       HashMap<String, VariableBuilder>,
     pub lists:           HashMap<String, ListBuilder>,
-    pub broadcasts:      HashMap<String, Uid>,
+    /// Only names, their uids are generated when building
+    pub broadcasts:      BTreeSet<String>,
     pub block_stackes:   Vec<StackBuilder>,
-    pub comments:        HashMap<Uid, Comment>,
+    /// Built when building, so their uids come from the same [`UidGenerator`]
+    pub comments:        Vec<CommentBuilder>,
     pub costumes:        Vec<CostumeBuilder>,
     pub sounds:          Vec<SoundBuilder>,
     pub current_costume: u64,
     pub layer_order:     u64,
     pub volume:          f64,
+    /// Arrange the scripts when building, see [`crate::layout`]
+    pub auto_layout:     bool,
 }
 
 impl TargetBuilder {
@@ -69,18 +77,42 @@ impl TargetBuilder {
     }
 
     pub fn add_broadcast<S: Into<String>>(&mut self, name: S) -> &mut Self {
-        self.broadcasts.insert(name.into(), Uid::generate());
+        self.broadcasts.insert(name.into());
         self
     }
 
+    /// Every broadcast that the blocks send or receive, declared or not
+    pub fn used_broadcasts(&self) -> BTreeSet<String> {
+        let mut refs = References::default();
+        for stack in &self.block_stackes {
+            refs.collect(&stack.stack);
+        }
+        refs.broadcasts
+    }
+
     pub fn add_block_stack(&mut self, stack_builder: StackBuilder) -> &mut Self {
         self.block_stackes.push(stack_builder);
         self
     }
 
+    /// Add every script from [`crate::scratch!`] at once
+    pub fn add_block_stacks<I: IntoIterator<Item = StackBuilder>>(
+        &mut self,
+        stack_builders: I,
+    ) -> &mut Self {
+        self.block_stackes.extend(stack_builders);
+        self
+    }
+
+    /// Arrange the scripts in columns when building, like "Clean up blocks" in the editor.
+    /// Positions from [`StackBuilder::set_top_block_position`] are overwritten.
+    pub fn set_auto_layout(&mut self, auto_layout: bool) -> &mut Self {
+        self.auto_layout = auto_layout;
+        self
+    }
+
     pub fn add_comment(&mut self, comment_builder: CommentBuilder) -> &mut Self {
-        let comment = comment_builder.build();
-        self.comments.insert(Uid::generate(), comment);
+        self.comments.push(comment_builder);
         self
     }
 
@@ -96,48 +128,83 @@ impl TargetBuilder {
 
     /// When global_varlist_buf suppose to be none when the Stage itself is building.
     /// The .1 return value is going to return Some when stage itself is also building.
+    ///
+    /// `all_broadcasts` needs to have every broadcast in [`TargetBuilder::broadcasts`].
     pub fn build(
         self,
         res_buf: &mut Vec<Resource>,
         global_varlist_ctx: Option<&GlobalVarListContext>,
         all_broadcasts: &HashMap<String, Uid>,
+        uid_gen: &mut UidGenerator,
     ) -> (Target, Option<GlobalVarListContext>) {
         let TargetBuilder {
             name,
             variables,
             lists,
             broadcasts,
-            block_stackes,
+            mut block_stackes,
             comments,
             costumes,
             sounds,
             current_costume,
             layer_order,
             volume,
+            auto_layout,
         } = self;
+        if auto_layout {
+            layout::layout(&mut block_stackes);
+        }
+        // Sorted by name so uids are generated in the same order every build
+        let mut variables: Vec<(String, VariableBuilder)> = variables.into_iter().collect();
+        variables.sort_by(|(a, _), (b, _)| a.cmp(b));
         let variables: HashMap<String, Variable> = variables
             .into_iter()
             .map(|(var_name, var_builder)| {
-                let (var, uid) = var_builder.build(var_name);
+                // Scratch only treats it as a cloud variable when it has the prefix
+                let var_name = match var_builder.is_cloud_variable {
+                    true => cloud::cloud_name(&var_name),
+                    false => var_name,
+                };
+                let (var, uid) = var_builder.build(var_name, uid_gen);
                 (uid.into_inner(), var)
             })
             .collect();
+        let mut lists: Vec<(String, ListBuilder)> = lists.into_iter().collect();
+        lists.sort_by(|(a, _), (b, _)| a.cmp(b));
         let lists: HashMap<String, List> = lists
             .into_iter()
             .map(|(list_name, list_builder)| {
-                let (list, uid) = list_builder.build(list_name);
+                let (list, uid) = list_builder.build(list_name, uid_gen);
                 (uid.into_inner(), list)
             })
             .collect();
         let broadcasts: HashMap<String, Broadcast> = broadcasts
             .into_iter()
-            .map(|(name, uid)| (uid.into_inner(), Broadcast { name }))
+            .map(|name| {
+                let uid = all_broadcasts
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or_else(|| uid_gen.generate());
+                (uid.into_inner(), Broadcast { name })
+            })
             .collect();
 
-        let mut comments = comments;
+        let mut comments: HashMap<Uid, Comment> = comments
+            .into_iter()
+            .map(|comment_builder| (uid_gen.generate(), comment_builder.build()))
+            .collect();
+        // Cloud variable is also found without its prefix
         let variable_ctx: HashMap<String, Uid> = variables
             .iter()
-            .map(|(uid, var)| (var.name.clone(), Uid::new(uid)))
+            .flat_map(|(uid, var)| {
+                let unprefixed = match var.is_cloud_variable {
+                    true => var.name.strip_prefix(cloud::CLOUD_PREFIX),
+                    false => None,
+                };
+                std::iter::once(var.name.as_str())
+                    .chain(unprefixed)
+                    .map(move |name| (name.to_owned(), Uid::new(uid)))
+            })
             .collect();
         let list_ctx: HashMap<String, Uid> = lists
             .iter()
@@ -147,7 +214,7 @@ impl TargetBuilder {
             .into_iter()
             .flat_map(|stack_builder| {
                 let builded_stack = stack_builder.build(
-                    &Uid::generate(),
+                    &uid_gen.generate(),
                     &mut comments,
                     &match global_varlist_ctx {
                         Some(global_varlist_ctx) => TargetContext {
@@ -165,6 +232,7 @@ impl TargetBuilder {
                             all_broadcasts,
                         },
                     },
+                    uid_gen,
                 );
                 builded_stack
                     .into_iter()
@@ -216,14 +284,15 @@ impl Default for TargetBuilder {
             name:            "".to_owned(),
             variables:       HashMap::default(),
             lists:           HashMap::default(),
-            broadcasts:      HashMap::default(),
+            broadcasts:      BTreeSet::default(),
             block_stackes:   Vec::default(),
-            comments:        HashMap::default(),
+            comments:        Vec::default(),
             costumes:        Vec::default(),
             sounds:          Vec::default(),
             current_costume: 0,
             layer_order:     0,
             volume:          100.,
+            auto_layout:     false,
         }
     }
 }
@@ -235,9 +304,8 @@ pub struct StageBuilder {
     pub tempo:                   i64,
     pub video_state:             VideoState,
     pub video_transparency:      i64,
-    // Not availiable yet.
-    // TODO: do this.
-    // text_to_speech_language: (),
+    /// Language of the Text to Speech extension. None is the editor's language.
+    pub text_to_speech_language: Option<TextToSpeechLanguage>,
 }
 
 impl StageBuilder {
@@ -246,6 +314,16 @@ impl StageBuilder {
         self
     }
 
+    /// Cloud variable is named with the [`cloud::CLOUD_PREFIX`] when building,
+    /// blocks can refer to it either with or without the prefix.
+    pub fn add_cloud_variable<S: Into<String>>(&mut self, name: S, value: f64) -> &mut Self {
+        self.target.add_variable(
+            name,
+            VariableBuilder::new_cloud_variable(Value::Number(value.into())),
+        );
+        self
+    }
+
     pub fn set_tempo(&mut self, tempo: i64) -> &mut Self {
         self.tempo = tempo;
         self
@@ -261,18 +339,29 @@ impl StageBuilder {
         self
     }
 
+    pub fn set_text_to_speech_language(
+        &mut self,
+        text_to_speech_language: Option<TextToSpeechLanguage>,
+    ) -> &mut Self {
+        self.text_to_speech_language = text_to_speech_language;
+        self
+    }
+
     pub fn build(
         self,
         res_buf: &mut Vec<Resource>,
         all_broadcasts: &HashMap<String, Uid>,
+        uid_gen: &mut UidGenerator,
     ) -> (Stage, GlobalVarListContext) {
         let StageBuilder {
             target,
             tempo,
             video_state,
             video_transparency,
+            text_to_speech_language,
         } = self;
-        let (target, Some(global_var_list)) = target.build(res_buf, None, all_broadcasts) else {
+        let (target, Some(global_var_list)) = target.build(res_buf, None, all_broadcasts, uid_gen)
+        else {
             panic!("stage suppose to return what global var they had");
         };
         let stage = Stage {
@@ -280,7 +369,7 @@ impl StageBuilder {
             tempo: tempo.into(),
             video_state,
             video_transparency: video_transparency.into(),
-            text_to_speech_language: None,
+            text_to_speech_language,
             is_stage: true,
         };
         (stage, global_var_list)
@@ -297,6 +386,7 @@ impl Default for StageBuilder {
             tempo: 60,
             video_state: VideoState::On,
             video_transparency: 50,
+            text_to_speech_language: None,
         }
     }
 }
@@ -366,6 +456,7 @@ impl SpriteBuilder {
         res_buf: &mut Vec<Resource>,
         global_varlist_buf: &GlobalVarListContext,
         all_broadcasts: &HashMap<String, Uid>,
+        uid_gen: &mut UidGenerator,
     ) -> Sprite {
         let SpriteBuilder {
             target,
@@ -379,7 +470,7 @@ impl SpriteBuilder {
         } = self;
         Sprite {
             target: target
-                .build(res_buf, Some(global_varlist_buf), all_broadcasts)
+                .build(res_buf, Some(global_varlist_buf), all_broadcasts, uid_gen)
                 .0,
             visible,
             x: x.into(),
@@ -391,6 +482,156 @@ impl SpriteBuilder {
             is_stage: false,
         }
     }
+
+    /// Build the sprite on its own without the stage, ex. for `.sprite3`.
+    ///
+    /// Global variables and lists used by the sprite's blocks are handled by `stage_references`.
+    /// Broadcasts used by the blocks are added to [`TargetBuilder::broadcasts`]
+    /// and Scratch moves them to the stage when the sprite is loaded.
+    pub fn build_standalone(
+        mut self,
+        stage_references: StageReferences,
+        res_buf: &mut Vec<Resource>,
+        uid_gen: &mut UidGenerator,
+    ) -> Sprite {
+        let mut refs = References::default();
+        for stack in &self.target.block_stackes {
+            refs.collect(&stack.stack);
+        }
+        let target = &mut self.target;
+        let global_varlist_ctx = match stage_references {
+            StageReferences::ByName => Some(GlobalVarListContext {
+                vars: refs
+                    .global_variables
+                    .into_iter()
+                    .map(|name| (name, uid_gen.generate()))
+                    .collect(),
+                lists: refs
+                    .global_lists
+                    .into_iter()
+                    .map(|name| (name, uid_gen.generate()))
+                    .collect(),
+            }),
+            StageReferences::Local => {
+                for name in refs.global_variables {
+                    target
+                        .variables
+                        .entry(name)
+                        .or_insert_with(|| VariableBuilder::new(Value::Number(0_i64.into())));
+                }
+                for name in refs.global_lists {
+                    target
+                        .lists
+                        .entry(name)
+                        .or_insert_with(|| ListBuilder::new(vec![]));
+                }
+                None
+            }
+        };
+        target.broadcasts.extend(refs.broadcasts);
+        let all_broadcasts: HashMap<String, Uid> = target
+            .broadcasts
+            .iter()
+            .map(|name| (name.clone(), uid_gen.generate()))
+            .collect();
+
+        let SpriteBuilder {
+            target,
+            visible,
+            x,
+            y,
+            size,
+            direction,
+            draggable,
+            rotation_style,
+        } = self;
+        Sprite {
+            // Without the stage's context global references point to the sprite's own variables
+            target: target
+                .build(
+                    res_buf,
+                    global_varlist_ctx.as_ref(),
+                    &all_broadcasts,
+                    uid_gen,
+                )
+                .0,
+            visible,
+            x: x.into(),
+            y: y.into(),
+            size: size.into(),
+            direction: direction.into(),
+            draggable,
+            rotation_style,
+            is_stage: false,
+        }
+    }
+}
+
+/// What [`SpriteBuilder::build_standalone`] does with the stage's variables and lists
+/// that the sprite uses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StageReferences {
+    /// Referred to by name with ids that aren't in the sprite.
+    /// When the sprite is loaded, Scratch points them to the project's variable or list
+    /// with the same name, or makes a new one when there isn't.
+    #[default]
+    ByName,
+    /// Become the sprite's own, starting at 0 or empty since their values are on the stage.
+    /// Scratch renames them when the project already has ones with the same name, ex. `Sprite1: score`,
+    /// so the sprite doesn't share them with the project.
+    Local,
+}
+
+/// Names of the stage's variables, lists and broadcasts that blocks refer to
+#[rustfmt::skip]
+#[derive(Default)]
+struct References {
+    global_variables: BTreeSet<String>,
+    global_lists:     BTreeSet<String>,
+    broadcasts:       BTreeSet<String>,
+}
+
+impl References {
+    fn collect(&mut self, blocks: &[BlockBuilder]) {
+        for block in blocks {
+            let b = match block {
+                BlockBuilder::VarList(vl) => {
+                    // Ones resolved by scope may be the stage's, the sprite's own are kept below
+                    if vl.from != VarListFrom::Sprite {
+                        let names = match vl.kind {
+                            ListOrVariable::Variable => &mut self.global_variables,
+                            ListOrVariable::List => &mut self.global_lists,
+                        };
+                        names.insert(vl.name.clone());
+                    }
+                    continue;
+                }
+                BlockBuilder::Normal(b) => b,
+            };
+            for field in b.fields().values() {
+                let names = match field.kind {
+                    FieldKind::GlobalVariable | FieldKind::Variable => &mut self.global_variables,
+                    FieldKind::GlobalList | FieldKind::List => &mut self.global_lists,
+                    FieldKind::Broadcast => &mut self.broadcasts,
+                    _ => continue,
+                };
+                names.insert(field.value.clone());
+            }
+            let values = b
+                .inputs()
+                .values()
+                .flat_map(|input| input.values.iter().flatten());
+            for value in values {
+                match value {
+                    StackOrValue::Stack(stack) => self.collect(&stack.stack),
+                    StackOrValue::Value(BlockInputValue::Broadcast { name, .. }) => {
+                        self.broadcasts.insert(name.clone());
+                    }
+                    StackOrValue::Value(_) => {}
+                }
+            }
+        }
+    }
 }
 
 impl Default for SpriteBuilder {
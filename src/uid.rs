@@ -1,6 +1,6 @@
-use rand::prelude::*;
+use std::collections::HashSet;
 
-// TODO: no collision
+use rand::prelude::*;
 
 const SOUP: &str =
     "!#%()*+,-./:;=?@[]^_`{|}~ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
@@ -11,7 +11,10 @@ const UID_LEN: usize = 20;
 pub struct Uid(String);
 
 pub fn uid() -> String {
-    let mut rng = thread_rng();
+    uid_from_rng(&mut thread_rng())
+}
+
+fn uid_from_rng<R: Rng + ?Sized>(rng: &mut R) -> String {
     let uid = (0..UID_LEN)
         .map(|_| SOUP.as_bytes()[rng.gen_range(0..SOUP_LEN)])
         .collect();
@@ -19,6 +22,44 @@ pub fn uid() -> String {
     unsafe { String::from_utf8_unchecked(uid) }
 }
 
+/// SplitMix64, written here so a seed gives the same uids no matter the version of `rand`.
+/// Not for anything that needs to be unpredictable.
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    fn uid(&mut self) -> String {
+        let uid = (0..UID_LEN)
+            .map(|_| SOUP.as_bytes()[(self.next_u64() % SOUP_LEN as u64) as usize])
+            .collect();
+        // SAFETY: We're taking from 'SOUP' const and they're all valid utf8 characters
+        unsafe { String::from_utf8_unchecked(uid) }
+    }
+}
+
+/// `n` written in base [`SOUP_LEN`] with 'SOUP' as digits
+fn sequential_uid(mut n: u64) -> String {
+    let mut uid = vec![];
+    loop {
+        uid.push(SOUP.as_bytes()[(n % SOUP_LEN as u64) as usize]);
+        n /= SOUP_LEN as u64;
+        if n == 0 {
+            break;
+        }
+    }
+    uid.reverse();
+    // SAFETY: We're taking from 'SOUP' const and they're all valid utf8 characters
+    unsafe { String::from_utf8_unchecked(uid) }
+}
+
 impl Uid {
     pub fn generate() -> Uid {
         Uid(uid())
@@ -36,3 +77,188 @@ impl Uid {
         self.0
     }
 }
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UidMode {
+    /// Different uids on every build
+    #[default]
+    Random,
+    /// Same project with the same seed always gets the same uids
+    Seeded(u64),
+    /// Short uids counting up from the start of the build
+    Sequential,
+}
+
+#[derive(Debug, Clone)]
+enum UidSource {
+    Random,
+    Seeded(SplitMix64),
+    Sequential(u64),
+}
+
+/// Gives out [`Uid`] while building.
+///
+/// Uids of the whole project are generated in the same order on every build,
+/// so [`UidMode::Seeded`] and [`UidMode::Sequential`] make reproducible projects.
+#[derive(Debug, Clone)]
+pub struct UidGenerator {
+    source: UidSource,
+    /// Every uid given out so far. Only tracked when uniqueness is guaranteed.
+    issued: Option<HashSet<Uid>>,
+}
+
+impl UidGenerator {
+    pub fn new(mode: UidMode) -> UidGenerator {
+        UidGenerator {
+            source: match mode {
+                UidMode::Random => UidSource::Random,
+                UidMode::Seeded(seed) => UidSource::Seeded(SplitMix64(seed)),
+                UidMode::Sequential => UidSource::Sequential(0),
+            },
+            issued: None,
+        }
+    }
+
+    /// Also remember every uid it has given out and never give out the same one twice.
+    pub fn new_unique(mode: UidMode) -> UidGenerator {
+        UidGenerator {
+            issued: Some(HashSet::new()),
+            ..UidGenerator::new(mode)
+        }
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.issued.is_some()
+    }
+
+    pub fn generate(&mut self) -> Uid {
+        loop {
+            let uid = Uid(match &mut self.source {
+                UidSource::Random => uid(),
+                UidSource::Seeded(rng) => rng.uid(),
+                UidSource::Sequential(n) => {
+                    *n += 1;
+                    sequential_uid(*n - 1)
+                }
+            });
+            match &mut self.issued {
+                Some(issued) => {
+                    if issued.insert(uid.clone()) {
+                        return uid;
+                    }
+                }
+                None => return uid,
+            }
+        }
+    }
+
+    /// Mark `uid` as already given out, so it won't be generated later.
+    /// Does nothing when uniqueness isn't tracked.
+    ///
+    /// Return false if `uid` is already given out.
+    pub fn reserve(&mut self, uid: Uid) -> bool {
+        match &mut self.issued {
+            Some(issued) => issued.insert(uid),
+            None => true,
+        }
+    }
+}
+
+impl Default for UidGenerator {
+    fn default() -> Self {
+        UidGenerator::new(UidMode::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comment::CommentBuilder,
+        data::{ListBuilder, VariableBuilder},
+        export::{to_json, write_sprite3_with_options, ZipOptions},
+        monitor::MonitorBuilder,
+        project::ProjectBuilder,
+        scratch,
+        target::{SpriteBuilder, StageReferences, TargetBuilder},
+    };
+    use sb_sbity::value::Value;
+
+    // scratch! pushes each block into a new Vec
+    #[allow(clippy::vec_init_then_push)]
+    fn project(uid_mode: UidMode) -> ProjectBuilder {
+        let mut stage = TargetBuilder::default();
+        stage
+            .add_variable("score", VariableBuilder::new(Value::Number(0_i64.into())))
+            .add_list("names", ListBuilder::new(vec![]))
+            .add_broadcast("start");
+        let mut sprite = TargetBuilder::default();
+        sprite
+            .set_name("Cat")
+            .add_variable("speed", VariableBuilder::new(Value::Number(2_i64.into())))
+            .add_comment(CommentBuilder::new("floating"))
+            .add_block_stacks(
+                scratch! {
+                    when flag clicked {
+                        broadcast "start";
+                        repeat 10 {
+                            change var "score" by 1;
+                            move (sprite_var "speed");
+                        }
+                        say "hi";
+                    }
+                    when I receive "go" {
+                        say (join (var "score") "!");
+                    }
+                }
+                .unwrap(),
+            );
+        let mut project = ProjectBuilder::default();
+        project
+            .set_uid_mode(uid_mode)
+            .add_sprite(SpriteBuilder::default().set_target(sprite).to_owned())
+            .add_monitor(MonitorBuilder::variable("score"))
+            .add_monitor(MonitorBuilder::reporter("motion_xposition"));
+        project.stage_builder.set_set_target(stage);
+        project
+    }
+
+    #[test]
+    fn seeded_and_sequential_builds_are_reproducible() {
+        for uid_mode in [UidMode::Seeded(42), UidMode::Sequential] {
+            let (first, _) = to_json(project(uid_mode));
+            let (second, _) = to_json(project(uid_mode));
+            assert_eq!(first, second, "{uid_mode:?}");
+        }
+    }
+
+    #[test]
+    fn seeded_sprite3_is_reproducible() {
+        let sprite3 = || {
+            let sprite = project(UidMode::Random).sprite_builders.remove(0);
+            let mut zip = std::io::Cursor::new(vec![]);
+            write_sprite3_with_options(
+                &mut zip,
+                sprite,
+                StageReferences::ByName,
+                UidMode::Seeded(7),
+                ZipOptions::default(),
+            )
+            .unwrap();
+            zip.into_inner()
+        };
+        assert_eq!(sprite3(), sprite3());
+    }
+
+    #[test]
+    fn seeded_uids_are_fixed() {
+        // Known first outputs of SplitMix64 seeded with 0
+        let mut rng = SplitMix64(0);
+        assert_eq!(rng.next_u64(), 0xE220A8397B1DCDAF);
+        assert_eq!(rng.next_u64(), 0x6E789E6AA1B965F4);
+        let mut gen = UidGenerator::new(UidMode::Seeded(0));
+        let uid = gen.generate();
+        assert_eq!(uid.inner(), SplitMix64(0).uid());
+        assert_eq!(uid.inner().len(), UID_LEN);
+    }
+}
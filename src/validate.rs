@@ -0,0 +1,792 @@
+//! Checks for mistakes that make Scratch refuse to load the project,
+//! or load it but silently break some of the scripts.
+
+use std::collections::{HashMap, HashSet};
+
+use sb_sbity::{
+    block::{BlockInputValue, ListOrVariable},
+    value::{OpCode, Value},
+};
+
+use crate::{
+    block::{
+        BlockBuilder, BlockFieldBuilder, BlockNormalBuilder, FieldKind, StackOrValue, VarListFrom,
+    },
+    cloud,
+    data::VariableBuilder,
+    monitor::MonitorSource,
+    opcode::{self, StandardOpCode},
+    project::ProjectBuilder,
+    schema::{self, SchemaViolation},
+    stack::StackBuilder,
+    target::TargetBuilder,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Scratch won't load the project or the project won't work as intended
+    Error,
+    /// Project will load but something is most likely wrong
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticKind {
+    MissingCostume,
+    CostumeOutOfRange {
+        current_costume: u64,
+        costume_count: usize,
+    },
+    LayerOrderCollision {
+        layer_order: u64,
+        other_target: String,
+    },
+    /// Stage's layer order that isn't 0
+    StageLayerOrder(u64),
+    UnresolvedVariable(String),
+    UnresolvedList(String),
+    /// Monitor of a variable or list that isn't in its sprite or the stage
+    UnresolvedMonitor(MonitorSource),
+    /// Variable resolved by scope that both the sprite and the stage have
+    AmbiguousVariable(String),
+    /// List resolved by scope that both the sprite and the stage have
+    AmbiguousList(String),
+    /// Sprite's variable with the same name as the stage's one, which Scratch's editor doesn't allow
+    ShadowedVariable(String),
+    /// Sprite's list with the same name as the stage's one
+    ShadowedList(String),
+    /// Cloud variable that is in a sprite, Scratch only keeps them on the stage
+    CloudVariableNotOnStage(String),
+    /// Cloud variable that doesn't hold a number
+    CloudVariableNotNumber(String),
+    /// More cloud variables than [`cloud::MAX_CLOUD_VARIABLES`]
+    TooManyCloudVariables(usize),
+    DuplicateSpriteName,
+    /// Reporter that is chained with other blocks or put inside a C block
+    ReporterAsStackBlock(OpCode),
+    /// Block that doesn't match [`crate::schema`]
+    Schema {
+        opcode: OpCode,
+        violation: SchemaViolation,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Name of the target this is found in
+    pub target: String,
+    pub kind: DiagnosticKind,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match &self.kind {
+            DiagnosticKind::Schema {
+                violation: SchemaViolation::MissingField(_),
+                ..
+            } => Severity::Error,
+            DiagnosticKind::Schema { .. } => Severity::Warning,
+            DiagnosticKind::ShadowedVariable(_) | DiagnosticKind::ShadowedList(_) => {
+                Severity::Warning
+            }
+            _ => Severity::Error,
+        }
+    }
+
+    /// Variable or list that builds with an `__unknown__` id, or that is resolved by scope
+    /// and may not be the one that's meant. [`crate::export::export`] refuses these.
+    pub fn is_unresolved_reference(&self) -> bool {
+        matches!(
+            self.kind,
+            DiagnosticKind::UnresolvedVariable(_)
+                | DiagnosticKind::UnresolvedList(_)
+                | DiagnosticKind::AmbiguousVariable(_)
+                | DiagnosticKind::AmbiguousList(_)
+        )
+    }
+
+    /// What even plain [`crate::export::export`] refuses, [`Diagnostic::is_unresolved_reference`]
+    /// and cloud variable in a sprite that Scratch wouldn't keep.
+    pub fn is_refused_by_export(&self) -> bool {
+        self.is_unresolved_reference()
+            || matches!(self.kind, DiagnosticKind::CloudVariableNotOnStage(_))
+    }
+}
+
+impl std::fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticKind::MissingCostume => write!(f, "doesn't have any costume"),
+            DiagnosticKind::CostumeOutOfRange {
+                current_costume,
+                costume_count,
+            } => write!(
+                f,
+                "current costume {current_costume} is out of range of {costume_count} costume(s)"
+            ),
+            DiagnosticKind::LayerOrderCollision {
+                layer_order,
+                other_target,
+            } => write!(
+                f,
+                "layer order {layer_order} is already used by `{other_target}`"
+            ),
+            DiagnosticKind::StageLayerOrder(layer_order) => {
+                write!(f, "stage's layer order is {layer_order} instead of 0")
+            }
+            DiagnosticKind::UnresolvedVariable(name) => write!(f, "unknown variable `{name}`"),
+            DiagnosticKind::UnresolvedList(name) => write!(f, "unknown list `{name}`"),
+            DiagnosticKind::UnresolvedMonitor(source) => match source {
+                MonitorSource::Variable(name) => write!(f, "monitor of unknown variable `{name}`"),
+                MonitorSource::List(name) => write!(f, "monitor of unknown list `{name}`"),
+                MonitorSource::Reporter { opcode, .. } => {
+                    write!(f, "monitor of unknown reporter `{opcode}`")
+                }
+            },
+            DiagnosticKind::AmbiguousVariable(name) => {
+                write!(f, "variable `{name}` is both in the sprite and the stage")
+            }
+            DiagnosticKind::AmbiguousList(name) => {
+                write!(f, "list `{name}` is both in the sprite and the stage")
+            }
+            DiagnosticKind::ShadowedVariable(name) => {
+                write!(f, "variable `{name}` shadows the stage's variable")
+            }
+            DiagnosticKind::ShadowedList(name) => {
+                write!(f, "list `{name}` shadows the stage's list")
+            }
+            DiagnosticKind::CloudVariableNotOnStage(name) => {
+                write!(f, "cloud variable `{name}` isn't on the stage")
+            }
+            DiagnosticKind::CloudVariableNotNumber(name) => {
+                write!(f, "cloud variable `{name}` doesn't hold a number")
+            }
+            DiagnosticKind::TooManyCloudVariables(count) => write!(
+                f,
+                "{count} cloud variables is more than the {} allowed",
+                cloud::MAX_CLOUD_VARIABLES
+            ),
+            DiagnosticKind::DuplicateSpriteName => write!(f, "sprite name is used more than once"),
+            DiagnosticKind::ReporterAsStackBlock(opcode) => {
+                write!(f, "reporter `{opcode}` is used as a stack block")
+            }
+            DiagnosticKind::Schema { opcode, violation } => write!(f, "`{opcode}` {violation}"),
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.target, self.kind)
+    }
+}
+
+pub fn validate(project: &ProjectBuilder) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let stage = &project.stage_builder.target;
+
+    let cloud_variables = stage
+        .variables
+        .values()
+        .filter(|var| var.is_cloud_variable)
+        .count();
+    if cloud_variables > cloud::MAX_CLOUD_VARIABLES {
+        diagnostics.push(Diagnostic {
+            target: stage.name.clone(),
+            kind: DiagnosticKind::TooManyCloudVariables(cloud_variables),
+        });
+    }
+    if stage.layer_order != 0 {
+        diagnostics.push(Diagnostic {
+            target: stage.name.clone(),
+            kind: DiagnosticKind::StageLayerOrder(stage.layer_order),
+        });
+    }
+    TargetChecker {
+        target: stage,
+        stage,
+        diagnostics: &mut diagnostics,
+    }
+    .check();
+
+    let mut layer_orders: HashMap<u64, &str> =
+        HashMap::from([(stage.layer_order, stage.name.as_str())]);
+    let mut sprite_names: HashSet<&str> = HashSet::new();
+    for sprite in &project.sprite_builders {
+        let target = &sprite.target;
+        match layer_orders.get(&target.layer_order) {
+            Some(other_target) => diagnostics.push(Diagnostic {
+                target: target.name.clone(),
+                kind: DiagnosticKind::LayerOrderCollision {
+                    layer_order: target.layer_order,
+                    other_target: (*other_target).to_owned(),
+                },
+            }),
+            None => {
+                layer_orders.insert(target.layer_order, &target.name);
+            }
+        }
+        if !sprite_names.insert(&target.name) {
+            diagnostics.push(Diagnostic {
+                target: target.name.clone(),
+                kind: DiagnosticKind::DuplicateSpriteName,
+            });
+        }
+        TargetChecker {
+            target,
+            stage,
+            diagnostics: &mut diagnostics,
+        }
+        .check();
+    }
+
+    // Same lookup as `MonitorBuilder::build`, the sprite and then the stage
+    for monitor in &project.monitors {
+        let sprite = monitor.sprite_name.as_ref().and_then(|sprite_name| {
+            project
+                .sprite_builders
+                .iter()
+                .map(|sprite| &sprite.target)
+                .find(|target| &target.name == sprite_name)
+        });
+        let mut owners = sprite.into_iter().chain([stage]);
+        let is_found = match &monitor.source {
+            MonitorSource::Variable(name) => owners.any(|target| has_variable(target, name)),
+            MonitorSource::List(name) => owners.any(|target| target.lists.contains_key(name)),
+            MonitorSource::Reporter { .. } => true,
+        };
+        if !is_found {
+            diagnostics.push(Diagnostic {
+                target: sprite.unwrap_or(stage).name.clone(),
+                kind: DiagnosticKind::UnresolvedMonitor(monitor.source.clone()),
+            });
+        }
+    }
+    diagnostics
+}
+
+struct TargetChecker<'a> {
+    target: &'a TargetBuilder,
+    stage: &'a TargetBuilder,
+    diagnostics: &'a mut Vec<Diagnostic>,
+}
+
+impl<'a> TargetChecker<'a> {
+    fn report(&mut self, kind: DiagnosticKind) {
+        self.diagnostics.push(Diagnostic {
+            target: self.target.name.clone(),
+            kind,
+        });
+    }
+
+    fn check(mut self) {
+        let target = self.target;
+        if target.costumes.is_empty() {
+            self.report(DiagnosticKind::MissingCostume);
+        } else if target.current_costume as usize >= target.costumes.len() {
+            self.report(DiagnosticKind::CostumeOutOfRange {
+                current_costume: target.current_costume,
+                costume_count: target.costumes.len(),
+            });
+        }
+        let mut cloud_variables: Vec<(&String, &VariableBuilder)> = target
+            .variables
+            .iter()
+            .filter(|(_, var)| var.is_cloud_variable)
+            .collect();
+        cloud_variables.sort_by_key(|(name, _)| *name);
+        for (name, var) in cloud_variables {
+            if !self.is_stage() {
+                self.report(DiagnosticKind::CloudVariableNotOnStage(name.clone()));
+            }
+            let is_number = matches!(&var.value, Value::Number(_))
+                || matches!(&var.value, Value::Text(text) if text.parse::<f64>().is_ok());
+            if !is_number {
+                self.report(DiagnosticKind::CloudVariableNotNumber(name.clone()));
+            }
+        }
+        if !self.is_stage() {
+            let mut shadowed: Vec<DiagnosticKind> = target
+                .variables
+                .keys()
+                .filter(|name| self.stage.variables.contains_key(*name))
+                .map(|name| DiagnosticKind::ShadowedVariable(name.clone()))
+                .chain(
+                    target
+                        .lists
+                        .keys()
+                        .filter(|name| self.stage.lists.contains_key(*name))
+                        .map(|name| DiagnosticKind::ShadowedList(name.clone())),
+                )
+                .collect();
+            // HashMap order isn't stable
+            shadowed.sort_by_key(|kind| kind.to_string());
+            for kind in shadowed {
+                self.report(kind);
+            }
+        }
+        for stack in &target.block_stackes {
+            self.check_stack(stack, false);
+        }
+    }
+
+    fn is_stage(&self) -> bool {
+        std::ptr::eq(self.target, self.stage)
+    }
+
+    /// Reporter is only allowed when it's alone and not inside a C block
+    fn check_stack(&mut self, stack: &StackBuilder, is_substack: bool) {
+        let must_be_stack_blocks = is_substack || stack.stack.len() > 1;
+        for block in &stack.stack {
+            match block {
+                BlockBuilder::Normal(n) => {
+                    if must_be_stack_blocks && is_reporter(n.opcode()) {
+                        self.report(DiagnosticKind::ReporterAsStackBlock(n.opcode().clone()));
+                    }
+                    self.check_block_normal(n);
+                }
+                BlockBuilder::VarList(vl) => {
+                    if must_be_stack_blocks {
+                        let opcode = match vl.kind {
+                            ListOrVariable::Variable => StandardOpCode::data_variable,
+                            ListOrVariable::List => StandardOpCode::data_listcontents,
+                        };
+                        self.report(DiagnosticKind::ReporterAsStackBlock(opcode.into()));
+                    }
+                    self.check_varlist(&vl.kind, vl.from, &vl.name);
+                }
+            }
+        }
+    }
+
+    fn check_block_normal(&mut self, block: &BlockNormalBuilder) {
+        for violation in schema::check_block(block) {
+            self.report(DiagnosticKind::Schema {
+                opcode: block.opcode().clone(),
+                violation,
+            });
+        }
+        for (key, input) in block.inputs() {
+            let is_substack = key.starts_with("SUBSTACK");
+            for value in input.values.iter().flatten() {
+                match value {
+                    StackOrValue::Stack(stack) => self.check_stack(stack, is_substack),
+                    StackOrValue::Value(BlockInputValue::Variable { name, .. }) => {
+                        if !has_variable(self.target, name) && !has_variable(self.stage, name) {
+                            self.report(DiagnosticKind::UnresolvedVariable(name.clone()));
+                        }
+                    }
+                    StackOrValue::Value(BlockInputValue::List { name, .. }) => {
+                        if !self.target.lists.contains_key(name)
+                            && !self.stage.lists.contains_key(name)
+                        {
+                            self.report(DiagnosticKind::UnresolvedList(name.clone()));
+                        }
+                    }
+                    StackOrValue::Value(_) => {}
+                }
+            }
+        }
+        for field in block.fields().values() {
+            self.check_field(field);
+        }
+    }
+
+    fn check_field(&mut self, field: &BlockFieldBuilder) {
+        let name = &field.value;
+        let (is_found, unresolved): (bool, fn(String) -> DiagnosticKind) = match field.kind {
+            // Broadcasts used by blocks are declared when building so they're never unresolved
+            FieldKind::NoRef | FieldKind::NoRefMaybe | FieldKind::Broadcast => return,
+            FieldKind::Variable => return self.check_scope(&ListOrVariable::Variable, name),
+            FieldKind::List => return self.check_scope(&ListOrVariable::List, name),
+            FieldKind::SpriteVariable => (
+                has_variable(self.target, name),
+                DiagnosticKind::UnresolvedVariable,
+            ),
+            FieldKind::GlobalVariable => (
+                has_variable(self.stage, name),
+                DiagnosticKind::UnresolvedVariable,
+            ),
+            FieldKind::SpriteList => (
+                self.target.lists.contains_key(name),
+                DiagnosticKind::UnresolvedList,
+            ),
+            FieldKind::GlobalList => (
+                self.stage.lists.contains_key(name),
+                DiagnosticKind::UnresolvedList,
+            ),
+        };
+        if !is_found {
+            self.report(unresolved(name.clone()));
+        }
+    }
+
+    fn check_varlist(&mut self, kind: &ListOrVariable, from: VarListFrom, name: &str) {
+        let owner = match from {
+            VarListFrom::Sprite => self.target,
+            VarListFrom::Global => self.stage,
+            VarListFrom::Scope => return self.check_scope(kind, name),
+        };
+        match kind {
+            ListOrVariable::Variable => {
+                if !has_variable(owner, name) {
+                    self.report(DiagnosticKind::UnresolvedVariable(name.to_owned()));
+                }
+            }
+            ListOrVariable::List => {
+                if !owner.lists.contains_key(name) {
+                    self.report(DiagnosticKind::UnresolvedList(name.to_owned()));
+                }
+            }
+        }
+    }
+
+    /// Must be in either the sprite or the stage but not both
+    fn check_scope(&mut self, kind: &ListOrVariable, name: &str) {
+        let (in_sprite, in_stage) = match kind {
+            ListOrVariable::Variable => (
+                has_variable(self.target, name),
+                has_variable(self.stage, name),
+            ),
+            ListOrVariable::List => (
+                self.target.lists.contains_key(name),
+                self.stage.lists.contains_key(name),
+            ),
+        };
+        let name = name.to_owned();
+        let kind = match (kind, in_sprite, in_stage) {
+            (ListOrVariable::Variable, false, false) => DiagnosticKind::UnresolvedVariable(name),
+            (ListOrVariable::List, false, false) => DiagnosticKind::UnresolvedList(name),
+            (ListOrVariable::Variable, true, true) if !self.is_stage() => {
+                DiagnosticKind::AmbiguousVariable(name)
+            }
+            (ListOrVariable::List, true, true) if !self.is_stage() => {
+                DiagnosticKind::AmbiguousList(name)
+            }
+            _ => return,
+        };
+        self.report(kind);
+    }
+}
+
+/// Cloud variable is found with or without its prefix, same as when building
+fn has_variable(target: &TargetBuilder, name: &str) -> bool {
+    let is_cloud = |name: &str| {
+        target
+            .variables
+            .get(name)
+            .is_some_and(|var| var.is_cloud_variable)
+    };
+    target.variables.contains_key(name)
+        || is_cloud(&cloud::cloud_name(name))
+        || name.strip_prefix(cloud::CLOUD_PREFIX).is_some_and(is_cloud)
+}
+
+fn is_reporter(opcode: &str) -> bool {
+    opcode::shape_of(opcode).is_some_and(|shape| shape.is_reporter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::{AssetBuilder, CostumeBuilder},
+        block::BlockInputBuilder,
+        blocks::{answer, forever, global_var, list, move_steps, say, set_var_to, var},
+        data::ListBuilder,
+        monitor::MonitorBuilder,
+        resource::Resource,
+        target::{SpriteBuilder, StageBuilder},
+    };
+
+    fn costume() -> CostumeBuilder {
+        let svg = Resource::new("svg".to_owned(), b"<svg/>".to_vec()).unwrap();
+        CostumeBuilder::new(AssetBuilder::new("costume", svg))
+    }
+
+    fn sprite(name: &str, layer_order: u64) -> SpriteBuilder {
+        let mut sprite = SpriteBuilder::default();
+        sprite
+            .target
+            .set_name(name)
+            .set_layer_order(layer_order)
+            .add_costume(costume());
+        sprite
+    }
+
+    /// Project without any mistake with `sprite` in it
+    fn project(sprite: SpriteBuilder) -> ProjectBuilder {
+        let mut project = ProjectBuilder::default();
+        project.add_sprite(sprite);
+        project.stage_builder.target.add_costume(costume());
+        project
+    }
+
+    fn kinds(project: &ProjectBuilder) -> Vec<DiagnosticKind> {
+        project
+            .validate()
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind)
+            .collect()
+    }
+
+    fn score() -> VariableBuilder {
+        VariableBuilder::new(Value::Number(0_i64.into()))
+    }
+
+    #[test]
+    fn valid_project() {
+        assert_eq!(project(sprite("Cat", 1)).validate(), []);
+    }
+
+    #[test]
+    fn costumes() {
+        let mut cat = sprite("Cat", 1);
+        cat.target.costumes.clear();
+        assert_eq!(
+            project(cat).validate(),
+            [Diagnostic {
+                target: "Cat".to_owned(),
+                kind: DiagnosticKind::MissingCostume,
+            }]
+        );
+
+        let mut cat = sprite("Cat", 1);
+        cat.target.set_costume(1);
+        assert_eq!(
+            kinds(&project(cat)),
+            [DiagnosticKind::CostumeOutOfRange {
+                current_costume: 1,
+                costume_count: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn layer_orders() {
+        let mut project = project(sprite("Cat", 1));
+        project.add_sprite(sprite("Dog", 1));
+        project.stage_builder.target.set_layer_order(2);
+        assert_eq!(
+            kinds(&project),
+            [
+                DiagnosticKind::StageLayerOrder(2),
+                DiagnosticKind::LayerOrderCollision {
+                    layer_order: 1,
+                    other_target: "Cat".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_sprite_name() {
+        let mut project = project(sprite("Cat", 1));
+        project.add_sprite(sprite("Cat", 2));
+        assert_eq!(
+            project.validate(),
+            [Diagnostic {
+                target: "Cat".to_owned(),
+                kind: DiagnosticKind::DuplicateSpriteName,
+            }]
+        );
+    }
+
+    #[test]
+    fn unresolved_references() {
+        let mut cat = sprite("Cat", 1);
+        cat.target
+            .add_block_stack(say(global_var("score")))
+            .add_block_stack(list("names"));
+        let mut project = project(cat);
+        project
+            .add_monitor(MonitorBuilder::variable("lives"))
+            .add_monitor(MonitorBuilder::list("items"));
+        assert_eq!(
+            kinds(&project),
+            [
+                DiagnosticKind::UnresolvedVariable("score".to_owned()),
+                DiagnosticKind::UnresolvedList("names".to_owned()),
+                DiagnosticKind::UnresolvedMonitor(MonitorSource::Variable("lives".to_owned())),
+                DiagnosticKind::UnresolvedMonitor(MonitorSource::List("items".to_owned())),
+            ]
+        );
+    }
+
+    #[test]
+    fn same_name_in_sprite_and_stage() {
+        let mut cat = sprite("Cat", 1);
+        cat.target
+            .add_variable("score", score())
+            .add_list("names", ListBuilder::new(vec![]))
+            .add_block_stack(say(var("score")))
+            .add_block_stack(list("names"));
+        let mut project = project(cat);
+        project
+            .stage_builder
+            .target
+            .add_variable("score", score())
+            .add_list("names", ListBuilder::new(vec![]));
+        assert_eq!(
+            kinds(&project),
+            [
+                DiagnosticKind::ShadowedList("names".to_owned()),
+                DiagnosticKind::ShadowedVariable("score".to_owned()),
+                DiagnosticKind::AmbiguousVariable("score".to_owned()),
+                DiagnosticKind::AmbiguousList("names".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn cloud_variables() {
+        let mut cat = sprite("Cat", 1);
+        cat.target.add_variable(
+            "☁ score",
+            VariableBuilder::new_cloud_variable(Value::Number(0_i64.into())),
+        );
+        let mut project = project(cat);
+        let stage = &mut project.stage_builder;
+        stage.target.add_variable(
+            "☁ name",
+            score()
+                .set_value(Value::Text("cat".to_owned()))
+                .set_cloud_variable(true)
+                .clone(),
+        );
+        for i in 0..cloud::MAX_CLOUD_VARIABLES {
+            stage.add_cloud_variable(format!("☁ {i}"), 0.);
+        }
+        assert_eq!(
+            project.validate(),
+            [
+                Diagnostic {
+                    target: "Stage".to_owned(),
+                    kind: DiagnosticKind::TooManyCloudVariables(cloud::MAX_CLOUD_VARIABLES + 1),
+                },
+                Diagnostic {
+                    target: "Stage".to_owned(),
+                    kind: DiagnosticKind::CloudVariableNotNumber("☁ name".to_owned()),
+                },
+                Diagnostic {
+                    target: "Cat".to_owned(),
+                    kind: DiagnosticKind::CloudVariableNotOnStage("☁ score".to_owned()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reporter_as_stack_block() {
+        let mut cat = sprite("Cat", 1);
+        cat.target
+            .add_block_stack(answer())
+            .add_block_stack(say("hi").next(answer()))
+            .add_block_stack(forever(Some(BlockInputBuilder::stack(list("names")))));
+        cat.target.add_list("names", ListBuilder::new(vec![]));
+        assert_eq!(
+            kinds(&project(cat)),
+            [
+                DiagnosticKind::ReporterAsStackBlock(StandardOpCode::sensing_answer.into()),
+                DiagnosticKind::Schema {
+                    opcode: StandardOpCode::control_forever.into(),
+                    violation: SchemaViolation::InputKindMismatch {
+                        input: "SUBSTACK".to_owned(),
+                        expected: schema::InputKind::Substack,
+                    },
+                },
+                DiagnosticKind::ReporterAsStackBlock(StandardOpCode::data_listcontents.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn schema_violations() {
+        let mut missing_field = BlockNormalBuilder::new(StandardOpCode::data_setvariableto);
+        missing_field.add_input("VALUE", BlockInputBuilder::stack(answer()));
+        let mut unknown_input = move_steps(10);
+        let BlockBuilder::Normal(b) = &mut unknown_input.stack[0] else {
+            unreachable!()
+        };
+        b.add_input("DEGREES", BlockInputBuilder::stack(answer()));
+        let mut cat = sprite("Cat", 1);
+        cat.target
+            .add_block_stack(StackBuilder::start(missing_field))
+            .add_block_stack(unknown_input);
+        assert_eq!(
+            kinds(&project(cat)),
+            [
+                DiagnosticKind::Schema {
+                    opcode: StandardOpCode::data_setvariableto.into(),
+                    violation: SchemaViolation::MissingField("VARIABLE".to_owned()),
+                },
+                DiagnosticKind::Schema {
+                    opcode: StandardOpCode::motion_movesteps.into(),
+                    violation: SchemaViolation::UnknownInput("DEGREES".to_owned()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn severity() {
+        let severity = |kind| {
+            Diagnostic {
+                target: "Cat".to_owned(),
+                kind,
+            }
+            .severity()
+        };
+        let schema = |violation| DiagnosticKind::Schema {
+            opcode: StandardOpCode::motion_movesteps.into(),
+            violation,
+        };
+        let errors = [
+            DiagnosticKind::MissingCostume,
+            DiagnosticKind::StageLayerOrder(1),
+            DiagnosticKind::UnresolvedVariable("score".to_owned()),
+            DiagnosticKind::AmbiguousList("names".to_owned()),
+            DiagnosticKind::CloudVariableNotOnStage("☁ score".to_owned()),
+            DiagnosticKind::TooManyCloudVariables(11),
+            DiagnosticKind::DuplicateSpriteName,
+            DiagnosticKind::ReporterAsStackBlock(StandardOpCode::sensing_answer.into()),
+            schema(SchemaViolation::MissingField("VARIABLE".to_owned())),
+        ];
+        for kind in errors {
+            assert_eq!(severity(kind.clone()), Severity::Error, "{kind}");
+        }
+        let warnings = [
+            DiagnosticKind::ShadowedVariable("score".to_owned()),
+            DiagnosticKind::ShadowedList("names".to_owned()),
+            schema(SchemaViolation::UnknownOpCode),
+            schema(SchemaViolation::UnknownInput("DEGREES".to_owned())),
+        ];
+        for kind in warnings {
+            assert_eq!(severity(kind.clone()), Severity::Warning, "{kind}");
+        }
+    }
+
+    #[test]
+    fn cloud_variable_is_found_with_or_without_prefix() {
+        let mut stage = StageBuilder::default();
+        stage.add_cloud_variable("high score", 0.);
+        let mut sprite = SpriteBuilder::default();
+        for name in ["high score", "☁ high score"] {
+            sprite.target.add_block_stack(set_var_to(
+                BlockFieldBuilder::new_with_kind(name.to_owned(), FieldKind::GlobalVariable),
+                global_var(name),
+            ));
+        }
+        let mut project = ProjectBuilder::default();
+        project
+            .set_stage(stage)
+            .add_sprite(sprite)
+            .add_monitor(MonitorBuilder::variable("☁ high score"));
+        let unresolved: Vec<Diagnostic> = project
+            .validate()
+            .into_iter()
+            .filter(|diagnostic| diagnostic.is_unresolved_reference())
+            .collect();
+        assert_eq!(unresolved, vec![]);
+    }
+}
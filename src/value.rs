@@ -0,0 +1,25 @@
+//! Reading numbers and text out of `project.json` values
+
+use sb_sbity::value::{Number, Value};
+
+pub(crate) fn number_to_f64(number: &Number) -> f64 {
+    match number {
+        Number::Int(n) => *n as f64,
+        Number::Float(n) => *n,
+    }
+}
+
+pub(crate) fn number_to_i64(number: &Number) -> i64 {
+    match number {
+        Number::Int(n) => *n,
+        Number::Float(n) => *n as i64,
+    }
+}
+
+/// Text of [`Value`]. Non-text value is written the same way as in `project.json`.
+pub(crate) fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Text(text) => text.clone(),
+        other => serde_json::to_string(other).unwrap(),
+    }
+}